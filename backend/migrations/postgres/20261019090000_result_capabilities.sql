@@ -0,0 +1,2 @@
+alter table upload_benchmarkresult
+    add column capabilities jsonb;
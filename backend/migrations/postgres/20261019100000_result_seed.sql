@@ -0,0 +1,3 @@
+-- Seeds are unsigned 64-bit integers, stored with the same bit pattern
+alter table upload_benchmarkresult
+    add column seed bigint;
@@ -0,0 +1,3 @@
+-- rows uploaded before this migration have no time of the upload, new ones are set by the backend
+alter table upload_benchmarkresult
+    add column created_at timestamptz;
@@ -0,0 +1,10 @@
+-- rows uploaded before this migration are backfilled as legacy
+alter table upload_benchmarkresult
+    add column schema_version   varchar(32) not null default 'legacy',
+    add column frontend_version varchar(64) not null default 'legacy',
+    add column parameters       jsonb;
+
+-- new rows have to state their versions
+alter table upload_benchmarkresult
+    alter column schema_version drop default,
+    alter column frontend_version drop default;
@@ -0,0 +1,37 @@
+-- One row per benchmark of every uploaded result
+create table benchmark_run
+(
+    id         bigserial primary key,
+    result_id  bigint      not null references upload_benchmarkresult (id) on delete cascade,
+    benchmark  varchar(32) not null,
+    -- parameters influencing only this benchmark, e.g. the cache size mode
+    parameters jsonb,
+    duration   real        not null,
+    data       jsonb       not null,
+    unique (result_id, benchmark)
+);
+
+create index benchmark_run_benchmark_idx on benchmark_run (benchmark);
+
+-- existing results are stored in the order in which the frontend ran the benchmarks
+insert into benchmark_run (result_id, benchmark, parameters, duration, data)
+select result.id,
+       benchmark.name,
+       case
+           when benchmark.name = 'CacheSize' and result.parameters is not null
+               then jsonb_build_object('cache_size_mode', result.parameters -> 'cache_size_mode')
+           end,
+       coalesce((result.times ->> (run.position - 1)::int)::real, 0),
+       run.data
+from upload_benchmarkresult result
+         cross join lateral jsonb_array_elements(result.benchmark_results) with ordinality as run(data, position)
+         join (values (1, 'PageSize'),
+                      (2, 'CacheSize'),
+                      (3, 'TlbSize'),
+                      (4, 'CacheAssociativity'),
+                      (5, 'SinglePerformance')) as benchmark(position, name)
+              on benchmark.position = run.position;
+
+alter table upload_benchmarkresult
+    drop column benchmark_results,
+    drop column times;
@@ -0,0 +1,6 @@
+alter table upload_benchmarkresult rename column model to model_raw;
+
+-- filled in by the backend on startup for the results uploaded before the normalisation
+alter table upload_benchmarkresult add column model_normalized varchar(255);
+
+create index upload_benchmarkresult_model_normalized_idx on upload_benchmarkresult (model_normalized);
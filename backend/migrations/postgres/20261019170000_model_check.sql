@@ -0,0 +1,2 @@
+-- comparison of the CPU estimated by the frontend with the entered model, missing for older results
+alter table upload_benchmarkresult add column model_check jsonb;
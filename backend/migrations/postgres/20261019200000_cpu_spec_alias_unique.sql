@@ -0,0 +1,3 @@
+-- a model must not match two specifications by their aliases, even after concurrent changes of the catalogue
+drop index cpu_spec_alias_idx;
+create unique index cpu_spec_alias_key on cpu_spec (alias);
@@ -0,0 +1,28 @@
+-- Same schema as the Postgres migrations produce, JSON is stored as text
+create table upload_benchmarkresult
+(
+    id               integer primary key autoincrement,
+    model            varchar(255) not null,
+    user_agent       varchar(255) not null,
+    b64_charts       text         not null,
+    capabilities     text,
+    seed             bigint,
+    -- RFC 3339, written by the backend so that the values compare as strings
+    created_at       text,
+    schema_version   varchar(32)  not null,
+    frontend_version varchar(64)  not null,
+    parameters       text
+);
+
+create table benchmark_run
+(
+    id         integer primary key autoincrement,
+    result_id  bigint      not null references upload_benchmarkresult (id) on delete cascade,
+    benchmark  varchar(32) not null,
+    parameters text,
+    duration   real        not null,
+    data       text        not null,
+    unique (result_id, benchmark)
+);
+
+create index benchmark_run_benchmark_idx on benchmark_run (benchmark);
@@ -0,0 +1,23 @@
+-- specifications of the CPU models, matched with the results by model_normalized
+create table cpu_spec
+(
+    id                integer primary key autoincrement,
+    manufacturer      varchar(64)  not null,
+    name              varchar(255) not null unique,
+    -- alternative name the results are also matched by
+    alias             varchar(255),
+    cores             bigint       not null,
+    threads           bigint       not null,
+    base_ghz          real         not null,
+    boost_ghz         real         not null,
+    l1_kib            bigint       not null,
+    l2_kib            bigint       not null,
+    l3_mib            bigint,
+    l1_associativity  bigint,
+    page_size         bigint       not null,
+    load_buffer       bigint,
+    l1_dtlb           bigint,
+    microarchitecture varchar(255) not null
+);
+
+create index cpu_spec_alias_idx on cpu_spec (alias);
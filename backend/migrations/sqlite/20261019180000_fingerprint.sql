@@ -0,0 +1,6 @@
+-- filled in by the backend on startup for the results uploaded before the fingerprints
+alter table upload_benchmarkresult add column fingerprint varchar(16);
+-- features of common::fingerprint::Fingerprint, null where missing
+alter table upload_benchmarkresult add column features text;
+
+create index upload_benchmarkresult_fingerprint_idx on upload_benchmarkresult (fingerprint);
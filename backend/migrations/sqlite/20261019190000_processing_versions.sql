@@ -0,0 +1,4 @@
+-- versions of the normalisation rules and of the fingerprint the result was last processed with,
+-- the backend normalizes and fingerprints the results again on startup when they are outdated
+alter table upload_benchmarkresult add column model_rules_version integer;
+alter table upload_benchmarkresult add column fingerprint_version integer;
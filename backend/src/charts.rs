@@ -0,0 +1,95 @@
+use base64::engine::general_purpose::STANDARD;
+use base64::Engine;
+
+use common::chart::svg::render_svg;
+use common::chart::Chart;
+use common::dto::chart::ChartDTO;
+use common::dto::result::ResultDTO;
+
+const SVG_CONTENT_TYPE: &str = "image/svg+xml";
+
+/// Renders a chart for every benchmark result, see `ResultDTO::benchmark_types`
+pub fn render_charts(result: &ResultDTO) -> Vec<ChartDTO> {
+    result
+        .benchmark_types()
+        .into_iter()
+        .zip(result.benchmark_results.iter())
+        .map(|(benchmark, data)| ChartDTO {
+            benchmark,
+            content_type: SVG_CONTENT_TYPE.to_string(),
+            data: STANDARD.encode(render_svg(&Chart::new(benchmark, data))),
+        })
+        .collect()
+}
+
+/// Decodes the image of the chart
+pub fn decode_chart(chart: &ChartDTO) -> Result<Vec<u8>, base64::DecodeError> {
+    STANDARD.decode(&chart.data)
+}
+
+/*
+ * TESTS
+ */
+
+#[cfg(test)]
+mod tests {
+    use serde_json::{json, Value};
+
+    use common::benchmark::BenchmarkType;
+    use common::dto::result::ResultDTO;
+
+    use crate::charts::{decode_chart, render_charts};
+
+    fn result(mut body: Value) -> ResultDTO {
+        body["model"] = json!("model");
+        body["user_agent"] = json!("agent");
+        serde_json::from_value(body).unwrap()
+    }
+
+    #[test]
+    fn every_result_gets_chart() {
+        // prepare
+        let result = result(json!({
+            "benchmark_results": [
+                [{"x": 512, "y": 1}, {"x": 516, "y": 1}],
+                [{"x": 1024, "y": 10}, {"x": 2048, "y": 11}]
+            ],
+            "times": [1.0, 2.0]
+        }));
+
+        // execute
+        let charts = render_charts(&result);
+
+        // assert
+        assert_eq!(charts.len(), 2);
+        assert_eq!(charts[0].benchmark, BenchmarkType::PageSize);
+        assert_eq!(charts[1].benchmark, BenchmarkType::CacheSize);
+        assert_eq!(charts[1].content_type, "image/svg+xml");
+        let svg = String::from_utf8(decode_chart(&charts[1]).unwrap()).unwrap();
+        assert!(svg.starts_with("<svg"));
+        assert!(svg.contains("Cache size"));
+    }
+
+    #[test]
+    fn charts_follow_named_benchmarks() {
+        // prepare
+        let result = result(json!({
+            "benchmark_results": [[{"x": 2, "y": 1}], [{"x": 512, "y": 1}]],
+            "times": [1.0, 2.0],
+            "benchmarks": ["TlbSize", "PageSize"]
+        }));
+
+        // execute
+        let charts = render_charts(&result);
+
+        // assert
+        let benchmarks = charts
+            .iter()
+            .map(|chart| chart.benchmark)
+            .collect::<Vec<_>>();
+        assert_eq!(
+            benchmarks,
+            vec![BenchmarkType::TlbSize, BenchmarkType::PageSize]
+        );
+    }
+}
@@ -0,0 +1,230 @@
+use crate::repository::result::ResultFilter;
+use crate::repository::Repository;
+use common::classification::{
+    catalogue_labels, feature_vector, KnnClassifier, TrainingSample, DEFAULT_NEIGHBOURS,
+    FEATURE_COUNT,
+};
+use log::info;
+use std::collections::HashSet;
+use std::error::Error;
+use std::fs::File;
+use std::io::BufReader;
+use std::path::Path;
+use std::sync::Arc;
+use tokio::sync::Mutex;
+
+/// Classifier trained from the stored results of the CPUs in the catalogue.
+/// It is trained on the first classification and again after the training data changes,
+/// unless a model trained offline by the `classify` tool is loaded.
+#[derive(Default)]
+pub struct ClassifierCache {
+    classifier: Mutex<Option<TrainedClassifier>>,
+    offline: bool,
+}
+
+struct TrainedClassifier {
+    classifier: Arc<KnnClassifier>,
+    /// Models in the catalogue when it was trained, whose results are its training samples
+    catalogue_models: HashSet<String>,
+}
+
+impl ClassifierCache {
+    /// Cache always returning the model written by the `classify` tool
+    pub fn load(path: &Path) -> Result<ClassifierCache, Box<dyn Error>> {
+        let classifier: KnnClassifier = serde_json::from_reader(BufReader::new(File::open(path)?))?;
+        if classifier.feature_count() != FEATURE_COUNT {
+            return Err(format!(
+                "the classifier model has {} features instead of {}",
+                classifier.feature_count(),
+                FEATURE_COUNT
+            )
+            .into());
+        }
+        info!(
+            "Loaded the CPU classifier trained on {} results",
+            classifier.len()
+        );
+
+        Ok(ClassifierCache {
+            classifier: Mutex::new(Some(TrainedClassifier {
+                classifier: Arc::new(classifier),
+                catalogue_models: HashSet::new(),
+            })),
+            offline: true,
+        })
+    }
+
+    pub async fn get_or_train(
+        &self,
+        repository: &dyn Repository,
+    ) -> Result<Arc<KnnClassifier>, Box<dyn Error>> {
+        // held while training, so concurrent requests do not train the same classifier
+        let mut classifier = self.classifier.lock().await;
+        if let Some(trained) = classifier.as_ref() {
+            return Ok(trained.classifier.clone());
+        }
+
+        let trained = train_classifier(repository).await?;
+        info!(
+            "Trained the CPU classifier on {} results",
+            trained.classifier.len()
+        );
+        let trained_classifier = trained.classifier.clone();
+        *classifier = Some(trained);
+
+        Ok(trained_classifier)
+    }
+
+    /// Drops the classifier after a change of the catalogue
+    pub async fn invalidate(&self) {
+        if !self.offline {
+            *self.classifier.lock().await = None;
+        }
+    }
+
+    /// Drops the classifier after a new result of the model, if the result is a training
+    /// sample. Results of the models outside the catalogue don't change the classifier.
+    pub async fn invalidate_model(&self, model_normalized: &str) {
+        if self.offline {
+            return;
+        }
+        let mut classifier = self.classifier.lock().await;
+        if classifier.as_ref().map_or(false, |trained| {
+            trained.catalogue_models.contains(model_normalized)
+        }) {
+            *classifier = None;
+        }
+    }
+}
+
+async fn train_classifier(
+    repository: &dyn Repository,
+) -> Result<TrainedClassifier, Box<dyn Error>> {
+    let specs = repository
+        .find_cpu_specs()
+        .await?
+        .into_iter()
+        .map(|stored| stored.spec)
+        .collect::<Vec<_>>();
+    let labels = catalogue_labels(&specs);
+
+    let mut samples = vec![];
+    repository
+        .for_each_result(&ResultFilter::default(), &mut |result| {
+            if let Some(labels) = labels.get(&result.model_normalized) {
+                samples.push(TrainingSample {
+                    features: feature_vector(&result.result),
+                    labels: labels.clone(),
+                });
+            }
+            Box::pin(async { Ok(()) })
+        })
+        .await?;
+
+    Ok(TrainedClassifier {
+        classifier: Arc::new(KnnClassifier::train(samples, DEFAULT_NEIGHBOURS)),
+        catalogue_models: labels.into_keys().collect(),
+    })
+}
+
+/*
+ * TESTS
+ */
+
+#[cfg(test)]
+mod tests {
+    use std::io::Write;
+    use std::sync::Arc;
+
+    use common::classification::{CpuLabels, KnnClassifier, TrainingSample, FEATURE_COUNT};
+    use common::dto::cpu::CpuSpecDTO;
+
+    use crate::classifier::ClassifierCache;
+    use crate::repository::memory::InMemoryRepository;
+    use crate::repository::CpuSpecRepository;
+
+    fn model_file(feature_count: usize) -> tempfile::NamedTempFile {
+        let classifier = KnnClassifier::train(
+            vec![TrainingSample {
+                features: vec![0.0; feature_count],
+                labels: CpuLabels {
+                    model: "model".to_string(),
+                    microarchitecture: "Zen 2".to_string(),
+                    l1_kib: 32,
+                    l2_kib: 512,
+                    l3_mib: Some(16),
+                },
+            }],
+            1,
+        );
+        let mut file = tempfile::NamedTempFile::new().unwrap();
+        file.write_all(&serde_json::to_vec(&classifier).unwrap())
+            .unwrap();
+        file
+    }
+
+    #[actix_web::test]
+    async fn loaded_model_is_kept() {
+        // prepare
+        let file = model_file(FEATURE_COUNT);
+        let cache = ClassifierCache::load(file.path()).unwrap();
+
+        // execute
+        cache.invalidate().await;
+        let classifier = cache
+            .get_or_train(&InMemoryRepository::default())
+            .await
+            .unwrap();
+
+        // assert
+        assert_eq!(classifier.len(), 1);
+    }
+
+    #[actix_web::test]
+    async fn results_of_catalogue_models_invalidate_classifier() {
+        // prepare
+        let repository = InMemoryRepository::default();
+        let spec = CpuSpecDTO {
+            manufacturer: "AMD".to_string(),
+            name: "AMD Ryzen 7 3700X".to_string(),
+            alias: Some("Ryzen 7 3700X".to_string()),
+            cores: 8,
+            threads: 16,
+            base_ghz: 3.6,
+            boost_ghz: 4.4,
+            l1_kib: 32,
+            l2_kib: 512,
+            l3_mib: Some(32),
+            l1_associativity: Some(8),
+            page_size: 4096,
+            load_buffer: None,
+            l1_dtlb: Some(64),
+            microarchitecture: "Zen 2".to_string(),
+        };
+        repository.insert_cpu_spec(&spec).await.unwrap();
+        let cache = ClassifierCache::default();
+        let trained = cache.get_or_train(&repository).await.unwrap();
+
+        // execute
+        cache.invalidate_model("Intel Core i7-8700").await;
+        let kept = cache.get_or_train(&repository).await.unwrap();
+        cache.invalidate_model("Ryzen 7 3700X").await;
+        let retrained = cache.get_or_train(&repository).await.unwrap();
+
+        // assert
+        assert!(Arc::ptr_eq(&trained, &kept));
+        assert!(!Arc::ptr_eq(&trained, &retrained));
+    }
+
+    #[test]
+    fn model_of_other_features_is_rejected() {
+        // prepare
+        let file = model_file(FEATURE_COUNT - 1);
+
+        // execute
+        let cache = ClassifierCache::load(file.path());
+
+        // assert
+        assert!(cache.is_err());
+    }
+}
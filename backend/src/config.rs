@@ -13,6 +13,14 @@ pub struct BackendConfig {
     pub port: u16,
     pub database_connection_count: u32,
     pub database_url: String,
+    /// Path of the classifier model written by the `classify` tool,
+    /// the classifier is trained from the stored results without it
+    #[serde(default)]
+    pub classifier_model: Option<String>,
+    /// Token required as `Authorization: Bearer <token>` by the endpoints changing the CPU catalogue,
+    /// the endpoints are disabled without it or with an empty one
+    #[serde(default)]
+    pub admin_token: Option<String>,
 }
 
 pub fn read_config(custom_file_path: Option<&Path>) -> BackendConfig {
@@ -54,11 +62,19 @@ pub fn read_config(custom_file_path: Option<&Path>) -> BackendConfig {
 mod tests {
     use crate::config::read_config;
     use std::io::Write;
+    use std::path::Path;
 
     #[test]
     fn development_config_parses() {
+        // prepare
+        // the backend is started from the workspace root, the tests from the crate directory
+        let path = Path::new(concat!(
+            env!("CARGO_MANIFEST_DIR"),
+            "/../config/backend.yml"
+        ));
+
         // execute
-        let config = read_config(None);
+        let config = read_config(Some(path));
 
         // assert
         assert_eq!(config.bind_address, "127.0.0.1")
@@ -1,22 +1,71 @@
+use crate::classifier::ClassifierCache;
 use crate::config::BackendConfig;
-use sqlx::postgres::PgPoolOptions;
-use sqlx::{Pool, Postgres};
+use crate::error::ApiError;
+use crate::repository::{connect_repository, Repository};
+use crate::suggestions::KnownModelCache;
+use actix_web::http::header;
+use actix_web::HttpRequest;
 use std::error::Error;
+use std::path::Path;
+use std::sync::Arc;
 
 #[derive(Clone)]
 pub struct BackendContext {
-    pub connection_pool: Pool<Postgres>,
+    pub repository: Arc<dyn Repository>,
+    pub classifier: Arc<ClassifierCache>,
+    pub known_models: Arc<KnownModelCache>,
+    /// Token of the admin endpoints, which are disabled without it
+    pub admin_token: Option<String>,
 }
 
-pub async fn build_context(config: &BackendConfig) -> Result<BackendContext, Box<dyn Error>> {
-    let pool = PgPoolOptions::new()
-        .max_connections(config.database_connection_count)
-        .connect(config.database_url.as_str())
-        .await?;
+impl BackendContext {
+    /// Fails unless the request carries the configured admin token
+    pub fn authorize_admin(&self, request: &HttpRequest) -> Result<(), ApiError> {
+        let Some(token) = self
+            .admin_token
+            .as_deref()
+            .filter(|token| !token.is_empty())
+        else {
+            return Err(ApiError::Forbidden(
+                "the endpoint is disabled, as no admin token is configured".to_string(),
+            ));
+        };
+
+        let bearer = request
+            .headers()
+            .get(header::AUTHORIZATION)
+            .and_then(|value| value.to_str().ok())
+            .and_then(|value| value.strip_prefix("Bearer "));
+        match bearer {
+            Some(bearer) if tokens_match(bearer, token) => Ok(()),
+            _ => Err(ApiError::Unauthorized(
+                "the endpoint requires the admin token".to_string(),
+            )),
+        }
+    }
+}
 
-    sqlx::migrate!().run(&pool).await?;
+pub async fn build_context(config: &BackendConfig) -> Result<BackendContext, Box<dyn Error>> {
+    let repository = connect_repository(config).await?;
+    let classifier = match &config.classifier_model {
+        Some(path) => ClassifierCache::load(Path::new(path))?,
+        None => ClassifierCache::default(),
+    };
 
     Ok(BackendContext {
-        connection_pool: pool,
+        repository,
+        classifier: Arc::new(classifier),
+        known_models: Arc::default(),
+        admin_token: config.admin_token.clone(),
     })
 }
+
+/// Compares all the bytes, so the time taken does not tell how much of the token was guessed
+fn tokens_match(given: &str, token: &str) -> bool {
+    given.len() == token.len()
+        && given
+            .bytes()
+            .zip(token.bytes())
+            .fold(0, |difference, (a, b)| difference | (a ^ b))
+            == 0
+}
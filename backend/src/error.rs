@@ -0,0 +1,193 @@
+use actix_web::error::{JsonPayloadError, QueryPayloadError};
+use actix_web::http::StatusCode;
+use actix_web::{HttpRequest, HttpResponse, ResponseError};
+use common::dto::error::{ErrorDTO, FieldErrorDTO};
+use log::error;
+
+use crate::repository::cpu::AliasTaken;
+use std::error::Error;
+use std::fmt::{Display, Formatter};
+
+/// Error returned by the handlers, rendered as an `ErrorDTO`
+#[derive(Debug)]
+pub enum ApiError {
+    /// The request could not be parsed
+    BadRequest(String),
+    /// The request body exceeds the configured limit
+    PayloadTooLarge(String),
+    /// The request lacks the admin token or carries a wrong one
+    Unauthorized(String),
+    /// The endpoint is disabled by the configuration
+    Forbidden(String),
+    /// The request was parsed, but some of its fields are invalid
+    Validation(Vec<FieldErrorDTO>),
+    /// The request collides with the stored data, e.g. a duplicate name
+    Conflict(String),
+    /// Failure of the backend, the details are only logged
+    Internal(String),
+}
+
+impl ApiError {
+    fn kind(&self) -> &'static str {
+        match self {
+            ApiError::BadRequest(_) => "bad_request",
+            ApiError::PayloadTooLarge(_) => "payload_too_large",
+            ApiError::Unauthorized(_) => "unauthorized",
+            ApiError::Forbidden(_) => "forbidden",
+            ApiError::Validation(_) => "validation_failed",
+            ApiError::Conflict(_) => "conflict",
+            ApiError::Internal(_) => "internal_error",
+        }
+    }
+}
+
+impl Display for ApiError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ApiError::BadRequest(message)
+            | ApiError::PayloadTooLarge(message)
+            | ApiError::Unauthorized(message)
+            | ApiError::Forbidden(message)
+            | ApiError::Conflict(message) => {
+                write!(f, "{}", message)
+            }
+            ApiError::Validation(fields) => match fields.len() {
+                1 => write!(f, "the request has 1 invalid field"),
+                count => write!(f, "the request has {} invalid fields", count),
+            },
+            ApiError::Internal(_) => write!(f, "the request could not be processed"),
+        }
+    }
+}
+
+impl ResponseError for ApiError {
+    fn status_code(&self) -> StatusCode {
+        match self {
+            ApiError::BadRequest(_) => StatusCode::BAD_REQUEST,
+            ApiError::PayloadTooLarge(_) => StatusCode::PAYLOAD_TOO_LARGE,
+            ApiError::Unauthorized(_) => StatusCode::UNAUTHORIZED,
+            ApiError::Forbidden(_) => StatusCode::FORBIDDEN,
+            ApiError::Validation(_) => StatusCode::UNPROCESSABLE_ENTITY,
+            ApiError::Conflict(_) => StatusCode::CONFLICT,
+            ApiError::Internal(_) => StatusCode::INTERNAL_SERVER_ERROR,
+        }
+    }
+
+    fn error_response(&self) -> HttpResponse {
+        if let ApiError::Internal(details) = self {
+            error!("Request failed: {}", details);
+        }
+
+        HttpResponse::build(self.status_code()).json(ErrorDTO {
+            error: self.kind().to_string(),
+            message: self.to_string(),
+            fields: match self {
+                ApiError::Validation(fields) => fields.clone(),
+                _ => vec![],
+            },
+        })
+    }
+}
+
+impl From<Box<dyn Error>> for ApiError {
+    fn from(error: Box<dyn Error>) -> Self {
+        // concurrent changes of the catalogue may pass the checks of the handlers
+        match error.downcast::<AliasTaken>() {
+            Ok(taken) => ApiError::Conflict(taken.to_string()),
+            Err(error) => ApiError::Internal(error.to_string()),
+        }
+    }
+}
+
+impl From<base64::DecodeError> for ApiError {
+    fn from(error: base64::DecodeError) -> Self {
+        ApiError::Internal(error.to_string())
+    }
+}
+
+/// Replaces the plain text errors of the JSON extractor
+pub fn json_error_handler(error: JsonPayloadError, _: &HttpRequest) -> actix_web::Error {
+    match error {
+        JsonPayloadError::Overflow { .. } | JsonPayloadError::OverflowKnownLength { .. } => {
+            ApiError::PayloadTooLarge(error.to_string())
+        }
+        error => ApiError::BadRequest(error.to_string()),
+    }
+    .into()
+}
+
+/// Replaces the plain text errors of the query extractor
+pub fn query_error_handler(error: QueryPayloadError, _: &HttpRequest) -> actix_web::Error {
+    ApiError::BadRequest(error.to_string()).into()
+}
+
+/*
+ * TESTS
+ */
+
+#[cfg(test)]
+mod tests {
+    use std::error::Error;
+
+    use actix_web::body::to_bytes;
+    use actix_web::http::StatusCode;
+    use actix_web::ResponseError;
+
+    use common::dto::error::{ErrorDTO, FieldErrorDTO};
+
+    use crate::error::ApiError;
+    use crate::repository::cpu::AliasTaken;
+
+    #[actix_web::test]
+    async fn validation_error_lists_fields() {
+        // prepare
+        let error = ApiError::Validation(vec![FieldErrorDTO {
+            field: "model".to_string(),
+            message: "must not be empty".to_string(),
+        }]);
+
+        // execute
+        let response = error.error_response();
+
+        // assert
+        assert_eq!(response.status(), StatusCode::UNPROCESSABLE_ENTITY);
+        let body = to_bytes(response.into_body()).await.unwrap();
+        let body = serde_json::from_slice::<ErrorDTO>(&body).unwrap();
+        assert_eq!(body.error, "validation_failed");
+        assert_eq!(body.message, "the request has 1 invalid field");
+        assert_eq!(body.fields.len(), 1);
+        assert_eq!(body.fields[0].field, "model");
+    }
+
+    #[test]
+    fn taken_alias_is_conflict() {
+        // prepare
+        let error: Box<dyn Error> = Box::new(AliasTaken {
+            alias: "Kaby Lake".to_string(),
+        });
+
+        // execute
+        let error = ApiError::from(error);
+
+        // assert
+        assert_eq!(error.status_code(), StatusCode::CONFLICT);
+        assert!(error.to_string().contains("Kaby Lake"));
+    }
+
+    #[actix_web::test]
+    async fn internal_error_hides_details() {
+        // prepare
+        let error = ApiError::Internal("connection refused".to_string());
+
+        // execute
+        let response = error.error_response();
+
+        // assert
+        assert_eq!(response.status(), StatusCode::INTERNAL_SERVER_ERROR);
+        let body = to_bytes(response.into_body()).await.unwrap();
+        let body = serde_json::from_slice::<ErrorDTO>(&body).unwrap();
+        assert_eq!(body.error, "internal_error");
+        assert!(!body.message.contains("connection refused"));
+        assert!(body.fields.is_empty());
+    }
+}
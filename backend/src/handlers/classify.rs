@@ -0,0 +1,24 @@
+use crate::context::BackendContext;
+use crate::error::ApiError;
+use crate::validation::validate_result;
+use actix_web::web::{Data, Json};
+use actix_web::{post, HttpResponse, Responder};
+use common::classification::feature_vector;
+use common::dto::result::ResultDTO;
+
+/// Candidate models, microarchitectures and cache sizes of the CPU which measured the result,
+/// voted by the most similar stored results of the CPUs in the catalogue
+#[post("/api/classify")]
+pub async fn classify(
+    body: Json<ResultDTO>,
+    context: Data<BackendContext>,
+) -> Result<impl Responder, ApiError> {
+    validate_result(&body).map_err(ApiError::Validation)?;
+
+    let classifier = context
+        .classifier
+        .get_or_train(context.repository.as_ref())
+        .await?;
+
+    Ok(HttpResponse::Ok().json(classifier.classify(&feature_vector(&body))))
+}
@@ -0,0 +1,253 @@
+use crate::context::BackendContext;
+use crate::error::ApiError;
+use crate::validation::{find_shared_alias, validate_cpu_import, validate_cpu_spec};
+use actix_web::web::{Data, Json, Path, Query};
+use actix_web::{delete, get, post, put, HttpRequest, HttpResponse, Responder};
+use common::cpu::{dedupe_cpu_specs, find_cpu_spec, parse_cpu_specs};
+use common::dto::cpu::{CpuImportDTO, CpuSpecDTO, ModelSuggestionsDTO};
+use common::dto::error::FieldErrorDTO;
+use common::model::{model_similarity, normalize_model, SUGGESTION_THRESHOLD};
+use serde::Deserialize;
+
+const DEFAULT_SUGGESTIONS: usize = 10;
+const MAX_SUGGESTIONS: usize = 50;
+
+#[derive(Deserialize)]
+pub struct SuggestionQuery {
+    model: String,
+    limit: Option<usize>,
+}
+
+#[get("/api/cpu")]
+pub async fn list_cpu_specs(context: Data<BackendContext>) -> Result<impl Responder, ApiError> {
+    Ok(HttpResponse::Ok().json(context.repository.find_cpu_specs().await?))
+}
+
+/// Normalized models of the results without a CPU specification, to be added to the catalogue
+#[get("/api/cpu/unmatched")]
+pub async fn list_unmatched_models(
+    context: Data<BackendContext>,
+) -> Result<impl Responder, ApiError> {
+    Ok(HttpResponse::Ok().json(context.repository.find_unmatched_models().await?))
+}
+
+/// Known models similar to the typed one, for the autocomplete of the model input.
+/// The models of the CPU catalogue and the common models of the previous results are suggested.
+#[get("/api/cpu/suggest")]
+pub async fn suggest_models(
+    query: Query<SuggestionQuery>,
+    context: Data<BackendContext>,
+) -> Result<impl Responder, ApiError> {
+    let limit = query
+        .limit
+        .unwrap_or(DEFAULT_SUGGESTIONS)
+        .clamp(1, MAX_SUGGESTIONS);
+    let model_normalized = normalize_model(&query.model);
+    let known = context
+        .known_models
+        .get_or_load(context.repository.as_ref())
+        .await?;
+
+    let mut suggestions = known
+        .iter()
+        .map(|known| (model_similarity(&model_normalized, &known.model), known))
+        .filter(|(similarity, _)| *similarity >= SUGGESTION_THRESHOLD)
+        .collect::<Vec<_>>();
+    suggestions.sort_by(|(a_similarity, a), (b_similarity, b)| {
+        b_similarity
+            .total_cmp(a_similarity)
+            .then(b.result_count.cmp(&a.result_count))
+            .then(a.model.cmp(&b.model))
+    });
+
+    Ok(HttpResponse::Ok().json(ModelSuggestionsDTO {
+        known: known
+            .iter()
+            .any(|known| known.model.eq_ignore_ascii_case(&model_normalized)),
+        suggestions: suggestions
+            .into_iter()
+            .take(limit)
+            .map(|(_, known)| known.clone())
+            .collect(),
+        model_normalized,
+    }))
+}
+
+#[get("/api/cpu/{id}")]
+pub async fn get_cpu_spec(
+    path: Path<i64>,
+    context: Data<BackendContext>,
+) -> Result<impl Responder, ApiError> {
+    Ok(
+        match context.repository.find_cpu_spec(path.into_inner()).await? {
+            Some(spec) => HttpResponse::Ok().json(spec),
+            None => HttpResponse::NotFound().finish(),
+        },
+    )
+}
+
+#[post("/api/cpu")]
+pub async fn create_cpu_spec(
+    request: HttpRequest,
+    body: Json<CpuSpecDTO>,
+    context: Data<BackendContext>,
+) -> Result<impl Responder, ApiError> {
+    context.authorize_admin(&request)?;
+    validate_cpu_spec(&body).map_err(ApiError::Validation)?;
+
+    let catalogue = context.repository.find_cpu_specs().await?;
+    if let Some(other) = find_shared_alias(&body, catalogue.iter().map(|stored| &stored.spec)) {
+        return Err(alias_taken(&body, other));
+    }
+
+    let Some(id) = context.repository.insert_cpu_spec(&body).await? else {
+        return Err(name_taken(&body));
+    };
+    context.classifier.invalidate().await;
+    context.known_models.invalidate().await;
+    let spec = context.repository.find_cpu_spec(id).await?;
+
+    Ok(HttpResponse::Created().json(spec))
+}
+
+#[put("/api/cpu/{id}")]
+pub async fn update_cpu_spec(
+    request: HttpRequest,
+    path: Path<i64>,
+    body: Json<CpuSpecDTO>,
+    context: Data<BackendContext>,
+) -> Result<impl Responder, ApiError> {
+    context.authorize_admin(&request)?;
+    validate_cpu_spec(&body).map_err(ApiError::Validation)?;
+
+    let id = path.into_inner();
+    let catalogue = context.repository.find_cpu_specs().await?;
+    let others = catalogue
+        .iter()
+        .filter(|stored| stored.id != id)
+        .map(|stored| &stored.spec);
+    if let Some(other) = find_shared_alias(&body, others) {
+        return Err(alias_taken(&body, other));
+    }
+    if !context.repository.update_cpu_spec(id, &body).await? {
+        // the update is skipped both for missing specifications and for duplicate names
+        return match context.repository.find_cpu_spec(id).await? {
+            Some(_) => Err(name_taken(&body)),
+            None => Ok(HttpResponse::NotFound().finish()),
+        };
+    }
+    context.classifier.invalidate().await;
+    context.known_models.invalidate().await;
+
+    Ok(HttpResponse::Ok().json(context.repository.find_cpu_spec(id).await?))
+}
+
+#[delete("/api/cpu/{id}")]
+pub async fn delete_cpu_spec(
+    request: HttpRequest,
+    path: Path<i64>,
+    context: Data<BackendContext>,
+) -> Result<impl Responder, ApiError> {
+    context.authorize_admin(&request)?;
+
+    Ok(
+        if context
+            .repository
+            .delete_cpu_spec(path.into_inner())
+            .await?
+        {
+            context.classifier.invalidate().await;
+            context.known_models.invalidate().await;
+            HttpResponse::NoContent().finish()
+        } else {
+            HttpResponse::NotFound().finish()
+        },
+    )
+}
+
+/// Imports the semicolon-separated catalogue in the format of `classification/cpus.csv`.
+/// Specifications with the same names are replaced, the unchanged ones are skipped.
+/// Repeated rows and the aliases of several imported specifications are dropped.
+#[post("/api/cpu/import")]
+pub async fn import_cpu_specs(
+    request: HttpRequest,
+    body: String,
+    context: Data<BackendContext>,
+) -> Result<impl Responder, ApiError> {
+    context.authorize_admin(&request)?;
+    let specs = dedupe_cpu_specs(parse_cpu_specs(&body).map_err(ApiError::BadRequest)?);
+
+    let mut errors = specs
+        .iter()
+        .enumerate()
+        .filter_map(|(i, spec)| validate_cpu_spec(spec).err().map(|errors| (i, errors)))
+        .flat_map(|(i, errors)| {
+            errors.into_iter().map(move |error| FieldErrorDTO {
+                field: format!("[{}].{}", i, error.field),
+                message: error.message,
+            })
+        })
+        .collect::<Vec<_>>();
+    let catalogue = context
+        .repository
+        .find_cpu_specs()
+        .await?
+        .into_iter()
+        .map(|stored| stored.spec)
+        .collect::<Vec<_>>();
+    if let Err(conflicts) = validate_cpu_import(&specs, &catalogue) {
+        errors.extend(conflicts);
+    }
+    if !errors.is_empty() {
+        return Err(ApiError::Validation(errors));
+    }
+
+    let changed = specs
+        .into_iter()
+        .filter(|spec| !catalogue.contains(spec))
+        .collect::<Vec<_>>();
+    if !changed.is_empty() {
+        context.repository.import_cpu_specs(&changed).await?;
+        context.classifier.invalidate().await;
+        context.known_models.invalidate().await;
+    }
+
+    Ok(HttpResponse::Ok().json(CpuImportDTO {
+        imported: changed.len(),
+    }))
+}
+
+/// CPU specification matching the normalized model of the result
+#[get("/api/result/{id}/cpu")]
+pub async fn get_result_cpu_spec(
+    path: Path<i64>,
+    context: Data<BackendContext>,
+) -> Result<impl Responder, ApiError> {
+    let Some(result) = context.repository.find_result(path.into_inner()).await? else {
+        return Ok(HttpResponse::NotFound().finish());
+    };
+    let specs = context
+        .repository
+        .find_model_cpu_specs(&result.model_normalized)
+        .await?;
+
+    Ok(match find_cpu_spec(&specs, &result.model_normalized) {
+        Some(spec) => HttpResponse::Ok().json(spec),
+        None => HttpResponse::NotFound().finish(),
+    })
+}
+
+fn alias_taken(spec: &CpuSpecDTO, other: &CpuSpecDTO) -> ApiError {
+    ApiError::Conflict(format!(
+        "the alias '{}' is already used by the CPU specification '{}'",
+        spec.alias.as_deref().unwrap_or_default(),
+        other.name
+    ))
+}
+
+fn name_taken(spec: &CpuSpecDTO) -> ApiError {
+    ApiError::Conflict(format!(
+        "a CPU specification named '{}' already exists",
+        spec.name
+    ))
+}
@@ -0,0 +1,221 @@
+use crate::context::BackendContext;
+use crate::handlers::result::FilterQuery;
+use actix_web::error::ErrorInternalServerError;
+use actix_web::web::{Bytes, Data, Query};
+use actix_web::{get, rt, HttpResponse, Responder};
+use common::chart::parse_points;
+use common::dto::result::StoredResultDTO;
+use futures::stream;
+use log::error;
+use serde::Deserialize;
+use std::error::Error;
+use tokio::sync::mpsc;
+
+/// Number of serialized results buffered between the database and the client
+const EXPORT_BUFFER: usize = 16;
+const CSV_HEADER: &str = "id,created_at,model,model_normalized,user_agent,benchmark,x,y\n";
+
+#[derive(Deserialize, Clone, Copy, Default)]
+#[serde(rename_all = "lowercase")]
+pub enum ExportFormat {
+    /// One `StoredResultDTO` per line
+    #[default]
+    Ndjson,
+    /// One row per data point
+    Csv,
+}
+
+#[derive(Deserialize)]
+pub struct ExportQuery {
+    #[serde(flatten)]
+    filter: FilterQuery,
+    #[serde(default)]
+    format: ExportFormat,
+}
+
+/// Streams all the results matching the filter. Only a few of them are kept
+/// in memory at once, so the whole dataset can be exported.
+#[get("/api/export")]
+pub async fn export(query: Query<ExportQuery>, context: Data<BackendContext>) -> impl Responder {
+    let query = query.into_inner();
+    let format = query.format;
+    let repository = context.repository.clone();
+    let (sender, receiver) = mpsc::channel::<Result<Bytes, actix_web::Error>>(EXPORT_BUFFER);
+
+    rt::spawn(async move {
+        if let ExportFormat::Csv = format {
+            if sender.send(Ok(Bytes::from(CSV_HEADER))).await.is_err() {
+                return;
+            }
+        }
+
+        let filter = query.filter.filter();
+        let outcome = repository
+            .for_each_result(&filter, &mut |result| {
+                let sender = sender.clone();
+                Box::pin(async move {
+                    let chunk = match format {
+                        ExportFormat::Ndjson => ndjson_line(&result)?,
+                        ExportFormat::Csv => csv_rows(&result),
+                    };
+                    // fails when the client disconnects, which stops the query
+                    sender.send(Ok(Bytes::from(chunk))).await?;
+                    Ok::<(), Box<dyn Error>>(())
+                })
+            })
+            .await;
+
+        if let Err(e) = outcome {
+            error!("Export failed: {}", e);
+            // aborts the response, so the client does not mistake it for a complete export
+            let _ = sender
+                .send(Err(ErrorInternalServerError("export failed")))
+                .await;
+        }
+    });
+
+    let content_type = match format {
+        ExportFormat::Ndjson => "application/x-ndjson",
+        ExportFormat::Csv => "text/csv",
+    };
+    let body = stream::unfold(receiver, |mut receiver| async move {
+        receiver.recv().await.map(|chunk| (chunk, receiver))
+    });
+
+    HttpResponse::Ok()
+        .content_type(content_type)
+        .streaming(body)
+}
+
+fn ndjson_line(result: &StoredResultDTO) -> Result<String, serde_json::Error> {
+    let mut line = serde_json::to_string(result)?;
+    line.push('\n');
+    Ok(line)
+}
+
+/// Rows of all data points of the result
+fn csv_rows(result: &StoredResultDTO) -> String {
+    let prefix = [
+        result.id.to_string(),
+        // empty for results uploaded before the time was recorded
+        result
+            .created_at
+            .map(|created_at| created_at.to_rfc3339())
+            .unwrap_or_default(),
+        csv_field(&result.result.model),
+        csv_field(&result.model_normalized),
+        csv_field(&result.result.user_agent),
+    ]
+    .join(",");
+    let prefix = prefix.as_str();
+
+    result
+        .result
+        .benchmark_types()
+        .into_iter()
+        .zip(result.result.benchmark_results.iter())
+        .flat_map(|(benchmark, points)| {
+            parse_points(points)
+                .into_iter()
+                .map(move |(x, y)| format!("{},{:?},{},{}\n", prefix, benchmark, x, y))
+        })
+        .collect()
+}
+
+fn csv_field(value: &str) -> String {
+    if value.contains([',', '"', '\n', '\r']) {
+        format!("\"{}\"", value.replace('"', "\"\""))
+    } else {
+        value.to_string()
+    }
+}
+
+/*
+ * TESTS
+ */
+
+#[cfg(test)]
+mod tests {
+    use chrono::{TimeZone, Utc};
+    use serde_json::json;
+
+    use common::dto::result::{ResultDTO, StoredResultDTO, SCHEMA_VERSION};
+
+    use crate::handlers::export::{csv_field, csv_rows, ndjson_line};
+
+    fn stored_result() -> StoredResultDTO {
+        StoredResultDTO {
+            id: 3,
+            created_at: Some(Utc.with_ymd_and_hms(2023, 5, 1, 12, 0, 0).unwrap()),
+            model_normalized: "Intel(R) Core(TM) i7".to_string(),
+            result: ResultDTO {
+                model: "Intel(R) Core(TM) i7, \"K\"".to_string(),
+                user_agent: "agent".to_string(),
+                benchmark_results: vec![
+                    json!([{"x": 512, "y": 1}, {"x": 516, "y": 2}]),
+                    json!({"points": [{"x": 1024, "y": 5}], "boundaries": []}),
+                ],
+                times: vec![1.0, 2.0],
+                benchmarks: vec![],
+                capabilities: None,
+                seed: Some(7),
+                schema_version: SCHEMA_VERSION.to_string(),
+                frontend_version: "0.1.0".to_string(),
+                parameters: None,
+                model_check: None,
+            },
+        }
+    }
+
+    #[test]
+    fn csv_has_row_per_data_point() {
+        // execute
+        let rows = csv_rows(&stored_result());
+
+        // assert
+        let prefix =
+            "3,2023-05-01T12:00:00+00:00,\"Intel(R) Core(TM) i7, \"\"K\"\"\",Intel(R) Core(TM) i7,agent";
+        assert_eq!(
+            rows,
+            format!(
+                "{0},PageSize,512,1\n{0},PageSize,516,2\n{0},CacheSize,1024,5\n",
+                prefix
+            )
+        );
+    }
+
+    #[test]
+    fn csv_leaves_unknown_upload_time_empty() {
+        // prepare
+        let legacy = StoredResultDTO {
+            created_at: None,
+            ..stored_result()
+        };
+
+        // execute
+        let rows = csv_rows(&legacy);
+
+        // assert
+        assert!(rows.starts_with("3,,"));
+    }
+
+    #[test]
+    fn ndjson_line_is_single_line() {
+        // execute
+        let line = ndjson_line(&stored_result()).unwrap();
+
+        // assert
+        assert!(line.ends_with('\n'));
+        assert_eq!(line.matches('\n').count(), 1);
+        let parsed = serde_json::from_str::<StoredResultDTO>(&line).unwrap();
+        assert_eq!(parsed.id, 3);
+        assert_eq!(parsed.result.seed, Some(7));
+    }
+
+    #[test]
+    fn plain_csv_fields_are_not_quoted() {
+        // execute & assert
+        assert_eq!(csv_field("plain"), "plain");
+        assert_eq!(csv_field("a\nb"), "\"a\nb\"");
+    }
+}
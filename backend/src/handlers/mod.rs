@@ -1,5 +1,8 @@
 use actix_web::web;
 
+mod classify;
+mod cpu;
+mod export;
 mod misc;
 mod result;
 
@@ -7,4 +10,829 @@ pub fn register_handlers(cfg: &mut web::ServiceConfig) {
     cfg.service(misc::ping);
 
     cfg.service(result::upload);
+    cfg.service(result::list_results);
+    cfg.service(result::get_result);
+    cfg.service(result::get_charts);
+    cfg.service(result::get_chart_image);
+    cfg.service(result::find_similar);
+    cfg.service(result::find_by_fingerprint);
+
+    cfg.service(export::export);
+
+    cfg.service(classify::classify);
+
+    // the fixed paths are registered before the ones with an id
+    cfg.service(cpu::list_cpu_specs);
+    cfg.service(cpu::list_unmatched_models);
+    cfg.service(cpu::suggest_models);
+    cfg.service(cpu::import_cpu_specs);
+    cfg.service(cpu::create_cpu_spec);
+    cfg.service(cpu::get_cpu_spec);
+    cfg.service(cpu::update_cpu_spec);
+    cfg.service(cpu::delete_cpu_spec);
+    cfg.service(cpu::get_result_cpu_spec);
+}
+
+/*
+ * TESTS
+ */
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Arc;
+
+    use actix_web::http::{header, StatusCode};
+    use actix_web::web::{Data, ServiceConfig};
+    use actix_web::{test, App};
+    use serde_json::{json, Value};
+
+    use common::dto::chart::ChartDTO;
+    use common::dto::classification::ClassificationDTO;
+    use common::dto::cpu::{
+        CpuImportDTO, ModelSuggestionsDTO, StoredCpuSpecDTO, UnmatchedModelDTO,
+    };
+    use common::dto::error::ErrorDTO;
+    use common::dto::fingerprint::{FingerprintMatchDTO, SimilarResultsDTO};
+    use common::dto::result::{ModelCheckDTO, ResultPageDTO, StoredResultDTO, SCHEMA_VERSION};
+    use common::fingerprint::DistanceMetric;
+
+    use crate::context::BackendContext;
+    use crate::handlers::register_handlers;
+    use crate::repository::memory::InMemoryRepository;
+    use crate::{configure_server, MAX_PAYLOAD_SIZE};
+
+    const ADMIN_TOKEN: &str = "admin-token";
+
+    /// Configures the application the same way as `main`, but with an empty in-memory repository
+    fn backend() -> impl FnOnce(&mut ServiceConfig) {
+        backend_with_admin_token(Some(ADMIN_TOKEN))
+    }
+
+    fn backend_with_admin_token(admin_token: Option<&str>) -> impl FnOnce(&mut ServiceConfig) {
+        let context = BackendContext {
+            repository: Arc::new(InMemoryRepository::default()),
+            classifier: Arc::default(),
+            known_models: Arc::default(),
+            admin_token: admin_token.map(str::to_string),
+        };
+
+        move |cfg| {
+            cfg.app_data(Data::new(context));
+            configure_server(cfg);
+            register_handlers(cfg);
+        }
+    }
+
+    fn result(model: &str, user_agent: &str, benchmarks: &[&str]) -> Value {
+        json!({
+            "model": model,
+            "user_agent": user_agent,
+            "benchmark_results": benchmarks.iter().map(|_| json!([{"x": 512, "y": 3}, {"x": 1024, "y": 7}])).collect::<Vec<_>>(),
+            "times": benchmarks.iter().map(|_| 1.5).collect::<Vec<_>>(),
+            "benchmarks": benchmarks,
+            "schema_version": SCHEMA_VERSION,
+            "frontend_version": "0.1.0",
+        })
+    }
+
+    fn cpu_spec(name: &str, alias: Option<&str>) -> Value {
+        json!({
+            "manufacturer": "Intel",
+            "name": name,
+            "alias": alias,
+            "cores": 6,
+            "threads": 12,
+            "base_ghz": 3.2,
+            "boost_ghz": 4.6,
+            "l1_kib": 32,
+            "l2_kib": 256,
+            "l3_mib": 12,
+            "l1_associativity": 8,
+            "page_size": 4096,
+            "load_buffer": 72,
+            "l1_dtlb": 64,
+            "microarchitecture": "Coffee Lake",
+        })
+    }
+
+    /// Result with a single associativity run, slower by `slowdown` after `ways` cache lines
+    fn associativity(model: &str, ways: u32, slowdown: u32) -> Value {
+        let points = (1..32)
+            .map(|lines| json!({"x": lines, "y": if lines <= ways { 10 } else { 10 * slowdown }}))
+            .collect::<Vec<_>>();
+        json!({
+            "model": model,
+            "user_agent": "agent",
+            "benchmark_results": [points],
+            "times": [1.0],
+            "benchmarks": ["CacheAssociativity"],
+            "schema_version": SCHEMA_VERSION,
+            "frontend_version": "0.1.0",
+        })
+    }
+
+    fn admin(request: test::TestRequest) -> test::TestRequest {
+        request.insert_header((header::AUTHORIZATION, format!("Bearer {}", ADMIN_TOKEN)))
+    }
+
+    fn upload(body: &Value) -> test::TestRequest {
+        test::TestRequest::post()
+            .uri("/api/result/upload")
+            .set_json(body)
+    }
+
+    #[actix_web::test]
+    async fn ping_responds() {
+        // prepare
+        let app = test::init_service(App::new().configure(backend())).await;
+
+        // execute
+        let request = test::TestRequest::get().uri("/api/misc/ping").to_request();
+        let response = test::call_service(&app, request).await;
+
+        // assert
+        assert_eq!(response.status(), StatusCode::OK);
+    }
+
+    #[actix_web::test]
+    async fn uploaded_result_is_stored() {
+        // prepare
+        let app = test::init_service(App::new().configure(backend())).await;
+        let body = result(
+            "Itel Core i7-8700 CPU @ 3.20GHz",
+            "Mozilla Firefox/110.0",
+            &["TlbSize"],
+        );
+
+        // execute
+        let response = test::call_service(&app, upload(&body).to_request()).await;
+        let request = test::TestRequest::get().uri("/api/result/1").to_request();
+        let stored: StoredResultDTO = test::call_and_read_body_json(&app, request).await;
+
+        // assert
+        assert_eq!(response.status(), StatusCode::OK);
+        assert_eq!(stored.id, 1);
+        assert_eq!(stored.result.model, "Itel Core i7-8700 CPU @ 3.20GHz");
+        assert_eq!(stored.model_normalized, "Intel Core i7-8700");
+        assert_eq!(stored.result.times, vec![1.5]);
+        assert_eq!(stored.result.schema_version, SCHEMA_VERSION);
+    }
+
+    #[actix_web::test]
+    async fn invalid_result_is_rejected() {
+        // prepare
+        let app = test::init_service(App::new().configure(backend())).await;
+        let mut body = result(" ", "agent", &["TlbSize"]);
+        body["times"] = json!([]);
+
+        // execute
+        let response = test::call_service(&app, upload(&body).to_request()).await;
+        let status = response.status();
+        let error: ErrorDTO = test::read_body_json(response).await;
+
+        // assert
+        assert_eq!(status, StatusCode::UNPROCESSABLE_ENTITY);
+        assert_eq!(error.error, "validation_failed");
+        let fields = error
+            .fields
+            .iter()
+            .map(|field| field.field.as_str())
+            .collect::<Vec<_>>();
+        assert_eq!(fields, vec!["model", "times"]);
+    }
+
+    #[actix_web::test]
+    async fn malformed_json_is_rejected() {
+        // prepare
+        let app = test::init_service(App::new().configure(backend())).await;
+
+        // execute
+        let request = test::TestRequest::post()
+            .uri("/api/result/upload")
+            .insert_header(header::ContentType::json())
+            .set_payload("{\"model\": ")
+            .to_request();
+        let response = test::call_service(&app, request).await;
+        let status = response.status();
+        let error: ErrorDTO = test::read_body_json(response).await;
+
+        // assert
+        assert_eq!(status, StatusCode::BAD_REQUEST);
+        assert_eq!(error.error, "bad_request");
+    }
+
+    #[actix_web::test]
+    async fn oversized_payload_is_rejected() {
+        // prepare
+        let app = test::init_service(App::new().configure(backend())).await;
+
+        // execute
+        let request = test::TestRequest::post()
+            .uri("/api/result/upload")
+            .insert_header(header::ContentType::json())
+            .set_payload(vec![b' '; MAX_PAYLOAD_SIZE + 1])
+            .to_request();
+        let response = test::call_service(&app, request).await;
+        let status = response.status();
+        let error: ErrorDTO = test::read_body_json(response).await;
+
+        // assert
+        assert_eq!(status, StatusCode::PAYLOAD_TOO_LARGE);
+        assert_eq!(error.error, "payload_too_large");
+    }
+
+    #[actix_web::test]
+    async fn results_are_filtered_and_paged() {
+        // prepare
+        let app = test::init_service(App::new().configure(backend())).await;
+        let uploads = [
+            result("Intel Core i5", "Mozilla Firefox/110.0", &["CacheSize"]),
+            result("AMD Ryzen 5", "Mozilla Firefox/110.0", &["CacheSize"]),
+            result("Intel Core i7", "Mozilla Chrome/111.0", &["CacheSize"]),
+            result("Intel Core i9", "Mozilla Firefox/110.0", &["CacheSize"]),
+            result("Intel Xeon", "Mozilla Firefox/110.0", &["TlbSize"]),
+        ];
+        for body in uploads.iter() {
+            test::call_service(&app, upload(body).to_request()).await;
+        }
+
+        // execute
+        let request = test::TestRequest::get()
+            .uri("/api/result?model=intel&user_agent=firefox&benchmark=CacheSize&limit=1")
+            .to_request();
+        let first: ResultPageDTO = test::call_and_read_body_json(&app, request).await;
+        let request = test::TestRequest::get()
+            .uri(&format!(
+                "/api/result?model=intel&user_agent=firefox&benchmark=CacheSize&limit=1&cursor={}",
+                first.next_cursor.unwrap()
+            ))
+            .to_request();
+        let second: ResultPageDTO = test::call_and_read_body_json(&app, request).await;
+
+        // assert
+        assert_eq!(first.results[0].result.model, "Intel Core i9");
+        assert_eq!(second.results[0].result.model, "Intel Core i5");
+        assert_eq!(second.next_cursor, None);
+    }
+
+    #[actix_web::test]
+    async fn invalid_query_is_rejected() {
+        // prepare
+        let app = test::init_service(App::new().configure(backend())).await;
+
+        // execute
+        let request = test::TestRequest::get()
+            .uri("/api/result?user_agent=netscape")
+            .to_request();
+        let response = test::call_service(&app, request).await;
+
+        // assert
+        assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+    }
+
+    #[actix_web::test]
+    async fn missing_result_is_not_found() {
+        // prepare
+        let app = test::init_service(App::new().configure(backend())).await;
+
+        // execute
+        let mut statuses = vec![];
+        for uri in [
+            "/api/result/7",
+            "/api/result/7/charts",
+            "/api/result/7/charts/TlbSize",
+        ] {
+            let request = test::TestRequest::get().uri(uri).to_request();
+            statuses.push(test::call_service(&app, request).await.status());
+        }
+
+        // assert
+        assert_eq!(statuses, vec![StatusCode::NOT_FOUND; 3]);
+    }
+
+    #[actix_web::test]
+    async fn charts_are_served() {
+        // prepare
+        let app = test::init_service(App::new().configure(backend())).await;
+        let body = result("model", "agent", &["PageSize", "TlbSize"]);
+        test::call_service(&app, upload(&body).to_request()).await;
+
+        // execute
+        let request = test::TestRequest::get()
+            .uri("/api/result/1/charts")
+            .to_request();
+        let charts: Vec<ChartDTO> = test::call_and_read_body_json(&app, request).await;
+        let request = test::TestRequest::get()
+            .uri("/api/result/1/charts/TlbSize")
+            .to_request();
+        let image = test::call_service(&app, request).await;
+
+        // assert
+        assert_eq!(charts.len(), 2);
+        assert_eq!(
+            image.headers().get(header::CONTENT_TYPE).unwrap(),
+            "image/svg+xml"
+        );
+        let svg = test::read_body(image).await;
+        assert!(svg.starts_with(b"<svg"));
+    }
+
+    #[actix_web::test]
+    async fn results_are_exported() {
+        // prepare
+        let app = test::init_service(App::new().configure(backend())).await;
+        test::call_service(
+            &app,
+            upload(&result("first", "agent", &["TlbSize"])).to_request(),
+        )
+        .await;
+        test::call_service(
+            &app,
+            upload(&result("second", "agent", &["TlbSize"])).to_request(),
+        )
+        .await;
+
+        // execute
+        let request = test::TestRequest::get()
+            .uri("/api/export?format=csv&model=second")
+            .to_request();
+        let csv = test::call_and_read_body(&app, request).await;
+        let request = test::TestRequest::get().uri("/api/export").to_request();
+        let ndjson = test::call_and_read_body(&app, request).await;
+
+        // assert
+        let csv = String::from_utf8(csv.to_vec()).unwrap();
+        let rows = csv.lines().collect::<Vec<_>>();
+        assert_eq!(
+            rows[0],
+            "id,created_at,model,model_normalized,user_agent,benchmark,x,y"
+        );
+        assert_eq!(rows.len(), 3);
+        assert!(
+            rows[1].starts_with("2,") && rows[1].ends_with(",second,second,agent,TlbSize,512,3")
+        );
+        let models = String::from_utf8(ndjson.to_vec())
+            .unwrap()
+            .lines()
+            .map(|line| {
+                serde_json::from_str::<StoredResultDTO>(line)
+                    .unwrap()
+                    .result
+                    .model
+            })
+            .collect::<Vec<_>>();
+        assert_eq!(models, vec!["first", "second"]);
+    }
+
+    #[actix_web::test]
+    async fn cpu_catalogue_is_imported() {
+        // prepare
+        let app = test::init_service(App::new().configure(backend())).await;
+        test::call_service(
+            &app,
+            upload(&result(
+                "Intel Core i7-8700 CPU @ 3.20GHz",
+                "agent",
+                &["TlbSize"],
+            ))
+            .to_request(),
+        )
+        .await;
+        let csv = "manufacturer;name;rname;cores;threads;base;boost;l1;l2;l3;l1asso;pagesize;loadbuffer;l1dtlb;microarchitecture
+Intel;Intel Core i7-8700;;6;12;3.2;4.6;32;256;12;8;4096;72;64;Coffee Lake
+Apple;Apple M1;;8;8;2.06;3.2;128;12288;;8;16384;;;Firestorm/Icestorm";
+
+        // execute
+        let request = admin(test::TestRequest::post())
+            .uri("/api/cpu/import")
+            .set_payload(csv)
+            .to_request();
+        let imported: CpuImportDTO = test::call_and_read_body_json(&app, request).await;
+        let request = admin(test::TestRequest::post())
+            .uri("/api/cpu/import")
+            .set_payload(csv.replace(";2.06;3.2;", ";2.06;3.3;"))
+            .to_request();
+        let reimported: CpuImportDTO = test::call_and_read_body_json(&app, request).await;
+        let request = admin(test::TestRequest::post())
+            .uri("/api/cpu/import")
+            .set_payload(csv.replace(";6;12;", ";6;4;"))
+            .to_request();
+        let invalid: ErrorDTO = test::call_and_read_body_json(&app, request).await;
+        let request = test::TestRequest::get().uri("/api/cpu").to_request();
+        let specs: Vec<StoredCpuSpecDTO> = test::call_and_read_body_json(&app, request).await;
+
+        // assert
+        assert_eq!(imported, CpuImportDTO { imported: 2 });
+        assert_eq!(reimported, CpuImportDTO { imported: 1 });
+        assert_eq!(invalid.fields[0].field, "[0].threads");
+        let specs = specs
+            .iter()
+            .map(|stored| (stored.spec.name.as_str(), stored.result_count))
+            .collect::<Vec<_>>();
+        assert_eq!(specs, vec![("Apple M1", 0), ("Intel Core i7-8700", 1)]);
+    }
+
+    #[actix_web::test]
+    async fn cpu_changes_require_admin_token() {
+        // prepare
+        let app = test::init_service(App::new().configure(backend())).await;
+        let disabled =
+            test::init_service(App::new().configure(backend_with_admin_token(None))).await;
+        let create = |request: test::TestRequest| {
+            request
+                .uri("/api/cpu")
+                .set_json(cpu_spec("first", None))
+                .to_request()
+        };
+
+        // execute
+        let missing = test::call_service(&app, create(test::TestRequest::post())).await;
+        let wrong = test::call_service(
+            &app,
+            create(
+                test::TestRequest::post().insert_header((header::AUTHORIZATION, "Bearer admin")),
+            ),
+        )
+        .await;
+        let without_token =
+            test::call_service(&disabled, create(admin(test::TestRequest::post()))).await;
+        let request = test::TestRequest::delete().uri("/api/cpu/1").to_request();
+        let deleted = test::call_service(&app, request).await;
+        let request = test::TestRequest::post()
+            .uri("/api/cpu/import")
+            .set_payload("")
+            .to_request();
+        let imported = test::call_service(&app, request).await;
+        let authorized = test::call_service(&app, create(admin(test::TestRequest::post()))).await;
+
+        // assert
+        assert_eq!(missing.status(), StatusCode::UNAUTHORIZED);
+        assert_eq!(wrong.status(), StatusCode::UNAUTHORIZED);
+        assert_eq!(without_token.status(), StatusCode::FORBIDDEN);
+        assert_eq!(deleted.status(), StatusCode::UNAUTHORIZED);
+        assert_eq!(imported.status(), StatusCode::UNAUTHORIZED);
+        assert_eq!(authorized.status(), StatusCode::CREATED);
+    }
+
+    #[actix_web::test]
+    async fn shared_cpu_aliases_are_rejected() {
+        // prepare
+        let app = test::init_service(App::new().configure(backend())).await;
+        let create = |body: Value| {
+            admin(test::TestRequest::post())
+                .uri("/api/cpu")
+                .set_json(body)
+                .to_request()
+        };
+        test::call_service(&app, create(cpu_spec("first", Some("alias")))).await;
+        test::call_service(&app, create(cpu_spec("second", None))).await;
+
+        // execute
+        let created = test::call_service(&app, create(cpu_spec("third", Some("alias")))).await;
+        let request = admin(test::TestRequest::put())
+            .uri("/api/cpu/2")
+            .set_json(cpu_spec("second", Some("alias")))
+            .to_request();
+        let updated = test::call_service(&app, request).await.status();
+        let request = admin(test::TestRequest::put())
+            .uri("/api/cpu/1")
+            .set_json(cpu_spec("first", Some("alias")))
+            .to_request();
+        let kept = test::call_service(&app, request).await.status();
+
+        // assert
+        assert_eq!(created.status(), StatusCode::CONFLICT);
+        let error: ErrorDTO = test::read_body_json(created).await;
+        assert_eq!(
+            error.message,
+            "the alias 'alias' is already used by the CPU specification 'first'"
+        );
+        assert_eq!(updated, StatusCode::CONFLICT);
+        assert_eq!(kept, StatusCode::OK);
+    }
+
+    #[actix_web::test]
+    async fn cpu_specs_are_managed() {
+        // prepare
+        let app = test::init_service(App::new().configure(backend())).await;
+        let create = |body: Value| {
+            admin(test::TestRequest::post())
+                .uri("/api/cpu")
+                .set_json(body)
+                .to_request()
+        };
+
+        // execute
+        let created = test::call_service(&app, create(cpu_spec("first", None))).await;
+        let created: StoredCpuSpecDTO = test::read_body_json(created).await;
+        let duplicate = test::call_service(&app, create(cpu_spec("first", None))).await;
+        test::call_service(&app, create(cpu_spec("second", None))).await;
+        let request = admin(test::TestRequest::put())
+            .uri("/api/cpu/1")
+            .set_json(cpu_spec("renamed", Some("alias")))
+            .to_request();
+        let updated: StoredCpuSpecDTO = test::call_and_read_body_json(&app, request).await;
+        let request = admin(test::TestRequest::put())
+            .uri("/api/cpu/1")
+            .set_json(cpu_spec("second", None))
+            .to_request();
+        let renamed = test::call_service(&app, request).await.status();
+        let request = admin(test::TestRequest::put())
+            .uri("/api/cpu/7")
+            .set_json(cpu_spec("third", None))
+            .to_request();
+        let missing = test::call_service(&app, request).await.status();
+        let request = admin(test::TestRequest::delete())
+            .uri("/api/cpu/1")
+            .to_request();
+        let deleted = test::call_service(&app, request).await.status();
+        let request = test::TestRequest::get().uri("/api/cpu/1").to_request();
+        let found = test::call_service(&app, request).await.status();
+
+        // assert
+        assert_eq!(created.id, 1);
+        assert_eq!(created.spec.name, "first");
+        assert_eq!(duplicate.status(), StatusCode::CONFLICT);
+        assert_eq!(updated.spec.name, "renamed");
+        assert_eq!(updated.spec.alias.as_deref(), Some("alias"));
+        assert_eq!(renamed, StatusCode::CONFLICT);
+        assert_eq!(missing, StatusCode::NOT_FOUND);
+        assert_eq!(deleted, StatusCode::NO_CONTENT);
+        assert_eq!(found, StatusCode::NOT_FOUND);
+    }
+
+    #[actix_web::test]
+    async fn results_are_matched_with_cpu_specs() {
+        // prepare
+        let app = test::init_service(App::new().configure(backend())).await;
+        for model in ["Intel Core i7-8700", "Coffee Lake", "unknown", "unknown"] {
+            test::call_service(
+                &app,
+                upload(&result(model, "agent", &["TlbSize"])).to_request(),
+            )
+            .await;
+        }
+        let request = admin(test::TestRequest::post())
+            .uri("/api/cpu")
+            .set_json(cpu_spec("Intel Core i7-8700", Some("Coffee Lake")))
+            .to_request();
+        test::call_service(&app, request).await;
+
+        // execute
+        let request = test::TestRequest::get()
+            .uri("/api/result/2/cpu")
+            .to_request();
+        let spec: StoredCpuSpecDTO = test::call_and_read_body_json(&app, request).await;
+        let request = test::TestRequest::get()
+            .uri("/api/result/3/cpu")
+            .to_request();
+        let unmatched_status = test::call_service(&app, request).await.status();
+        let request = test::TestRequest::get()
+            .uri("/api/cpu/unmatched")
+            .to_request();
+        let unmatched: Vec<UnmatchedModelDTO> = test::call_and_read_body_json(&app, request).await;
+
+        // assert
+        assert_eq!(spec.spec.name, "Intel Core i7-8700");
+        assert_eq!(spec.result_count, 2);
+        assert_eq!(unmatched_status, StatusCode::NOT_FOUND);
+        assert_eq!(
+            unmatched,
+            vec![UnmatchedModelDTO {
+                model_normalized: "unknown".to_string(),
+                result_count: 2,
+            }]
+        );
+    }
+
+    #[actix_web::test]
+    async fn model_check_is_computed_at_upload() {
+        // prepare
+        let app = test::init_service(App::new().configure(backend())).await;
+        let request = admin(test::TestRequest::post())
+            .uri("/api/cpu")
+            .set_json(cpu_spec("Intel Core i7-8700", Some("Coffee Lake")))
+            .to_request();
+        test::call_service(&app, request).await;
+        let forged = json!({"spec_name": "forged", "mismatches": ["L2Size"]});
+        for model in ["Coffee Lake", "unknown"] {
+            let mut body = result(model, "agent", &["TlbSize"]);
+            body["model_check"] = forged.clone();
+            test::call_service(&app, upload(&body).to_request()).await;
+        }
+
+        // execute
+        let request = test::TestRequest::get().uri("/api/result/1").to_request();
+        let matched: StoredResultDTO = test::call_and_read_body_json(&app, request).await;
+        let request = test::TestRequest::get().uri("/api/result/2").to_request();
+        let unmatched: StoredResultDTO = test::call_and_read_body_json(&app, request).await;
+
+        // assert
+        assert_eq!(
+            matched.result.model_check,
+            Some(ModelCheckDTO {
+                spec_name: Some("Intel Core i7-8700".to_string()),
+                mismatches: vec![],
+            })
+        );
+        assert_eq!(
+            unmatched.result.model_check,
+            Some(ModelCheckDTO {
+                spec_name: None,
+                mismatches: vec![],
+            })
+        );
+    }
+
+    #[actix_web::test]
+    async fn models_are_suggested() {
+        // prepare
+        let app = test::init_service(App::new().configure(backend())).await;
+        for model in ["AMD Ryzen 7 3700X", "AMD Ryzen 7 3700X", "AMD Ryzen 5 3600"] {
+            test::call_service(
+                &app,
+                upload(&result(model, "agent", &["TlbSize"])).to_request(),
+            )
+            .await;
+        }
+        let request = admin(test::TestRequest::post())
+            .uri("/api/cpu")
+            .set_json(cpu_spec("AMD Ryzen 7 3700", None))
+            .to_request();
+        test::call_service(&app, request).await;
+        let suggest = |model: &str| {
+            test::TestRequest::get()
+                .uri(&format!("/api/cpu/suggest?model={}", model))
+                .to_request()
+        };
+
+        // execute
+        let typed: ModelSuggestionsDTO =
+            test::call_and_read_body_json(&app, suggest("ryzn%207%2037")).await;
+        let common: ModelSuggestionsDTO =
+            test::call_and_read_body_json(&app, suggest("AMD%20Ryzen%207%203700X")).await;
+        let rare: ModelSuggestionsDTO =
+            test::call_and_read_body_json(&app, suggest("amd%20ryzen%205%203600%20@%204GHz")).await;
+        let unknown: ModelSuggestionsDTO =
+            test::call_and_read_body_json(&app, suggest("Snapdragon")).await;
+
+        // assert
+        let models = typed
+            .suggestions
+            .iter()
+            .map(|suggestion| (suggestion.model.as_str(), suggestion.in_catalogue))
+            .collect::<Vec<_>>();
+        assert_eq!(
+            models,
+            vec![("AMD Ryzen 7 3700X", false), ("AMD Ryzen 7 3700", true)]
+        );
+        assert_eq!(typed.suggestions[0].result_count, 2);
+        assert!(!typed.known);
+        assert!(common.known);
+        // a model of a single result outside the catalogue is neither suggested nor known
+        assert_eq!(rare.model_normalized, "amd ryzen 5 3600");
+        assert!(rare.suggestions.is_empty());
+        assert!(!rare.known);
+        assert!(unknown.suggestions.is_empty());
+        assert!(!unknown.known);
+    }
+
+    #[actix_web::test]
+    async fn results_are_classified_by_similar_results() {
+        // prepare
+        let app = test::init_service(App::new().configure(backend())).await;
+        let classify = |body: &Value| {
+            test::TestRequest::post()
+                .uri("/api/classify")
+                .set_json(body)
+                .to_request()
+        };
+        let untrained: ClassificationDTO =
+            test::call_and_read_body_json(&app, classify(&associativity("unknown", 8, 4))).await;
+        for (model, ways, slowdown) in [
+            ("Intel Core i7-8700", 8, 4),
+            ("Intel Core i7-8700", 8, 5),
+            ("AMD Ryzen 7 3700X", 12, 2),
+            ("unknown", 8, 4),
+        ] {
+            test::call_service(
+                &app,
+                upload(&associativity(model, ways, slowdown)).to_request(),
+            )
+            .await;
+        }
+        let mut ryzen = cpu_spec("AMD Ryzen 7 3700X", None);
+        ryzen["l1_kib"] = json!(64);
+        ryzen["microarchitecture"] = json!("Zen 2");
+        for spec in [cpu_spec("Intel Core i7-8700", None), ryzen] {
+            let request = admin(test::TestRequest::post())
+                .uri("/api/cpu")
+                .set_json(spec)
+                .to_request();
+            test::call_service(&app, request).await;
+        }
+
+        // execute
+        let classification: ClassificationDTO =
+            test::call_and_read_body_json(&app, classify(&associativity("unknown", 8, 4))).await;
+        let invalid = test::call_service(&app, classify(&json!({"model": ""}))).await;
+
+        // assert
+        assert_eq!(untrained.training_size, 0);
+        assert!(untrained.models.is_empty());
+        assert_eq!(classification.training_size, 3);
+        assert_eq!(classification.models[0].value, "Intel Core i7-8700");
+        assert!(classification.models[0].score > 0.9);
+        assert_eq!(classification.microarchitectures[0].value, "Coffee Lake");
+        assert_eq!(classification.l1_kib[0].value, 32);
+        assert_eq!(classification.l3_mib[0].value, 12);
+        assert_eq!(invalid.status(), StatusCode::BAD_REQUEST);
+    }
+
+    #[actix_web::test]
+    async fn similar_results_are_found_by_fingerprint() {
+        // prepare
+        let app = test::init_service(App::new().configure(backend())).await;
+        let model = "Intel Core i7-8700";
+        for body in [
+            associativity(model, 8, 4),
+            associativity(model, 12, 2),
+            associativity(model, 8, 4),
+            // shares no features with the others
+            result("model", "agent", &["TlbSize"]),
+        ] {
+            test::call_service(&app, upload(&body).to_request()).await;
+        }
+        let similar = |uri: &str| admin(test::TestRequest::get()).uri(uri).to_request();
+
+        // execute
+        let euclidean: SimilarResultsDTO =
+            test::call_and_read_body_json(&app, similar("/api/result/1/similar")).await;
+        let hamming: SimilarResultsDTO = test::call_and_read_body_json(
+            &app,
+            similar("/api/result/1/similar?metric=hamming&limit=1"),
+        )
+        .await;
+        let missing = test::call_service(&app, similar("/api/result/99/similar")).await;
+        let anonymous = test::TestRequest::get()
+            .uri("/api/result/1/similar")
+            .to_request();
+        let unauthorized = test::call_service(&app, anonymous).await;
+
+        // assert
+        let ids = euclidean
+            .similar
+            .iter()
+            .map(|result| result.id)
+            .collect::<Vec<_>>();
+        assert_eq!(ids, vec![3, 2]);
+        assert_eq!(euclidean.similar[0].fingerprint, euclidean.fingerprint);
+        assert_eq!(euclidean.similar[0].distance, 0.0);
+        assert_ne!(euclidean.similar[1].fingerprint, euclidean.fingerprint);
+        assert!(euclidean.similar[1].distance > 0.0);
+        assert_eq!(hamming.metric, DistanceMetric::Hamming);
+        assert_eq!(hamming.similar.len(), 1);
+        assert_eq!(missing.status(), StatusCode::NOT_FOUND);
+        assert_eq!(unauthorized.status(), StatusCode::UNAUTHORIZED);
+    }
+
+    #[actix_web::test]
+    async fn results_are_found_by_exact_fingerprint() {
+        // prepare
+        let app = test::init_service(App::new().configure(backend())).await;
+        let model = "Intel Core i7-8700";
+        for body in [
+            associativity(model, 8, 4),
+            associativity(model, 12, 2),
+            associativity(model, 8, 4),
+        ] {
+            test::call_service(&app, upload(&body).to_request()).await;
+        }
+        let request = admin(test::TestRequest::get())
+            .uri("/api/result/1/similar")
+            .to_request();
+        let searched: SimilarResultsDTO = test::call_and_read_body_json(&app, request).await;
+        let lookup = |hash: &str| {
+            admin(test::TestRequest::get())
+                .uri(&format!("/api/fingerprint/{}", hash))
+                .to_request()
+        };
+
+        // execute
+        let matches: Vec<FingerprintMatchDTO> =
+            test::call_and_read_body_json(&app, lookup(&searched.fingerprint)).await;
+        let unknown: Vec<FingerprintMatchDTO> =
+            test::call_and_read_body_json(&app, lookup("unknown")).await;
+        let anonymous = test::TestRequest::get()
+            .uri(&format!("/api/fingerprint/{}", searched.fingerprint))
+            .to_request();
+        let unauthorized = test::call_service(&app, anonymous).await;
+
+        // assert
+        let ids = matches.iter().map(|found| found.id).collect::<Vec<_>>();
+        assert_eq!(ids, vec![1, 3]);
+        assert_eq!(matches[0].model_normalized, model);
+        assert!(unknown.is_empty());
+        assert_eq!(unauthorized.status(), StatusCode::UNAUTHORIZED);
+    }
 }
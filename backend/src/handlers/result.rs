@@ -1,16 +1,343 @@
+use crate::charts::{decode_chart, render_charts};
 use crate::context::BackendContext;
-use crate::repository::result::insert_result;
-use actix_web::web::{Data, Json};
-use actix_web::{post, HttpResponse, Responder};
+use crate::error::ApiError;
+use crate::repository::result::{ResultFilter, UserAgentFamily};
+use crate::validation::validate_result;
+use actix_web::web::{Data, Json, Path, Query};
+use actix_web::{get, post, HttpRequest, HttpResponse, Responder};
+use chrono::{DateTime, Utc};
+use common::analysis::{check_model, extract_features};
+use common::benchmark::BenchmarkType;
+use common::cpu::find_cpu_spec;
+use common::dto::chart::ChartDTO;
+use common::dto::fingerprint::{FingerprintMatchDTO, SimilarResultDTO, SimilarResultsDTO};
 use common::dto::result::ResultDTO;
-use std::error::Error;
+use common::fingerprint::{DistanceMetric, Fingerprint};
+use common::model::normalize_model;
+use serde::Deserialize;
+use serde_json::Value;
+use std::cmp::Ordering;
+use std::collections::BinaryHeap;
+
+const DEFAULT_PAGE_SIZE: i64 = 50;
+const MAX_PAGE_SIZE: i64 = 500;
+const DEFAULT_SIMILAR_COUNT: usize = 10;
+const MAX_SIMILAR_COUNT: usize = 100;
+
+/// Filter of the results, shared by the listing and the export
+#[derive(Deserialize)]
+pub struct FilterQuery {
+    model: Option<String>,
+    user_agent: Option<UserAgentFamily>,
+    from: Option<DateTime<Utc>>,
+    to: Option<DateTime<Utc>>,
+    benchmark: Option<BenchmarkType>,
+}
+
+impl FilterQuery {
+    pub fn filter(&self) -> ResultFilter {
+        ResultFilter {
+            model: self.model.clone(),
+            user_agent_family: self.user_agent,
+            from: self.from,
+            to: self.to,
+            benchmark: self.benchmark,
+        }
+    }
+}
+
+#[derive(Deserialize)]
+pub struct ResultQuery {
+    #[serde(flatten)]
+    filter: FilterQuery,
+    cursor: Option<i64>,
+    limit: Option<i64>,
+}
+
+/// Similar result ordered by the distance, the older one first on a tie
+struct Nearest(SimilarResultDTO);
+
+impl Ord for Nearest {
+    fn cmp(&self, other: &Self) -> Ordering {
+        self.0
+            .distance
+            .total_cmp(&other.0.distance)
+            .then(self.0.id.cmp(&other.0.id))
+    }
+}
+
+impl PartialOrd for Nearest {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl PartialEq for Nearest {
+    fn eq(&self, other: &Self) -> bool {
+        self.cmp(other) == Ordering::Equal
+    }
+}
+
+impl Eq for Nearest {}
+
+#[derive(Deserialize)]
+pub struct SimilarQuery {
+    #[serde(default)]
+    metric: DistanceMetric,
+    limit: Option<usize>,
+}
 
 #[post("/api/result/upload")]
 pub async fn upload(
     body: Json<ResultDTO>,
     context: Data<BackendContext>,
-) -> Result<impl Responder, Box<dyn Error>> {
-    insert_result(&context.connection_pool, &body).await?;
+) -> Result<impl Responder, ApiError> {
+    validate_result(&body).map_err(ApiError::Validation)?;
+    let mut result = body.into_inner();
+
+    let model_normalized = normalize_model(&result.model);
+    // the check of the frontend uses its built-in catalogue, which may differ from the stored one
+    let specs = context
+        .repository
+        .find_model_cpu_specs(&model_normalized)
+        .await?;
+    result.model_check = Some(check_model(
+        &extract_features(&result),
+        find_cpu_spec(&specs, &model_normalized).map(|stored| &stored.spec),
+    ));
+
+    let charts = render_charts(&result);
+    context
+        .repository
+        .insert_result(
+            &result,
+            &model_normalized,
+            &Fingerprint::of(&result),
+            &charts,
+        )
+        .await?;
+    context.classifier.invalidate_model(&model_normalized).await;
 
     Ok(HttpResponse::Ok())
 }
+
+#[get("/api/result")]
+pub async fn list_results(
+    query: Query<ResultQuery>,
+    context: Data<BackendContext>,
+) -> Result<impl Responder, ApiError> {
+    let limit = query
+        .limit
+        .unwrap_or(DEFAULT_PAGE_SIZE)
+        .clamp(1, MAX_PAGE_SIZE);
+    let page = context
+        .repository
+        .find_results(&query.filter.filter(), query.cursor, limit)
+        .await?;
+
+    Ok(HttpResponse::Ok().json(page))
+}
+
+#[get("/api/result/{id}")]
+pub async fn get_result(
+    path: Path<i64>,
+    context: Data<BackendContext>,
+) -> Result<impl Responder, ApiError> {
+    Ok(
+        match context.repository.find_result(path.into_inner()).await? {
+            Some(result) => HttpResponse::Ok().json(result),
+            None => HttpResponse::NotFound().finish(),
+        },
+    )
+}
+
+#[get("/api/result/{id}/charts")]
+pub async fn get_charts(
+    path: Path<i64>,
+    context: Data<BackendContext>,
+) -> Result<impl Responder, ApiError> {
+    Ok(match load_charts(&context, path.into_inner()).await? {
+        Some(charts) => HttpResponse::Ok().json(charts),
+        None => HttpResponse::NotFound().finish(),
+    })
+}
+
+/// Chart of a single benchmark as an image, which can be opened directly in the browser
+#[get("/api/result/{id}/charts/{benchmark}")]
+pub async fn get_chart_image(
+    path: Path<(i64, String)>,
+    context: Data<BackendContext>,
+) -> Result<impl Responder, ApiError> {
+    let (id, benchmark) = path.into_inner();
+    let Ok(benchmark) = serde_json::from_value::<BenchmarkType>(Value::String(benchmark)) else {
+        return Ok(HttpResponse::NotFound().finish());
+    };
+
+    let chart = load_charts(&context, id).await?.and_then(|charts| {
+        charts
+            .into_iter()
+            .find(|chart| chart.benchmark == benchmark)
+    });
+
+    Ok(match chart {
+        Some(chart) => HttpResponse::Ok()
+            .content_type(chart.content_type.as_str())
+            .body(decode_chart(&chart)?),
+        None => HttpResponse::NotFound().finish(),
+    })
+}
+
+/// Other results nearest to the result by the distance of their fingerprints.
+/// Results sharing too few features with it are left out.
+#[get("/api/result/{id}/similar")]
+pub async fn find_similar(
+    request: HttpRequest,
+    path: Path<i64>,
+    query: Query<SimilarQuery>,
+    context: Data<BackendContext>,
+) -> Result<impl Responder, ApiError> {
+    context.authorize_admin(&request)?;
+    let id = path.into_inner();
+    let limit = query
+        .limit
+        .unwrap_or(DEFAULT_SIMILAR_COUNT)
+        .clamp(1, MAX_SIMILAR_COUNT);
+
+    let Some(searched) = context.repository.find_fingerprint(id).await? else {
+        return Ok(HttpResponse::NotFound().finish());
+    };
+
+    // the nearest results so far, the farthest of them on the top
+    let mut nearest = BinaryHeap::with_capacity(limit + 1);
+    context
+        .repository
+        .for_each_fingerprint(&mut |stored| {
+            if stored.id == id {
+                return;
+            }
+            let Some(distance) = query
+                .metric
+                .distance(&searched.fingerprint.features, &stored.fingerprint.features)
+            else {
+                return;
+            };
+            nearest.push(Nearest(SimilarResultDTO {
+                id: stored.id,
+                created_at: stored.created_at,
+                model_normalized: stored.model_normalized,
+                fingerprint: stored.fingerprint.hash,
+                distance,
+            }));
+            if nearest.len() > limit {
+                nearest.pop();
+            }
+        })
+        .await?;
+
+    Ok(HttpResponse::Ok().json(SimilarResultsDTO {
+        id,
+        fingerprint: searched.fingerprint.hash,
+        metric: query.metric,
+        similar: nearest
+            .into_sorted_vec()
+            .into_iter()
+            .map(|nearest| nearest.0)
+            .collect(),
+    }))
+}
+
+/// Results with exactly the hash, oldest first, e.g. the uploads of the same measurement
+#[get("/api/fingerprint/{hash}")]
+pub async fn find_by_fingerprint(
+    request: HttpRequest,
+    path: Path<String>,
+    context: Data<BackendContext>,
+) -> Result<impl Responder, ApiError> {
+    context.authorize_admin(&request)?;
+
+    let matches = context
+        .repository
+        .find_fingerprints_by_hash(&path.into_inner())
+        .await?
+        .into_iter()
+        .map(|stored| FingerprintMatchDTO {
+            id: stored.id,
+            created_at: stored.created_at,
+            model_normalized: stored.model_normalized,
+        })
+        .collect::<Vec<_>>();
+
+    Ok(HttpResponse::Ok().json(matches))
+}
+
+/// Loads the charts of the result. Results uploaded before the charts were rendered
+/// on ingest get them rendered and stored on the first request.
+async fn load_charts(context: &BackendContext, id: i64) -> Result<Option<Vec<ChartDTO>>, ApiError> {
+    let Some(stored_charts) = context.repository.find_charts(id).await? else {
+        return Ok(None);
+    };
+    if !stored_charts.is_empty() {
+        return Ok(Some(stored_charts));
+    }
+
+    let Some(stored) = context.repository.find_result(id).await? else {
+        return Ok(None);
+    };
+    if stored.result.benchmark_results.is_empty() {
+        return Ok(Some(vec![]));
+    }
+
+    let charts = render_charts(&stored.result);
+    context.repository.update_charts(id, &charts).await?;
+
+    Ok(Some(charts))
+}
+
+/*
+ * TESTS
+ */
+
+#[cfg(test)]
+mod tests {
+    use actix_web::web::Query;
+
+    use common::benchmark::BenchmarkType;
+    use common::fingerprint::DistanceMetric;
+
+    use crate::handlers::result::{ResultQuery, SimilarQuery};
+    use crate::repository::result::UserAgentFamily;
+
+    #[test]
+    fn result_query_parses_filter_and_page() {
+        // execute
+        let query = Query::<ResultQuery>::from_query(
+            "model=i7&user_agent=firefox&from=2023-05-01T00:00:00Z&benchmark=CacheSize&cursor=42&limit=10",
+        )
+        .unwrap();
+
+        // assert
+        let filter = query.filter.filter();
+        assert_eq!(filter.model.as_deref(), Some("i7"));
+        assert_eq!(filter.user_agent_family, Some(UserAgentFamily::Firefox));
+        assert!(filter.from.is_some());
+        assert!(filter.to.is_none());
+        assert_eq!(filter.benchmark, Some(BenchmarkType::CacheSize));
+        assert_eq!(query.cursor, Some(42));
+        assert_eq!(query.limit, Some(10));
+    }
+
+    #[test]
+    fn similar_query_defaults_to_euclidean() {
+        // execute
+        let default = Query::<SimilarQuery>::from_query("").unwrap();
+        let hamming = Query::<SimilarQuery>::from_query("metric=hamming&limit=3").unwrap();
+        let unknown = Query::<SimilarQuery>::from_query("metric=chebyshev");
+
+        // assert
+        assert_eq!(default.metric, DistanceMetric::Euclidean);
+        assert_eq!(default.limit, None);
+        assert_eq!(hamming.metric, DistanceMetric::Hamming);
+        assert_eq!(hamming.limit, Some(3));
+        assert!(unknown.is_err());
+    }
+}
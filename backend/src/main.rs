@@ -1,10 +1,16 @@
+mod charts;
+mod classifier;
 mod config;
 mod context;
+mod error;
 mod handlers;
 mod repository;
+mod suggestions;
+mod validation;
 
 use crate::config::read_config;
 use crate::context::build_context;
+use crate::error::{json_error_handler, query_error_handler};
 use crate::handlers::register_handlers;
 use actix_web::middleware::Logger;
 use actix_web::web::Data;
@@ -13,6 +19,9 @@ use env_logger::Env;
 use log::info;
 use std::error::Error;
 
+/// Size limit of the request body, the uploaded results can be large
+const MAX_PAYLOAD_SIZE: usize = 100 * 1024 * 1024;
+
 #[actix_web::main]
 async fn main() -> Result<(), Box<dyn Error>> {
     env_logger::init_from_env(Env::default().default_filter_or("debug"));
@@ -39,9 +48,13 @@ async fn main() -> Result<(), Box<dyn Error>> {
 }
 
 fn configure_server(cfg: &mut web::ServiceConfig) {
-    // Increase the size limit of the payload to 100 MB
-    let json_cfg = web::JsonConfig::default().limit(100 * 1024 * 1024);
-    let payload_cfg = web::PayloadConfig::default().limit(100 * 1024 * 1024);
-
-    cfg.app_data(json_cfg).app_data(payload_cfg);
+    let json_cfg = web::JsonConfig::default()
+        .limit(MAX_PAYLOAD_SIZE)
+        .error_handler(json_error_handler);
+    let payload_cfg = web::PayloadConfig::default().limit(MAX_PAYLOAD_SIZE);
+    let query_cfg = web::QueryConfig::default().error_handler(query_error_handler);
+
+    cfg.app_data(json_cfg)
+        .app_data(payload_cfg)
+        .app_data(query_cfg);
 }
@@ -0,0 +1,259 @@
+use std::error::Error;
+use std::fmt::{Display, Formatter};
+
+use sqlx::error::DatabaseError;
+use sqlx::{Database, Encode, FromRow, QueryBuilder, Type};
+
+use common::dto::cpu::{CpuSpecDTO, StoredCpuSpecDTO, UnmatchedModelDTO};
+
+/// Selects the CPU specifications with the number of results they match.
+/// The query is the same for all the supported databases.
+pub const SELECT_CPU_SPECS: &str =
+    "SELECT cpu_spec.id, manufacturer, name, alias, cores, threads, base_ghz, boost_ghz, l1_kib, l2_kib, l3_mib,
+        l1_associativity, page_size, load_buffer, l1_dtlb, microarchitecture,
+        (
+            SELECT count(*) FROM upload_benchmarkresult
+            WHERE upload_benchmarkresult.model_normalized IN (cpu_spec.name, cpu_spec.alias)
+        ) AS result_count
+    FROM cpu_spec";
+
+/// Selects the normalized models of the results which match no CPU specification, the most common first
+pub const SELECT_UNMATCHED_MODELS: &str =
+    "SELECT model_normalized, count(*) AS result_count FROM upload_benchmarkresult
+    WHERE model_normalized IS NOT NULL AND NOT EXISTS (
+        SELECT 1 FROM cpu_spec WHERE upload_benchmarkresult.model_normalized IN (cpu_spec.name, cpu_spec.alias)
+    )
+    GROUP BY model_normalized
+    ORDER BY result_count DESC, model_normalized";
+
+/// Appended to `SELECT_CPU_SPECS` after the condition with the bound model, keeps the specifications
+/// with the model as the name or the alias
+pub const MATCHING_MODEL: &str = " IN (cpu_spec.name, cpu_spec.alias) ORDER BY cpu_spec.id";
+
+/// Columns of `cpu_spec` set by the insert and the update, in the order of `push_spec_values`
+pub const CPU_SPEC_COLUMNS: &str =
+    "manufacturer, name, alias, cores, threads, base_ghz, boost_ghz, l1_kib, l2_kib, l3_mib,
+    l1_associativity, page_size, load_buffer, l1_dtlb, microarchitecture";
+
+/// Unique index of the aliases, named the same in all the supported databases
+const ALIAS_INDEX: &str = "cpu_spec_alias_key";
+
+/// The alias is already used by another specification. Reported by the unique index of the aliases
+/// when concurrent changes of the catalogue pass the checks of the handlers.
+#[derive(Debug)]
+pub struct AliasTaken {
+    pub alias: String,
+}
+
+impl Display for AliasTaken {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "the alias '{}' is already used by another CPU specification",
+            self.alias
+        )
+    }
+}
+
+impl Error for AliasTaken {}
+
+/// Replaces the violation of the unique index of the aliases by the stored specification with `AliasTaken`
+pub fn map_alias_violation(error: sqlx::Error, spec: &CpuSpecDTO) -> Box<dyn Error> {
+    match error {
+        sqlx::Error::Database(database) if is_alias_violation(database.as_ref()) => {
+            Box::new(AliasTaken {
+                alias: spec.alias.clone().unwrap_or_default(),
+            })
+        }
+        error => Box::new(error),
+    }
+}
+
+fn is_alias_violation(error: &dyn DatabaseError) -> bool {
+    // SQLite names only the column of the violated index
+    error.constraint() == Some(ALIAS_INDEX)
+        || error
+            .message()
+            .contains("UNIQUE constraint failed: cpu_spec.alias")
+}
+
+/// Inserts the specification, unless its name is taken. Returns the id of the inserted row.
+pub fn insert_cpu_spec_query<'a, DB>(spec: &CpuSpecDTO) -> QueryBuilder<'a, DB>
+where
+    DB: Database,
+    String: Encode<'a, DB> + Type<DB>,
+    i64: Encode<'a, DB> + Type<DB>,
+    f32: Encode<'a, DB> + Type<DB>,
+    Option<String>: Encode<'a, DB>,
+    Option<i64>: Encode<'a, DB>,
+{
+    let mut builder = QueryBuilder::new(format!(
+        "INSERT INTO cpu_spec ({}) VALUES (",
+        CPU_SPEC_COLUMNS
+    ));
+    push_spec_values(&mut builder, spec);
+    builder.push(") ON CONFLICT (name) DO NOTHING RETURNING id");
+    builder
+}
+
+/// Inserts the specification, or replaces the one with the same name
+pub fn upsert_cpu_spec_query<'a, DB>(spec: &CpuSpecDTO) -> QueryBuilder<'a, DB>
+where
+    DB: Database,
+    String: Encode<'a, DB> + Type<DB>,
+    i64: Encode<'a, DB> + Type<DB>,
+    f32: Encode<'a, DB> + Type<DB>,
+    Option<String>: Encode<'a, DB>,
+    Option<i64>: Encode<'a, DB>,
+{
+    let mut builder = QueryBuilder::new(format!(
+        "INSERT INTO cpu_spec ({}) VALUES (",
+        CPU_SPEC_COLUMNS
+    ));
+    push_spec_values(&mut builder, spec);
+    let assignments = CPU_SPEC_COLUMNS
+        .split(',')
+        .map(str::trim)
+        .map(|column| format!("{0} = excluded.{0}", column))
+        .collect::<Vec<_>>();
+    builder
+        .push(") ON CONFLICT (name) DO UPDATE SET ")
+        .push(assignments.join(", "));
+    builder
+}
+
+/// Replaces the specification, unless another one has the same name
+pub fn update_cpu_spec_query<'a, DB>(id: i64, spec: &CpuSpecDTO) -> QueryBuilder<'a, DB>
+where
+    DB: Database,
+    String: Encode<'a, DB> + Type<DB>,
+    i64: Encode<'a, DB> + Type<DB>,
+    f32: Encode<'a, DB> + Type<DB>,
+    Option<String>: Encode<'a, DB>,
+    Option<i64>: Encode<'a, DB>,
+{
+    let mut builder = QueryBuilder::new(format!("UPDATE cpu_spec SET ({}) = (", CPU_SPEC_COLUMNS));
+    push_spec_values(&mut builder, spec);
+    builder
+        .push(") WHERE id = ")
+        .push_bind(id)
+        .push(" AND NOT EXISTS (SELECT 1 FROM cpu_spec AS other WHERE other.name = ")
+        .push_bind(spec.name.clone())
+        .push(" AND other.id <> ")
+        .push_bind(id)
+        .push(")");
+    builder
+}
+
+/// Removes the aliases of the stored specifications with the names, so that the import may move
+/// an alias from one replaced specification to another without violating the unique index
+pub fn clear_aliases_query<'a, DB>(specs: &[CpuSpecDTO]) -> QueryBuilder<'a, DB>
+where
+    DB: Database,
+    String: Encode<'a, DB> + Type<DB>,
+{
+    let mut builder = QueryBuilder::new("UPDATE cpu_spec SET alias = NULL WHERE name IN (");
+    let mut names = builder.separated(", ");
+    for spec in specs {
+        names.push_bind(spec.name.clone());
+    }
+    builder.push(")");
+    builder
+}
+
+/// Binds the values of `CPU_SPEC_COLUMNS`, separated by commas
+fn push_spec_values<'a, DB>(builder: &mut QueryBuilder<'a, DB>, spec: &CpuSpecDTO)
+where
+    DB: Database,
+    String: Encode<'a, DB> + Type<DB>,
+    i64: Encode<'a, DB> + Type<DB>,
+    f32: Encode<'a, DB> + Type<DB>,
+    Option<String>: Encode<'a, DB>,
+    Option<i64>: Encode<'a, DB>,
+{
+    let count = |value: u32| value as i64;
+
+    builder
+        .separated(", ")
+        .push_bind(spec.manufacturer.clone())
+        .push_bind(spec.name.clone())
+        .push_bind(spec.alias.clone())
+        .push_bind(count(spec.cores))
+        .push_bind(count(spec.threads))
+        .push_bind(spec.base_ghz)
+        .push_bind(spec.boost_ghz)
+        .push_bind(count(spec.l1_kib))
+        .push_bind(count(spec.l2_kib))
+        .push_bind(spec.l3_mib.map(count))
+        .push_bind(spec.l1_associativity.map(count))
+        .push_bind(count(spec.page_size))
+        .push_bind(spec.load_buffer.map(count))
+        .push_bind(spec.l1_dtlb.map(count))
+        .push_bind(spec.microarchitecture.clone());
+}
+
+/// Counts are stored as `bigint`, as the databases have no unsigned 32-bit type
+#[derive(FromRow)]
+pub struct CpuSpecRow {
+    pub id: i64,
+    pub result_count: i64,
+    pub manufacturer: String,
+    pub name: String,
+    pub alias: Option<String>,
+    pub cores: i64,
+    pub threads: i64,
+    pub base_ghz: f32,
+    pub boost_ghz: f32,
+    pub l1_kib: i64,
+    pub l2_kib: i64,
+    pub l3_mib: Option<i64>,
+    pub l1_associativity: Option<i64>,
+    pub page_size: i64,
+    pub load_buffer: Option<i64>,
+    pub l1_dtlb: Option<i64>,
+    pub microarchitecture: String,
+}
+
+impl From<CpuSpecRow> for StoredCpuSpecDTO {
+    fn from(row: CpuSpecRow) -> Self {
+        // the values were converted from u32 when they were stored
+        let count = |value: i64| value as u32;
+
+        StoredCpuSpecDTO {
+            id: row.id,
+            result_count: row.result_count,
+            spec: CpuSpecDTO {
+                manufacturer: row.manufacturer,
+                name: row.name,
+                alias: row.alias,
+                cores: count(row.cores),
+                threads: count(row.threads),
+                base_ghz: row.base_ghz,
+                boost_ghz: row.boost_ghz,
+                l1_kib: count(row.l1_kib),
+                l2_kib: count(row.l2_kib),
+                l3_mib: row.l3_mib.map(count),
+                l1_associativity: row.l1_associativity.map(count),
+                page_size: count(row.page_size),
+                load_buffer: row.load_buffer.map(count),
+                l1_dtlb: row.l1_dtlb.map(count),
+                microarchitecture: row.microarchitecture,
+            },
+        }
+    }
+}
+
+#[derive(FromRow)]
+pub struct UnmatchedModelRow {
+    pub model_normalized: String,
+    pub result_count: i64,
+}
+
+impl From<UnmatchedModelRow> for UnmatchedModelDTO {
+    fn from(row: UnmatchedModelRow) -> Self {
+        UnmatchedModelDTO {
+            model_normalized: row.model_normalized,
+            result_count: row.result_count,
+        }
+    }
+}
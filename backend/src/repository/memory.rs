@@ -0,0 +1,382 @@
+use std::error::Error;
+use std::sync::Mutex;
+
+use async_trait::async_trait;
+use chrono::Utc;
+
+use common::dto::chart::ChartDTO;
+use common::dto::cpu::{CpuSpecDTO, StoredCpuSpecDTO, UnmatchedModelDTO};
+use common::dto::result::{ResultDTO, ResultPageDTO, StoredResultDTO};
+use common::fingerprint::Fingerprint;
+
+use crate::repository::cpu::AliasTaken;
+use crate::repository::result::{into_page, ResultFilter, StoredFingerprint, UserAgentFamily};
+use crate::repository::{CpuSpecRepository, FingerprintVisitor, ResultRepository, ResultVisitor};
+
+/// Repository keeping the results in memory, for tests which need no database
+#[derive(Default)]
+pub struct InMemoryRepository {
+    /// Results with their charts, in the order of insertion
+    results: Mutex<Vec<(StoredResultDTO, Vec<ChartDTO>)>>,
+    /// Fingerprints of the results, in the order of insertion
+    fingerprints: Mutex<Vec<StoredFingerprint>>,
+    /// CPU specifications with their ids, in the order of insertion
+    cpu_specs: Mutex<Vec<(i64, CpuSpecDTO)>>,
+}
+
+impl InMemoryRepository {
+    /// Results matching the filter, oldest first
+    fn matching(&self, filter: &ResultFilter) -> Vec<StoredResultDTO> {
+        self.results
+            .lock()
+            .unwrap()
+            .iter()
+            .map(|(stored, _)| stored)
+            .filter(|stored| matches(filter, stored))
+            .cloned()
+            .collect()
+    }
+
+    fn stored_cpu_spec(&self, id: i64, spec: &CpuSpecDTO) -> StoredCpuSpecDTO {
+        let result_count = self
+            .results
+            .lock()
+            .unwrap()
+            .iter()
+            .filter(|(stored, _)| spec_matches(spec, &stored.model_normalized))
+            .count();
+
+        StoredCpuSpecDTO {
+            id,
+            result_count: result_count as i64,
+            spec: spec.clone(),
+        }
+    }
+}
+
+#[async_trait(?Send)]
+impl ResultRepository for InMemoryRepository {
+    async fn insert_result(
+        &self,
+        result: &ResultDTO,
+        model_normalized: &str,
+        fingerprint: &Fingerprint,
+        charts: &[ChartDTO],
+    ) -> Result<i64, Box<dyn Error>> {
+        let mut results = self.results.lock().unwrap();
+        let id = results.len() as i64 + 1;
+        let stored = StoredResultDTO {
+            id,
+            created_at: Some(Utc::now()),
+            model_normalized: model_normalized.to_string(),
+            // the databases store the benchmark of every run, even for legacy uploads
+            result: ResultDTO {
+                benchmarks: result.benchmark_types(),
+                ..result.clone()
+            },
+        };
+        self.fingerprints.lock().unwrap().push(StoredFingerprint {
+            id,
+            created_at: stored.created_at,
+            model_normalized: stored.model_normalized.clone(),
+            fingerprint: fingerprint.clone(),
+        });
+        results.push((stored, charts.to_vec()));
+
+        Ok(id)
+    }
+
+    async fn find_charts(&self, id: i64) -> Result<Option<Vec<ChartDTO>>, Box<dyn Error>> {
+        let results = self.results.lock().unwrap();
+        Ok(results
+            .iter()
+            .find(|(stored, _)| stored.id == id)
+            .map(|(_, charts)| charts.clone()))
+    }
+
+    async fn update_charts(&self, id: i64, charts: &[ChartDTO]) -> Result<(), Box<dyn Error>> {
+        let mut results = self.results.lock().unwrap();
+        if let Some((_, stored_charts)) = results.iter_mut().find(|(stored, _)| stored.id == id) {
+            *stored_charts = charts.to_vec();
+        }
+
+        Ok(())
+    }
+
+    async fn find_result(&self, id: i64) -> Result<Option<StoredResultDTO>, Box<dyn Error>> {
+        let results = self.results.lock().unwrap();
+        Ok(results
+            .iter()
+            .find(|(stored, _)| stored.id == id)
+            .map(|(stored, _)| stored.clone()))
+    }
+
+    async fn find_results(
+        &self,
+        filter: &ResultFilter,
+        cursor: Option<i64>,
+        limit: i64,
+    ) -> Result<ResultPageDTO, Box<dyn Error>> {
+        let results = self
+            .matching(filter)
+            .into_iter()
+            .rev()
+            .filter(|stored| cursor.map_or(true, |cursor| stored.id < cursor))
+            .take(limit as usize + 1)
+            .collect();
+
+        Ok(into_page(results, limit))
+    }
+
+    async fn for_each_result(
+        &self,
+        filter: &ResultFilter,
+        visit: &mut ResultVisitor<'_>,
+    ) -> Result<(), Box<dyn Error>> {
+        // the lock is not held while visiting, the visitor may use the repository
+        for stored in self.matching(filter) {
+            visit(stored).await?;
+        }
+
+        Ok(())
+    }
+
+    async fn for_each_fingerprint(
+        &self,
+        visit: &mut FingerprintVisitor<'_>,
+    ) -> Result<(), Box<dyn Error>> {
+        for stored in self.fingerprints.lock().unwrap().iter() {
+            visit(stored.clone());
+        }
+
+        Ok(())
+    }
+
+    async fn find_fingerprint(&self, id: i64) -> Result<Option<StoredFingerprint>, Box<dyn Error>> {
+        Ok(self
+            .fingerprints
+            .lock()
+            .unwrap()
+            .iter()
+            .find(|stored| stored.id == id)
+            .cloned())
+    }
+
+    async fn find_fingerprints_by_hash(
+        &self,
+        hash: &str,
+    ) -> Result<Vec<StoredFingerprint>, Box<dyn Error>> {
+        Ok(self
+            .fingerprints
+            .lock()
+            .unwrap()
+            .iter()
+            .filter(|stored| stored.fingerprint.hash == hash)
+            .cloned()
+            .collect())
+    }
+}
+
+#[async_trait(?Send)]
+impl CpuSpecRepository for InMemoryRepository {
+    async fn insert_cpu_spec(&self, spec: &CpuSpecDTO) -> Result<Option<i64>, Box<dyn Error>> {
+        let mut specs = self.cpu_specs.lock().unwrap();
+        if specs.iter().any(|(_, stored)| stored.name == spec.name) {
+            return Ok(None);
+        }
+        check_alias(&specs, None, spec)?;
+
+        let id = specs.iter().map(|(id, _)| *id).max().unwrap_or_default() + 1;
+        specs.push((id, spec.clone()));
+
+        Ok(Some(id))
+    }
+
+    async fn update_cpu_spec(&self, id: i64, spec: &CpuSpecDTO) -> Result<bool, Box<dyn Error>> {
+        let mut specs = self.cpu_specs.lock().unwrap();
+        if specs
+            .iter()
+            .any(|(other, stored)| *other != id && stored.name == spec.name)
+        {
+            return Ok(false);
+        }
+        check_alias(&specs, Some(id), spec)?;
+
+        Ok(match specs.iter_mut().find(|(stored, _)| *stored == id) {
+            Some((_, stored)) => {
+                *stored = spec.clone();
+                true
+            }
+            None => false,
+        })
+    }
+
+    async fn delete_cpu_spec(&self, id: i64) -> Result<bool, Box<dyn Error>> {
+        let mut specs = self.cpu_specs.lock().unwrap();
+        let count = specs.len();
+        specs.retain(|(stored, _)| *stored != id);
+
+        Ok(specs.len() < count)
+    }
+
+    async fn import_cpu_specs(&self, specs: &[CpuSpecDTO]) -> Result<(), Box<dyn Error>> {
+        // the same as in the databases, the aliases of the replaced specifications may move
+        for (_, stored) in self.cpu_specs.lock().unwrap().iter_mut() {
+            if specs.iter().any(|spec| spec.name == stored.name) {
+                stored.alias = None;
+            }
+        }
+        for spec in specs {
+            let existing = self
+                .cpu_specs
+                .lock()
+                .unwrap()
+                .iter()
+                .find(|(_, stored)| stored.name == spec.name)
+                .map(|(id, _)| *id);
+            match existing {
+                Some(id) => self.update_cpu_spec(id, spec).await?,
+                None => self.insert_cpu_spec(spec).await?.is_some(),
+            };
+        }
+
+        Ok(())
+    }
+
+    async fn find_cpu_spec(&self, id: i64) -> Result<Option<StoredCpuSpecDTO>, Box<dyn Error>> {
+        let spec = self
+            .cpu_specs
+            .lock()
+            .unwrap()
+            .iter()
+            .find(|(stored, _)| *stored == id)
+            .map(|(_, spec)| spec.clone());
+
+        Ok(spec.map(|spec| self.stored_cpu_spec(id, &spec)))
+    }
+
+    async fn find_cpu_specs(&self) -> Result<Vec<StoredCpuSpecDTO>, Box<dyn Error>> {
+        let mut specs = self.cpu_specs.lock().unwrap().clone();
+        specs.sort_by(|(_, a), (_, b)| a.name.cmp(&b.name));
+
+        Ok(specs
+            .iter()
+            .map(|(id, spec)| self.stored_cpu_spec(*id, spec))
+            .collect())
+    }
+
+    async fn find_model_cpu_specs(
+        &self,
+        model_normalized: &str,
+    ) -> Result<Vec<StoredCpuSpecDTO>, Box<dyn Error>> {
+        let specs = self.cpu_specs.lock().unwrap().clone();
+
+        Ok(specs
+            .iter()
+            .filter(|(_, spec)| spec_matches(spec, model_normalized))
+            .map(|(id, spec)| self.stored_cpu_spec(*id, spec))
+            .collect())
+    }
+
+    async fn find_unmatched_models(&self) -> Result<Vec<UnmatchedModelDTO>, Box<dyn Error>> {
+        let specs = self.cpu_specs.lock().unwrap().clone();
+        let mut unmatched: Vec<UnmatchedModelDTO> = vec![];
+        for (stored, _) in self.results.lock().unwrap().iter() {
+            let model = &stored.model_normalized;
+            if specs.iter().any(|(_, spec)| spec_matches(spec, model)) {
+                continue;
+            }
+            match unmatched
+                .iter_mut()
+                .find(|unmatched| unmatched.model_normalized == *model)
+            {
+                Some(unmatched) => unmatched.result_count += 1,
+                None => unmatched.push(UnmatchedModelDTO {
+                    model_normalized: model.clone(),
+                    result_count: 1,
+                }),
+            }
+        }
+        unmatched.sort_by(|a, b| {
+            (b.result_count, &a.model_normalized).cmp(&(a.result_count, &b.model_normalized))
+        });
+
+        Ok(unmatched)
+    }
+}
+
+fn spec_matches(spec: &CpuSpecDTO, model_normalized: &str) -> bool {
+    spec.name == model_normalized || spec.alias.as_deref() == Some(model_normalized)
+}
+
+/// Fails like the unique index of the aliases if another specification than `id` has the alias
+fn check_alias(
+    specs: &[(i64, CpuSpecDTO)],
+    id: Option<i64>,
+    spec: &CpuSpecDTO,
+) -> Result<(), AliasTaken> {
+    let Some(alias) = &spec.alias else {
+        return Ok(());
+    };
+    if specs
+        .iter()
+        .any(|(other, stored)| Some(*other) != id && stored.alias.as_ref() == Some(alias))
+    {
+        return Err(AliasTaken {
+            alias: alias.clone(),
+        });
+    }
+
+    Ok(())
+}
+
+/// Evaluates the filter the same way as the SQL conditions of `ResultFilter`
+fn matches(filter: &ResultFilter, stored: &StoredResultDTO) -> bool {
+    let result = &stored.result;
+
+    filter.model.as_ref().map_or(true, |model| {
+        let model = model.to_lowercase();
+        result.model.to_lowercase().contains(&model)
+            || stored.model_normalized.to_lowercase().contains(&model)
+    }) && filter
+        .user_agent_family
+        .map_or(true, |family| family_matches(family, &result.user_agent))
+        // results without the time of the upload are left out by any time range, as in SQL
+        && filter
+            .from
+            .map_or(true, |from| stored.created_at.map_or(false, |at| at >= from))
+        && filter
+            .to
+            .map_or(true, |to| stored.created_at.map_or(false, |at| at < to))
+        && filter.benchmark.map_or(true, |benchmark| {
+            result
+                .benchmarks
+                .iter()
+                .zip(result.benchmark_results.iter())
+                .any(|(run, data)| {
+                    *run == benchmark && !data.is_null() && data.as_array() != Some(&vec![])
+                })
+        })
+}
+
+fn family_matches(family: UserAgentFamily, user_agent: &str) -> bool {
+    let agent = user_agent.to_lowercase();
+    let has = |token: &str| agent.contains(token);
+    let native = user_agent.starts_with("native ");
+
+    match family {
+        UserAgentFamily::Firefox => has("firefox/"),
+        UserAgentFamily::Chrome => has("chrome/") && !has("edg/"),
+        UserAgentFamily::Edge => has("edg/"),
+        UserAgentFamily::Safari => has("safari/") && !has("chrome/") && !has("chromium/"),
+        UserAgentFamily::Native => native,
+        UserAgentFamily::Other => {
+            !(has("firefox/")
+                || has("chrome/")
+                || has("chromium/")
+                || has("edg/")
+                || has("safari/")
+                || native)
+        }
+    }
+}
@@ -1 +1,137 @@
+use std::error::Error;
+use std::sync::Arc;
+
+use async_trait::async_trait;
+use futures::future::LocalBoxFuture;
+
+use common::dto::chart::ChartDTO;
+use common::dto::cpu::{CpuSpecDTO, StoredCpuSpecDTO, UnmatchedModelDTO};
+use common::dto::result::{ResultDTO, ResultPageDTO, StoredResultDTO};
+use common::fingerprint::Fingerprint;
+
+use crate::config::BackendConfig;
+use crate::repository::postgres::PostgresRepository;
+use crate::repository::result::{ResultFilter, StoredFingerprint};
+use crate::repository::sqlite::SqliteRepository;
+
+pub mod cpu;
+#[cfg(test)]
+pub mod memory;
+pub mod postgres;
 pub mod result;
+pub mod sqlite;
+
+/// Receives the results one by one, stops the iteration by returning an error
+pub type ResultVisitor<'a> =
+    dyn FnMut(StoredResultDTO) -> LocalBoxFuture<'static, Result<(), Box<dyn Error>>> + 'a;
+
+/// Receives the fingerprints one by one
+pub type FingerprintVisitor<'a> = dyn FnMut(StoredFingerprint) + 'a;
+
+/// Storage of the uploaded results
+#[async_trait(?Send)]
+pub trait ResultRepository: Send + Sync {
+    /// Stores the result and a benchmark run for each of its benchmarks. Returns the id of the result.
+    async fn insert_result(
+        &self,
+        result: &ResultDTO,
+        model_normalized: &str,
+        fingerprint: &Fingerprint,
+        charts: &[ChartDTO],
+    ) -> Result<i64, Box<dyn Error>>;
+
+    /// Stored charts of the result, empty if they were not rendered yet
+    async fn find_charts(&self, id: i64) -> Result<Option<Vec<ChartDTO>>, Box<dyn Error>>;
+
+    async fn update_charts(&self, id: i64, charts: &[ChartDTO]) -> Result<(), Box<dyn Error>>;
+
+    async fn find_result(&self, id: i64) -> Result<Option<StoredResultDTO>, Box<dyn Error>>;
+
+    /// Page of the results matching the filter, newest first.
+    /// The cursor is the id of the last result of the previous page.
+    async fn find_results(
+        &self,
+        filter: &ResultFilter,
+        cursor: Option<i64>,
+        limit: i64,
+    ) -> Result<ResultPageDTO, Box<dyn Error>>;
+
+    /// Passes the results matching the filter to `visit` one by one, oldest first,
+    /// without loading all of them into memory. Stops at the first error returned by `visit`.
+    async fn for_each_result(
+        &self,
+        filter: &ResultFilter,
+        visit: &mut ResultVisitor<'_>,
+    ) -> Result<(), Box<dyn Error>>;
+
+    /// Passes the current fingerprints of all the results to `visit` one by one, oldest first,
+    /// without loading all of them into memory
+    async fn for_each_fingerprint(
+        &self,
+        visit: &mut FingerprintVisitor<'_>,
+    ) -> Result<(), Box<dyn Error>>;
+
+    /// Fingerprint of the result, `None` if it doesn't exist or has no fingerprint
+    async fn find_fingerprint(&self, id: i64) -> Result<Option<StoredFingerprint>, Box<dyn Error>>;
+
+    /// Fingerprints with the hash, oldest first, looked up by the index of the hashes
+    async fn find_fingerprints_by_hash(
+        &self,
+        hash: &str,
+    ) -> Result<Vec<StoredFingerprint>, Box<dyn Error>>;
+}
+
+/// Storage of the CPU specifications, matched with the results by their normalized model
+#[async_trait(?Send)]
+pub trait CpuSpecRepository: Send + Sync {
+    /// Stores the specification, unless another one has the same name. Returns its id.
+    async fn insert_cpu_spec(&self, spec: &CpuSpecDTO) -> Result<Option<i64>, Box<dyn Error>>;
+
+    /// Replaces the specification, unless another one has the same name.
+    /// Returns whether it was replaced.
+    async fn update_cpu_spec(&self, id: i64, spec: &CpuSpecDTO) -> Result<bool, Box<dyn Error>>;
+
+    /// Returns whether the specification existed
+    async fn delete_cpu_spec(&self, id: i64) -> Result<bool, Box<dyn Error>>;
+
+    /// Stores the specifications, replacing the ones with the same names
+    async fn import_cpu_specs(&self, specs: &[CpuSpecDTO]) -> Result<(), Box<dyn Error>>;
+
+    async fn find_cpu_spec(&self, id: i64) -> Result<Option<StoredCpuSpecDTO>, Box<dyn Error>>;
+
+    /// All the specifications, ordered by name
+    async fn find_cpu_specs(&self) -> Result<Vec<StoredCpuSpecDTO>, Box<dyn Error>>;
+
+    /// Specifications with the normalized model as the name or the alias,
+    /// `common::cpu::find_cpu_spec` picks the one matching the model
+    async fn find_model_cpu_specs(
+        &self,
+        model_normalized: &str,
+    ) -> Result<Vec<StoredCpuSpecDTO>, Box<dyn Error>>;
+
+    /// Normalized models of the results without a specification, the most common first
+    async fn find_unmatched_models(&self) -> Result<Vec<UnmatchedModelDTO>, Box<dyn Error>>;
+}
+
+/// All the storage of the backend
+pub trait Repository: ResultRepository + CpuSpecRepository {}
+
+impl<T: ResultRepository + CpuSpecRepository> Repository for T {}
+
+/// Connects to the database given by the scheme of `database_url`
+/// and applies its migrations
+pub async fn connect_repository(
+    config: &BackendConfig,
+) -> Result<Arc<dyn Repository>, Box<dyn Error>> {
+    let url = config.database_url.as_str();
+    let connections = config.database_connection_count;
+
+    // only the scheme is reported, the rest of the URL may contain the password
+    match url.split(':').next().unwrap_or_default() {
+        "postgres" | "postgresql" => Ok(Arc::new(
+            PostgresRepository::connect(url, connections).await?,
+        )),
+        "sqlite" => Ok(Arc::new(SqliteRepository::connect(url, connections).await?)),
+        scheme => Err(format!("Unsupported database URL scheme '{}'", scheme).into()),
+    }
+}
@@ -0,0 +1,428 @@
+use std::error::Error;
+
+use async_trait::async_trait;
+use chrono::Utc;
+use futures::TryStreamExt;
+use log::error;
+use serde_json::{json, Value};
+use sqlx::postgres::PgPoolOptions;
+use sqlx::{Pool, Postgres, QueryBuilder};
+
+use common::dto::chart::ChartDTO;
+use common::dto::cpu::{CpuSpecDTO, StoredCpuSpecDTO, UnmatchedModelDTO};
+use common::dto::result::{ResultDTO, ResultPageDTO, StoredResultDTO};
+use common::fingerprint::{Fingerprint, FINGERPRINT_VERSION};
+use common::model::{normalize_model, RULES_VERSION};
+
+use crate::repository::cpu::{
+    clear_aliases_query, insert_cpu_spec_query, map_alias_violation, update_cpu_spec_query,
+    upsert_cpu_spec_query, CpuSpecRow, UnmatchedModelRow, MATCHING_MODEL, SELECT_CPU_SPECS,
+    SELECT_UNMATCHED_MODELS,
+};
+use crate::repository::result::{
+    benchmark_name, into_page, run_parameters, seed_to_column, select_fingerprints, Dialect,
+    FingerprintRow, ResultFilter, ResultRow, StoredFingerprint, FINGERPRINT_BATCH_SIZE,
+};
+use crate::repository::{CpuSpecRepository, FingerprintVisitor, ResultRepository, ResultVisitor};
+
+/// Selects the results together with their benchmark runs, aggregated into a JSON array
+const SELECT_RESULTS: &str =
+    "SELECT id, created_at, model_raw, coalesce(model_normalized, model_raw) AS model_normalized, user_agent,
+        capabilities, seed, schema_version, frontend_version, parameters, model_check,
+        coalesce((
+            SELECT jsonb_agg(jsonb_build_object('benchmark', benchmark, 'duration', duration, 'data', data) ORDER BY id)
+            FROM benchmark_run WHERE benchmark_run.result_id = upload_benchmarkresult.id
+        ), '[]') AS runs
+    FROM upload_benchmarkresult";
+
+pub struct PostgresRepository {
+    pool: Pool<Postgres>,
+}
+
+impl PostgresRepository {
+    pub async fn connect(url: &str, connections: u32) -> Result<Self, Box<dyn Error>> {
+        let pool = PgPoolOptions::new()
+            .max_connections(connections)
+            .connect(url)
+            .await?;
+
+        sqlx::migrate!("./migrations/postgres").run(&pool).await?;
+
+        let repository = PostgresRepository { pool };
+        repository.normalize_models().await?;
+        repository.fingerprint_results().await?;
+
+        Ok(repository)
+    }
+
+    /// Normalizes the models of the results normalized by outdated rules or not at all
+    async fn normalize_models(&self) -> Result<(), Box<dyn Error>> {
+        let rows = sqlx::query_as::<_, (i64, String)>(
+            "SELECT id, model_raw FROM upload_benchmarkresult
+            WHERE model_rules_version IS NULL OR model_rules_version <> $1",
+        )
+        .bind(RULES_VERSION)
+        .fetch_all(&self.pool)
+        .await?;
+
+        let mut transaction = self.pool.begin().await?;
+        for (id, model_raw) in rows {
+            sqlx::query(
+                "UPDATE upload_benchmarkresult SET model_normalized = $2, model_rules_version = $3 WHERE id = $1",
+            )
+            .bind(id)
+            .bind(normalize_model(&model_raw))
+            .bind(RULES_VERSION)
+            .execute(&mut transaction)
+                .await?;
+        }
+        transaction.commit().await?;
+
+        Ok(())
+    }
+
+    /// Fingerprints the results fingerprinted by an outdated layout or not at all
+    async fn fingerprint_results(&self) -> Result<(), Box<dyn Error>> {
+        // in batches, so the results with all their runs are not loaded at once
+        let mut last_id = 0;
+        loop {
+            let rows = sqlx::query_as::<_, ResultRow>(&format!(
+                "{} WHERE (fingerprint_version IS NULL OR fingerprint_version <> $1) AND id > $2
+                ORDER BY id LIMIT $3",
+                SELECT_RESULTS
+            ))
+            .bind(FINGERPRINT_VERSION)
+            .bind(last_id)
+            .bind(FINGERPRINT_BATCH_SIZE)
+            .fetch_all(&self.pool)
+            .await?;
+            let Some(last) = rows.last() else {
+                return Ok(());
+            };
+            last_id = last.id;
+
+            let mut transaction = self.pool.begin().await?;
+            for row in rows {
+                // a broken result must not stop the startup, it stays outdated until it's fixed
+                let stored = match StoredResultDTO::try_from(row) {
+                    Ok(stored) => stored,
+                    Err(error) => {
+                        error!("Skipping the fingerprint: {}", error);
+                        continue;
+                    }
+                };
+                let fingerprint = Fingerprint::of(&stored.result);
+                sqlx::query(
+                    "UPDATE upload_benchmarkresult SET fingerprint = $2, features = $3, fingerprint_version = $4
+                    WHERE id = $1",
+                )
+                .bind(stored.id)
+                .bind(fingerprint.hash)
+                .bind(json!(fingerprint.features))
+                .bind(FINGERPRINT_VERSION)
+                .execute(&mut transaction)
+                .await?;
+            }
+            transaction.commit().await?;
+        }
+    }
+}
+
+#[async_trait(?Send)]
+impl ResultRepository for PostgresRepository {
+    async fn insert_result(
+        &self,
+        result: &ResultDTO,
+        model_normalized: &str,
+        fingerprint: &Fingerprint,
+        charts: &[ChartDTO],
+    ) -> Result<i64, Box<dyn Error>> {
+        let mut transaction = self.pool.begin().await?;
+
+        let id = sqlx::query_scalar::<_, i64>(
+            "INSERT INTO upload_benchmarkresult (model_raw, model_normalized, user_agent, b64_charts, capabilities, seed,
+                created_at, schema_version, frontend_version, parameters, model_check, fingerprint, features,
+                model_rules_version, fingerprint_version)
+            VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10, $11, $12, $13, $14, $15)
+            RETURNING id
+            ",
+        )
+        .bind(&result.model)
+        .bind(model_normalized)
+        .bind(&result.user_agent)
+        .bind(json!(charts))
+        .bind(
+            result
+                .capabilities
+                .as_ref()
+                .map(|capabilities| json!(capabilities)),
+        )
+        .bind(seed_to_column(result.seed))
+        .bind(Utc::now())
+        .bind(&result.schema_version)
+        .bind(&result.frontend_version)
+        .bind(
+            result
+                .parameters
+                .as_ref()
+                .map(|parameters| json!(parameters)),
+        )
+        .bind(
+            result
+                .model_check
+                .as_ref()
+                .map(|model_check| json!(model_check)),
+        )
+        .bind(&fingerprint.hash)
+        .bind(json!(fingerprint.features))
+        .bind(RULES_VERSION)
+        .bind(FINGERPRINT_VERSION)
+        .fetch_one(&mut transaction)
+        .await?;
+
+        let runs = result
+            .benchmark_types()
+            .into_iter()
+            .zip(result.benchmark_results.iter())
+            .zip(result.times.iter());
+        for ((benchmark, data), duration) in runs {
+            sqlx::query(
+                "INSERT INTO benchmark_run (result_id, benchmark, parameters, duration, data)
+                VALUES ($1, $2, $3, $4, $5)
+                ",
+            )
+            .bind(id)
+            .bind(benchmark_name(benchmark))
+            .bind(run_parameters(benchmark, result))
+            .bind(duration)
+            .bind(data)
+            .execute(&mut transaction)
+            .await?;
+        }
+
+        transaction.commit().await?;
+
+        Ok(id)
+    }
+
+    async fn find_charts(&self, id: i64) -> Result<Option<Vec<ChartDTO>>, Box<dyn Error>> {
+        let charts = sqlx::query_scalar::<_, Value>(
+            "SELECT b64_charts FROM upload_benchmarkresult WHERE id = $1",
+        )
+        .bind(id)
+        .fetch_optional(&self.pool)
+        .await?;
+
+        Ok(charts.map(|charts| serde_json::from_value(charts).unwrap_or_default()))
+    }
+
+    async fn update_charts(&self, id: i64, charts: &[ChartDTO]) -> Result<(), Box<dyn Error>> {
+        sqlx::query("UPDATE upload_benchmarkresult SET b64_charts = $2 WHERE id = $1")
+            .bind(id)
+            .bind(json!(charts))
+            .execute(&self.pool)
+            .await?;
+
+        Ok(())
+    }
+
+    async fn find_result(&self, id: i64) -> Result<Option<StoredResultDTO>, Box<dyn Error>> {
+        let mut builder = QueryBuilder::new(SELECT_RESULTS);
+        builder.push(" WHERE id = ").push_bind(id);
+
+        let row = builder
+            .build_query_as::<ResultRow>()
+            .fetch_optional(&self.pool)
+            .await?;
+
+        Ok(row.map(StoredResultDTO::try_from).transpose()?)
+    }
+
+    async fn find_results(
+        &self,
+        filter: &ResultFilter,
+        cursor: Option<i64>,
+        limit: i64,
+    ) -> Result<ResultPageDTO, Box<dyn Error>> {
+        let mut builder = QueryBuilder::new(SELECT_RESULTS);
+        filter.push_conditions(&mut builder, Dialect::Postgres);
+        if let Some(cursor) = cursor {
+            builder.push(" AND id < ").push_bind(cursor);
+        }
+        builder
+            .push(" ORDER BY id DESC LIMIT ")
+            .push_bind(limit + 1);
+
+        let results = builder
+            .build_query_as::<ResultRow>()
+            .fetch_all(&self.pool)
+            .await?
+            .into_iter()
+            .map(StoredResultDTO::try_from)
+            .collect::<Result<Vec<_>, _>>()?;
+
+        Ok(into_page(results, limit))
+    }
+
+    async fn for_each_result(
+        &self,
+        filter: &ResultFilter,
+        visit: &mut ResultVisitor<'_>,
+    ) -> Result<(), Box<dyn Error>> {
+        let mut builder = QueryBuilder::new(SELECT_RESULTS);
+        filter.push_conditions(&mut builder, Dialect::Postgres);
+        builder.push(" ORDER BY id");
+
+        let mut rows = builder.build_query_as::<ResultRow>().fetch(&self.pool);
+        while let Some(row) = rows.try_next().await? {
+            visit(StoredResultDTO::try_from(row)?).await?;
+        }
+
+        Ok(())
+    }
+
+    async fn for_each_fingerprint(
+        &self,
+        visit: &mut FingerprintVisitor<'_>,
+    ) -> Result<(), Box<dyn Error>> {
+        let mut builder = select_fingerprints::<Postgres>();
+        builder.push(" ORDER BY id");
+
+        let mut rows = builder.build_query_as::<FingerprintRow>().fetch(&self.pool);
+        while let Some(row) = rows.try_next().await? {
+            visit(StoredFingerprint::from(row));
+        }
+
+        Ok(())
+    }
+
+    async fn find_fingerprint(&self, id: i64) -> Result<Option<StoredFingerprint>, Box<dyn Error>> {
+        let mut builder = select_fingerprints::<Postgres>();
+        builder.push(" AND id = ").push_bind(id);
+
+        let row = builder
+            .build_query_as::<FingerprintRow>()
+            .fetch_optional(&self.pool)
+            .await?;
+
+        Ok(row.map(StoredFingerprint::from))
+    }
+
+    async fn find_fingerprints_by_hash(
+        &self,
+        hash: &str,
+    ) -> Result<Vec<StoredFingerprint>, Box<dyn Error>> {
+        let mut builder = select_fingerprints::<Postgres>();
+        builder
+            .push(" AND fingerprint = ")
+            .push_bind(hash)
+            .push(" ORDER BY id");
+
+        let rows = builder
+            .build_query_as::<FingerprintRow>()
+            .fetch_all(&self.pool)
+            .await?;
+
+        Ok(rows.into_iter().map(StoredFingerprint::from).collect())
+    }
+}
+
+#[async_trait(?Send)]
+impl CpuSpecRepository for PostgresRepository {
+    async fn insert_cpu_spec(&self, spec: &CpuSpecDTO) -> Result<Option<i64>, Box<dyn Error>> {
+        let id = insert_cpu_spec_query::<Postgres>(spec)
+            .build_query_as::<(i64,)>()
+            .fetch_optional(&self.pool)
+            .await
+            .map_err(|error| map_alias_violation(error, spec))?;
+
+        Ok(id.map(|(id,)| id))
+    }
+
+    async fn update_cpu_spec(&self, id: i64, spec: &CpuSpecDTO) -> Result<bool, Box<dyn Error>> {
+        let updated = update_cpu_spec_query::<Postgres>(id, spec)
+            .build()
+            .execute(&self.pool)
+            .await
+            .map_err(|error| map_alias_violation(error, spec))?;
+
+        Ok(updated.rows_affected() > 0)
+    }
+
+    async fn delete_cpu_spec(&self, id: i64) -> Result<bool, Box<dyn Error>> {
+        let deleted = sqlx::query("DELETE FROM cpu_spec WHERE id = $1")
+            .bind(id)
+            .execute(&self.pool)
+            .await?;
+
+        Ok(deleted.rows_affected() > 0)
+    }
+
+    async fn import_cpu_specs(&self, specs: &[CpuSpecDTO]) -> Result<(), Box<dyn Error>> {
+        if specs.is_empty() {
+            return Ok(());
+        }
+
+        let mut transaction = self.pool.begin().await?;
+        clear_aliases_query::<Postgres>(specs)
+            .build()
+            .execute(&mut transaction)
+            .await?;
+        for spec in specs {
+            upsert_cpu_spec_query::<Postgres>(spec)
+                .build()
+                .execute(&mut transaction)
+                .await
+                .map_err(|error| map_alias_violation(error, spec))?;
+        }
+        transaction.commit().await?;
+
+        Ok(())
+    }
+
+    async fn find_cpu_spec(&self, id: i64) -> Result<Option<StoredCpuSpecDTO>, Box<dyn Error>> {
+        let mut builder = QueryBuilder::new(SELECT_CPU_SPECS);
+        builder.push(" WHERE cpu_spec.id = ").push_bind(id);
+
+        let row = builder
+            .build_query_as::<CpuSpecRow>()
+            .fetch_optional(&self.pool)
+            .await?;
+
+        Ok(row.map(StoredCpuSpecDTO::from))
+    }
+
+    async fn find_cpu_specs(&self) -> Result<Vec<StoredCpuSpecDTO>, Box<dyn Error>> {
+        let rows = sqlx::query_as::<_, CpuSpecRow>(&format!("{} ORDER BY name", SELECT_CPU_SPECS))
+            .fetch_all(&self.pool)
+            .await?;
+
+        Ok(rows.into_iter().map(StoredCpuSpecDTO::from).collect())
+    }
+
+    async fn find_model_cpu_specs(
+        &self,
+        model_normalized: &str,
+    ) -> Result<Vec<StoredCpuSpecDTO>, Box<dyn Error>> {
+        let mut builder = QueryBuilder::new(SELECT_CPU_SPECS);
+        builder
+            .push(" WHERE ")
+            .push_bind(model_normalized)
+            .push(MATCHING_MODEL);
+
+        let rows = builder
+            .build_query_as::<CpuSpecRow>()
+            .fetch_all(&self.pool)
+            .await?;
+
+        Ok(rows.into_iter().map(StoredCpuSpecDTO::from).collect())
+    }
+
+    async fn find_unmatched_models(&self) -> Result<Vec<UnmatchedModelDTO>, Box<dyn Error>> {
+        let rows = sqlx::query_as::<_, UnmatchedModelRow>(SELECT_UNMATCHED_MODELS)
+            .fetch_all(&self.pool)
+            .await?;
+
+        Ok(rows.into_iter().map(UnmatchedModelDTO::from).collect())
+    }
+}
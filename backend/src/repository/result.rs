@@ -1,26 +1,406 @@
 use std::error::Error;
 
-use serde_json::json;
-use sqlx::{Pool, Postgres};
-
-use common::dto::result::ResultDTO;
-
-pub async fn insert_result<'a>(
-    pool: &Pool<Postgres>,
-    result: &ResultDTO,
-) -> Result<(), Box<dyn Error>> {
-    sqlx::query!(
-        "INSERT INTO upload_benchmarkresult (model, user_agent, benchmark_results, times, b64_charts)
-        VALUES ($1, $2, $3, $4, $5)
-        ",
-        &result.model,
-        &result.user_agent,
-        json!(result.benchmark_results),
-        json!(result.times),
-        json!("[]")
-    )
-        .execute(pool)
-        .await?;
-
-    Ok(())
+use chrono::{DateTime, Utc};
+use serde::Deserialize;
+use serde_json::{json, Value};
+use sqlx::types::Json;
+use sqlx::{Database, Encode, FromRow, QueryBuilder, Type};
+
+use common::benchmark::BenchmarkType;
+use common::dto::result::{ResultDTO, ResultPageDTO, StoredResultDTO};
+use common::fingerprint::{Fingerprint, FINGERPRINT_VERSION};
+
+/// Database specific parts of the queries
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Dialect {
+    Postgres,
+    Sqlite,
+}
+
+/// Browser family, recognized by the product tokens of the user agent
+#[derive(Deserialize, Clone, Copy, Debug, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum UserAgentFamily {
+    Firefox,
+    Chrome,
+    Edge,
+    Safari,
+    /// Results of the native benchmark runner
+    Native,
+    Other,
+}
+
+impl UserAgentFamily {
+    /// SQL condition matching the `user_agent` column, understood by all the supported databases
+    fn condition(&self) -> &'static str {
+        match self {
+            UserAgentFamily::Firefox => "lower(user_agent) LIKE '%firefox/%'",
+            UserAgentFamily::Chrome => {
+                "(lower(user_agent) LIKE '%chrome/%' AND lower(user_agent) NOT LIKE '%edg/%')"
+            }
+            UserAgentFamily::Edge => "lower(user_agent) LIKE '%edg/%'",
+            UserAgentFamily::Safari => {
+                "(lower(user_agent) LIKE '%safari/%' AND lower(user_agent) NOT LIKE '%chrome/%' AND lower(user_agent) NOT LIKE '%chromium/%')"
+            }
+            UserAgentFamily::Native => "user_agent LIKE 'native %'",
+            UserAgentFamily::Other => {
+                "NOT (lower(user_agent) LIKE '%firefox/%' OR lower(user_agent) LIKE '%chrome/%' OR lower(user_agent) LIKE '%chromium/%'
+                OR lower(user_agent) LIKE '%edg/%' OR lower(user_agent) LIKE '%safari/%' OR user_agent LIKE 'native %')"
+            }
+        }
+    }
+}
+
+#[derive(Default, Clone, Debug)]
+pub struct ResultFilter {
+    /// Case-insensitive substring of the model, either as typed or normalized
+    pub model: Option<String>,
+    pub user_agent_family: Option<UserAgentFamily>,
+    /// Inclusive start of the upload time range
+    pub from: Option<DateTime<Utc>>,
+    /// Exclusive end of the upload time range
+    pub to: Option<DateTime<Utc>>,
+    /// Benchmark which has to be present in the results
+    pub benchmark: Option<BenchmarkType>,
+}
+
+impl ResultFilter {
+    pub fn push_conditions<'a, DB>(&self, builder: &mut QueryBuilder<'a, DB>, dialect: Dialect)
+    where
+        DB: Database,
+        String: Encode<'a, DB> + Type<DB>,
+        DateTime<Utc>: Encode<'a, DB> + Type<DB>,
+    {
+        builder.push(" WHERE true");
+        if let Some(model) = &self.model {
+            builder.push(" AND (");
+            for (i, column) in ["model_raw", "model_normalized"].into_iter().enumerate() {
+                if i > 0 {
+                    builder.push(" OR ");
+                }
+                match dialect {
+                    Dialect::Postgres => builder
+                        .push("position(lower(")
+                        .push_bind(model.clone())
+                        .push(format!(") in lower({})) > 0", column)),
+                    Dialect::Sqlite => builder
+                        .push(format!("instr(lower({}), lower(", column))
+                        .push_bind(model.clone())
+                        .push(")) > 0"),
+                };
+            }
+            builder.push(")");
+        }
+        if let Some(family) = self.user_agent_family {
+            builder.push(" AND ").push(family.condition());
+        }
+        if let Some(from) = self.from {
+            builder.push(" AND created_at >= ").push_bind(from);
+        }
+        if let Some(to) = self.to {
+            builder.push(" AND created_at < ").push_bind(to);
+        }
+        if let Some(benchmark) = self.benchmark {
+            builder
+                .push(
+                    " AND EXISTS (SELECT 1 FROM benchmark_run
+                    WHERE benchmark_run.result_id = upload_benchmarkresult.id AND benchmark_run.benchmark = ",
+                )
+                .push_bind(benchmark_name(benchmark))
+                .push(match dialect {
+                    Dialect::Postgres => {
+                        " AND benchmark_run.data NOT IN ('[]'::jsonb, 'null'::jsonb))"
+                    }
+                    Dialect::Sqlite => " AND benchmark_run.data NOT IN ('[]', 'null'))",
+                });
+        }
+    }
+}
+
+/// Result with its benchmark runs aggregated into a JSON array, see `SELECT_RESULTS`
+/// of the repositories
+#[derive(FromRow)]
+pub struct ResultRow {
+    pub id: i64,
+    pub created_at: Option<DateTime<Utc>>,
+    pub model_raw: String,
+    pub model_normalized: String,
+    pub user_agent: String,
+    pub capabilities: Option<Json<Value>>,
+    pub seed: Option<i64>,
+    pub schema_version: String,
+    pub frontend_version: String,
+    pub parameters: Option<Json<Value>>,
+    pub model_check: Option<Json<Value>>,
+    pub runs: Json<Value>,
+}
+
+/// Row of `benchmark_run`, as aggregated into `ResultRow::runs`
+#[derive(Deserialize)]
+struct RunRow {
+    benchmark: BenchmarkType,
+    duration: f32,
+    data: Value,
+}
+
+/// Fails if the runs can't be parsed, so a result is never returned without its measurements
+impl TryFrom<ResultRow> for StoredResultDTO {
+    type Error = Box<dyn Error>;
+
+    fn try_from(row: ResultRow) -> Result<Self, Self::Error> {
+        let runs = serde_json::from_value::<Vec<RunRow>>(row.runs.0)
+            .map_err(|error| format!("Runs of the result {} can't be parsed: {}", row.id, error))?;
+
+        Ok(StoredResultDTO {
+            id: row.id,
+            created_at: row.created_at,
+            model_normalized: row.model_normalized,
+            result: ResultDTO {
+                model: row.model_raw,
+                user_agent: row.user_agent,
+                benchmarks: runs.iter().map(|run| run.benchmark).collect(),
+                times: runs.iter().map(|run| run.duration).collect(),
+                benchmark_results: runs.into_iter().map(|run| run.data).collect(),
+                // capabilities stored by older versions may not parse anymore
+                capabilities: row
+                    .capabilities
+                    .and_then(|capabilities| serde_json::from_value(capabilities.0).ok()),
+                // bit-cast back, see `seed_to_column`
+                seed: row.seed.map(|seed| seed as u64),
+                schema_version: row.schema_version,
+                frontend_version: row.frontend_version,
+                parameters: row
+                    .parameters
+                    .and_then(|parameters| serde_json::from_value(parameters.0).ok()),
+                model_check: row
+                    .model_check
+                    .and_then(|model_check| serde_json::from_value(model_check.0).ok()),
+            },
+        })
+    }
+}
+
+/// Selects the fingerprints of the results, see `select_fingerprints`
+const SELECT_FINGERPRINTS: &str =
+    "SELECT id, created_at, coalesce(model_normalized, model_raw) AS model_normalized, fingerprint, features
+    FROM upload_benchmarkresult
+    WHERE fingerprint IS NOT NULL AND features IS NOT NULL AND fingerprint_version = ";
+
+/// Selects the fingerprints of the current layout, further conditions are appended with `AND`.
+/// Fingerprints of an outdated layout are not comparable, they are computed again on the startup.
+pub fn select_fingerprints<'a, DB>() -> QueryBuilder<'a, DB>
+where
+    DB: Database,
+    i32: Encode<'a, DB> + Type<DB>,
+{
+    let mut builder = QueryBuilder::new(SELECT_FINGERPRINTS);
+    builder.push_bind(FINGERPRINT_VERSION);
+    builder
+}
+
+/// Number of the results fingerprinted again in a single transaction on the startup
+pub const FINGERPRINT_BATCH_SIZE: i64 = 500;
+
+/// Fingerprint of a stored result, compared by the similarity search
+#[derive(Clone, Debug, PartialEq)]
+pub struct StoredFingerprint {
+    pub id: i64,
+    pub created_at: Option<DateTime<Utc>>,
+    pub model_normalized: String,
+    pub fingerprint: Fingerprint,
+}
+
+/// Row of `select_fingerprints`
+#[derive(FromRow)]
+pub struct FingerprintRow {
+    pub id: i64,
+    pub created_at: Option<DateTime<Utc>>,
+    pub model_normalized: String,
+    pub fingerprint: String,
+    pub features: Json<Vec<Option<f64>>>,
+}
+
+impl From<FingerprintRow> for StoredFingerprint {
+    fn from(row: FingerprintRow) -> Self {
+        StoredFingerprint {
+            id: row.id,
+            created_at: row.created_at,
+            model_normalized: row.model_normalized,
+            fingerprint: Fingerprint {
+                features: row.features.0,
+                hash: row.fingerprint,
+            },
+        }
+    }
+}
+
+/// Name of the benchmark in `benchmark_run`, the same as in JSON
+pub fn benchmark_name(benchmark: BenchmarkType) -> String {
+    json!(benchmark).as_str().unwrap_or_default().to_string()
+}
+
+/// Parameters of the run which influence only the given benchmark
+pub fn run_parameters(benchmark: BenchmarkType, result: &ResultDTO) -> Option<Value> {
+    let parameters = result.parameters.as_ref()?;
+    match benchmark {
+        BenchmarkType::CacheSize => Some(json!({ "cache_size_mode": parameters.cache_size_mode })),
+        _ => None,
+    }
+}
+
+/// Seeds are stored with the same bit pattern, as the databases have no unsigned 64-bit type
+pub fn seed_to_column(seed: Option<u64>) -> Option<i64> {
+    seed.map(|seed| seed as i64)
+}
+
+/// Turns the rows fetched for a page into the page. One more row than the limit
+/// is fetched to tell whether there is a next page.
+pub fn into_page(mut results: Vec<StoredResultDTO>, limit: i64) -> ResultPageDTO {
+    let next_cursor = if results.len() as i64 > limit {
+        results.truncate(limit as usize);
+        results.last().map(|result| result.id)
+    } else {
+        None
+    };
+
+    ResultPageDTO {
+        results,
+        next_cursor,
+    }
+}
+
+/*
+ * TESTS
+ */
+
+#[cfg(test)]
+mod tests {
+    use chrono::Utc;
+    use serde_json::json;
+    use sqlx::types::Json;
+    use sqlx::{Postgres, QueryBuilder, Sqlite};
+
+    use common::benchmark::{BenchmarkConfig, BenchmarkType, CacheSizeMode};
+    use common::dto::result::StoredResultDTO;
+
+    use crate::repository::result::{
+        benchmark_name, Dialect, ResultFilter, ResultRow, UserAgentFamily,
+    };
+
+    #[test]
+    fn empty_filter_matches_everything() {
+        // prepare
+        let mut builder = QueryBuilder::<Postgres>::new("SELECT id FROM upload_benchmarkresult");
+
+        // execute
+        ResultFilter::default().push_conditions(&mut builder, Dialect::Postgres);
+
+        // assert
+        assert_eq!(
+            builder.sql(),
+            "SELECT id FROM upload_benchmarkresult WHERE true"
+        );
+    }
+
+    #[test]
+    fn filter_values_are_bound() {
+        // prepare
+        let mut builder = QueryBuilder::<Postgres>::new("SELECT id FROM upload_benchmarkresult");
+        let filter = ResultFilter {
+            model: Some("'; DROP TABLE upload_benchmarkresult; --".to_string()),
+            user_agent_family: Some(UserAgentFamily::Firefox),
+            from: Some(Utc::now()),
+            to: None,
+            benchmark: Some(BenchmarkType::TlbSize),
+        };
+
+        // execute
+        filter.push_conditions(&mut builder, Dialect::Postgres);
+
+        // assert
+        let sql = builder.sql();
+        assert!(!sql.contains("DROP TABLE"));
+        assert!(sql.contains("(position(lower($1) in lower(model_raw)) > 0 OR position(lower($2) in lower(model_normalized)) > 0)"));
+        assert!(sql.contains("lower(user_agent) LIKE '%firefox/%'"));
+        assert!(sql.contains("created_at >= $3"));
+        assert!(sql.contains("benchmark_run.benchmark = $4"));
+    }
+
+    #[test]
+    fn sqlite_filter_uses_its_functions() {
+        // prepare
+        let mut builder = QueryBuilder::<Sqlite>::new("SELECT id FROM upload_benchmarkresult");
+        let filter = ResultFilter {
+            model: Some("i7".to_string()),
+            benchmark: Some(BenchmarkType::TlbSize),
+            ..ResultFilter::default()
+        };
+
+        // execute
+        filter.push_conditions(&mut builder, Dialect::Sqlite);
+
+        // assert
+        let sql = builder.sql();
+        assert!(sql.contains("instr(lower(model_normalized), lower(?)) > 0"));
+        assert!(sql.contains("benchmark_run.benchmark = ?"));
+        assert!(!sql.contains("jsonb"));
+    }
+
+    #[test]
+    fn row_is_converted_to_dto() {
+        // prepare
+        let row = ResultRow {
+            id: 7,
+            created_at: Some(Utc::now()),
+            model_raw: "model".to_string(),
+            model_normalized: "normalized".to_string(),
+            user_agent: "agent".to_string(),
+            capabilities: Some(Json(json!({"unknown": true}))),
+            seed: Some(-1),
+            schema_version: "legacy".to_string(),
+            frontend_version: "legacy".to_string(),
+            parameters: Some(Json(
+                json!({"cache_size_mode": "Adaptive", "stages": [["CacheSize"]]}),
+            )),
+            model_check: Some(Json(json!({"spec_name": null, "mismatches": ["L2Size"]}))),
+            runs: Json(json!([
+                {"benchmark": "TlbSize", "duration": 1.5, "data": [{"x": 1, "y": 2}]}
+            ])),
+        };
+
+        // execute
+        let dto = StoredResultDTO::try_from(row).unwrap();
+
+        // assert
+        assert_eq!(dto.id, 7);
+        assert_eq!(dto.result.model, "model");
+        assert_eq!(dto.model_normalized, "normalized");
+        assert_eq!(dto.result.benchmark_results.len(), 1);
+        assert_eq!(dto.result.times, vec![1.5]);
+        assert_eq!(dto.result.benchmarks, vec![BenchmarkType::TlbSize]);
+        assert!(dto.result.capabilities.is_none());
+        assert_eq!(dto.result.seed, Some(u64::MAX));
+        assert_eq!(dto.result.schema_version, "legacy");
+        assert_eq!(
+            dto.result.parameters,
+            Some(BenchmarkConfig {
+                cache_size_mode: CacheSizeMode::Adaptive,
+                stages: vec![vec![BenchmarkType::CacheSize]],
+            })
+        );
+        assert!(dto
+            .result
+            .model_check
+            .map_or(false, |model_check| model_check.is_mismatch()));
+    }
+
+    #[test]
+    fn benchmark_names_match_json() {
+        // execute & assert
+        assert_eq!(benchmark_name(BenchmarkType::PageSize), "PageSize");
+        assert_eq!(
+            benchmark_name(BenchmarkType::CacheAssociativity),
+            "CacheAssociativity"
+        );
+    }
 }
@@ -0,0 +1,846 @@
+use std::error::Error;
+use std::str::FromStr;
+
+use async_trait::async_trait;
+use chrono::Utc;
+use futures::TryStreamExt;
+use log::error;
+use serde_json::Value;
+use sqlx::sqlite::{SqliteConnectOptions, SqlitePoolOptions};
+use sqlx::types::Json;
+use sqlx::{Pool, QueryBuilder, Sqlite};
+
+use common::dto::chart::ChartDTO;
+use common::dto::cpu::{CpuSpecDTO, StoredCpuSpecDTO, UnmatchedModelDTO};
+use common::dto::result::{ResultDTO, ResultPageDTO, StoredResultDTO};
+use common::fingerprint::{Fingerprint, FINGERPRINT_VERSION};
+use common::model::{normalize_model, RULES_VERSION};
+
+use crate::repository::cpu::{
+    clear_aliases_query, insert_cpu_spec_query, map_alias_violation, update_cpu_spec_query,
+    upsert_cpu_spec_query, CpuSpecRow, UnmatchedModelRow, MATCHING_MODEL, SELECT_CPU_SPECS,
+    SELECT_UNMATCHED_MODELS,
+};
+use crate::repository::result::{
+    benchmark_name, into_page, run_parameters, seed_to_column, select_fingerprints, Dialect,
+    FingerprintRow, ResultFilter, ResultRow, StoredFingerprint, FINGERPRINT_BATCH_SIZE,
+};
+use crate::repository::{CpuSpecRepository, FingerprintVisitor, ResultRepository, ResultVisitor};
+
+/// Selects the results together with their benchmark runs, aggregated into a JSON array
+const SELECT_RESULTS: &str =
+    "SELECT id, created_at, model_raw, coalesce(model_normalized, model_raw) AS model_normalized, user_agent,
+        capabilities, seed, schema_version, frontend_version, parameters, model_check,
+        (
+            SELECT json_group_array(json_object('benchmark', benchmark, 'duration', duration, 'data', json(data)))
+            FROM (SELECT * FROM benchmark_run WHERE benchmark_run.result_id = upload_benchmarkresult.id ORDER BY id)
+        ) AS runs
+    FROM upload_benchmarkresult";
+
+/// Results stored in a SQLite database file, for running the backend without Postgres
+pub struct SqliteRepository {
+    pool: Pool<Sqlite>,
+}
+
+impl SqliteRepository {
+    pub async fn connect(url: &str, connections: u32) -> Result<Self, Box<dyn Error>> {
+        let options = SqliteConnectOptions::from_str(url)?
+            .create_if_missing(true)
+            .foreign_keys(true);
+        let pool = if url.contains(":memory:") {
+            // every connection would open a separate in-memory database
+            SqlitePoolOptions::new()
+                .max_connections(1)
+                .idle_timeout(None)
+                .max_lifetime(None)
+        } else {
+            SqlitePoolOptions::new().max_connections(connections)
+        }
+        .connect_with(options)
+        .await?;
+
+        sqlx::migrate!("./migrations/sqlite").run(&pool).await?;
+
+        let repository = SqliteRepository { pool };
+        repository.normalize_models().await?;
+        repository.fingerprint_results().await?;
+
+        Ok(repository)
+    }
+
+    /// Normalizes the models of the results normalized by outdated rules or not at all
+    async fn normalize_models(&self) -> Result<(), Box<dyn Error>> {
+        let rows = sqlx::query_as::<_, (i64, String)>(
+            "SELECT id, model_raw FROM upload_benchmarkresult
+            WHERE model_rules_version IS NULL OR model_rules_version <> ?",
+        )
+        .bind(RULES_VERSION)
+        .fetch_all(&self.pool)
+        .await?;
+
+        let mut transaction = self.pool.begin().await?;
+        for (id, model_raw) in rows {
+            sqlx::query(
+                "UPDATE upload_benchmarkresult SET model_normalized = ?, model_rules_version = ? WHERE id = ?",
+            )
+            .bind(normalize_model(&model_raw))
+            .bind(RULES_VERSION)
+            .bind(id)
+            .execute(&mut transaction)
+                .await?;
+        }
+        transaction.commit().await?;
+
+        Ok(())
+    }
+
+    /// Fingerprints the results fingerprinted by an outdated layout or not at all
+    async fn fingerprint_results(&self) -> Result<(), Box<dyn Error>> {
+        // in batches, so the results with all their runs are not loaded at once
+        let mut last_id = 0;
+        loop {
+            let rows = sqlx::query_as::<_, ResultRow>(&format!(
+                "{} WHERE (fingerprint_version IS NULL OR fingerprint_version <> ?) AND id > ?
+                ORDER BY id LIMIT ?",
+                SELECT_RESULTS
+            ))
+            .bind(FINGERPRINT_VERSION)
+            .bind(last_id)
+            .bind(FINGERPRINT_BATCH_SIZE)
+            .fetch_all(&self.pool)
+            .await?;
+            let Some(last) = rows.last() else {
+                return Ok(());
+            };
+            last_id = last.id;
+
+            let mut transaction = self.pool.begin().await?;
+            for row in rows {
+                // a broken result must not stop the startup, it stays outdated until it's fixed
+                let stored = match StoredResultDTO::try_from(row) {
+                    Ok(stored) => stored,
+                    Err(error) => {
+                        error!("Skipping the fingerprint: {}", error);
+                        continue;
+                    }
+                };
+                let fingerprint = Fingerprint::of(&stored.result);
+                sqlx::query(
+                    "UPDATE upload_benchmarkresult SET fingerprint = ?, features = ?, fingerprint_version = ?
+                    WHERE id = ?",
+                )
+                .bind(fingerprint.hash)
+                .bind(Json(fingerprint.features))
+                .bind(FINGERPRINT_VERSION)
+                .bind(stored.id)
+                .execute(&mut transaction)
+                .await?;
+            }
+            transaction.commit().await?;
+        }
+    }
+}
+
+#[async_trait(?Send)]
+impl ResultRepository for SqliteRepository {
+    async fn insert_result(
+        &self,
+        result: &ResultDTO,
+        model_normalized: &str,
+        fingerprint: &Fingerprint,
+        charts: &[ChartDTO],
+    ) -> Result<i64, Box<dyn Error>> {
+        let mut transaction = self.pool.begin().await?;
+
+        let id = sqlx::query(
+            "INSERT INTO upload_benchmarkresult (model_raw, model_normalized, user_agent, b64_charts, capabilities, seed,
+                created_at, schema_version, frontend_version, parameters, model_check, fingerprint, features,
+                model_rules_version, fingerprint_version)
+            VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?)
+            ",
+        )
+        .bind(result.model.as_str())
+        .bind(model_normalized)
+        .bind(result.user_agent.as_str())
+        .bind(Json(charts))
+        .bind(result.capabilities.as_ref().map(Json))
+        .bind(seed_to_column(result.seed))
+        .bind(Utc::now())
+        .bind(result.schema_version.as_str())
+        .bind(result.frontend_version.as_str())
+        .bind(result.parameters.as_ref().map(Json))
+        .bind(result.model_check.as_ref().map(Json))
+        .bind(fingerprint.hash.as_str())
+        .bind(Json(&fingerprint.features))
+        .bind(RULES_VERSION)
+        .bind(FINGERPRINT_VERSION)
+        .execute(&mut transaction)
+        .await?
+        .last_insert_rowid();
+
+        let runs = result
+            .benchmark_types()
+            .into_iter()
+            .zip(result.benchmark_results.iter())
+            .zip(result.times.iter());
+        for ((benchmark, data), duration) in runs {
+            sqlx::query(
+                "INSERT INTO benchmark_run (result_id, benchmark, parameters, duration, data)
+                VALUES (?, ?, ?, ?, ?)
+                ",
+            )
+            .bind(id)
+            .bind(benchmark_name(benchmark))
+            .bind(run_parameters(benchmark, result).map(Json))
+            .bind(duration)
+            .bind(Json(data))
+            .execute(&mut transaction)
+            .await?;
+        }
+
+        transaction.commit().await?;
+
+        Ok(id)
+    }
+
+    async fn find_charts(&self, id: i64) -> Result<Option<Vec<ChartDTO>>, Box<dyn Error>> {
+        let charts = sqlx::query_scalar::<_, Json<Value>>(
+            "SELECT b64_charts FROM upload_benchmarkresult WHERE id = ?",
+        )
+        .bind(id)
+        .fetch_optional(&self.pool)
+        .await?;
+
+        Ok(charts.map(|charts| serde_json::from_value(charts.0).unwrap_or_default()))
+    }
+
+    async fn update_charts(&self, id: i64, charts: &[ChartDTO]) -> Result<(), Box<dyn Error>> {
+        sqlx::query("UPDATE upload_benchmarkresult SET b64_charts = ? WHERE id = ?")
+            .bind(Json(charts))
+            .bind(id)
+            .execute(&self.pool)
+            .await?;
+
+        Ok(())
+    }
+
+    async fn find_result(&self, id: i64) -> Result<Option<StoredResultDTO>, Box<dyn Error>> {
+        let mut builder = QueryBuilder::new(SELECT_RESULTS);
+        builder.push(" WHERE id = ").push_bind(id);
+
+        let row = builder
+            .build_query_as::<ResultRow>()
+            .fetch_optional(&self.pool)
+            .await?;
+
+        Ok(row.map(StoredResultDTO::try_from).transpose()?)
+    }
+
+    async fn find_results(
+        &self,
+        filter: &ResultFilter,
+        cursor: Option<i64>,
+        limit: i64,
+    ) -> Result<ResultPageDTO, Box<dyn Error>> {
+        let mut builder = QueryBuilder::new(SELECT_RESULTS);
+        filter.push_conditions(&mut builder, Dialect::Sqlite);
+        if let Some(cursor) = cursor {
+            builder.push(" AND id < ").push_bind(cursor);
+        }
+        builder
+            .push(" ORDER BY id DESC LIMIT ")
+            .push_bind(limit + 1);
+
+        let results = builder
+            .build_query_as::<ResultRow>()
+            .fetch_all(&self.pool)
+            .await?
+            .into_iter()
+            .map(StoredResultDTO::try_from)
+            .collect::<Result<Vec<_>, _>>()?;
+
+        Ok(into_page(results, limit))
+    }
+
+    async fn for_each_result(
+        &self,
+        filter: &ResultFilter,
+        visit: &mut ResultVisitor<'_>,
+    ) -> Result<(), Box<dyn Error>> {
+        let mut builder = QueryBuilder::new(SELECT_RESULTS);
+        filter.push_conditions(&mut builder, Dialect::Sqlite);
+        builder.push(" ORDER BY id");
+
+        let mut rows = builder.build_query_as::<ResultRow>().fetch(&self.pool);
+        while let Some(row) = rows.try_next().await? {
+            visit(StoredResultDTO::try_from(row)?).await?;
+        }
+
+        Ok(())
+    }
+
+    async fn for_each_fingerprint(
+        &self,
+        visit: &mut FingerprintVisitor<'_>,
+    ) -> Result<(), Box<dyn Error>> {
+        let mut builder = select_fingerprints::<Sqlite>();
+        builder.push(" ORDER BY id");
+
+        let mut rows = builder.build_query_as::<FingerprintRow>().fetch(&self.pool);
+        while let Some(row) = rows.try_next().await? {
+            visit(StoredFingerprint::from(row));
+        }
+
+        Ok(())
+    }
+
+    async fn find_fingerprint(&self, id: i64) -> Result<Option<StoredFingerprint>, Box<dyn Error>> {
+        let mut builder = select_fingerprints::<Sqlite>();
+        builder.push(" AND id = ").push_bind(id);
+
+        let row = builder
+            .build_query_as::<FingerprintRow>()
+            .fetch_optional(&self.pool)
+            .await?;
+
+        Ok(row.map(StoredFingerprint::from))
+    }
+
+    async fn find_fingerprints_by_hash(
+        &self,
+        hash: &str,
+    ) -> Result<Vec<StoredFingerprint>, Box<dyn Error>> {
+        let mut builder = select_fingerprints::<Sqlite>();
+        builder
+            .push(" AND fingerprint = ")
+            .push_bind(hash)
+            .push(" ORDER BY id");
+
+        let rows = builder
+            .build_query_as::<FingerprintRow>()
+            .fetch_all(&self.pool)
+            .await?;
+
+        Ok(rows.into_iter().map(StoredFingerprint::from).collect())
+    }
+}
+
+#[async_trait(?Send)]
+impl CpuSpecRepository for SqliteRepository {
+    async fn insert_cpu_spec(&self, spec: &CpuSpecDTO) -> Result<Option<i64>, Box<dyn Error>> {
+        let id = insert_cpu_spec_query::<Sqlite>(spec)
+            .build_query_as::<(i64,)>()
+            .fetch_optional(&self.pool)
+            .await
+            .map_err(|error| map_alias_violation(error, spec))?;
+
+        Ok(id.map(|(id,)| id))
+    }
+
+    async fn update_cpu_spec(&self, id: i64, spec: &CpuSpecDTO) -> Result<bool, Box<dyn Error>> {
+        let updated = update_cpu_spec_query::<Sqlite>(id, spec)
+            .build()
+            .execute(&self.pool)
+            .await
+            .map_err(|error| map_alias_violation(error, spec))?;
+
+        Ok(updated.rows_affected() > 0)
+    }
+
+    async fn delete_cpu_spec(&self, id: i64) -> Result<bool, Box<dyn Error>> {
+        let deleted = sqlx::query("DELETE FROM cpu_spec WHERE id = ?")
+            .bind(id)
+            .execute(&self.pool)
+            .await?;
+
+        Ok(deleted.rows_affected() > 0)
+    }
+
+    async fn import_cpu_specs(&self, specs: &[CpuSpecDTO]) -> Result<(), Box<dyn Error>> {
+        if specs.is_empty() {
+            return Ok(());
+        }
+
+        let mut transaction = self.pool.begin().await?;
+        clear_aliases_query::<Sqlite>(specs)
+            .build()
+            .execute(&mut transaction)
+            .await?;
+        for spec in specs {
+            upsert_cpu_spec_query::<Sqlite>(spec)
+                .build()
+                .execute(&mut transaction)
+                .await
+                .map_err(|error| map_alias_violation(error, spec))?;
+        }
+        transaction.commit().await?;
+
+        Ok(())
+    }
+
+    async fn find_cpu_spec(&self, id: i64) -> Result<Option<StoredCpuSpecDTO>, Box<dyn Error>> {
+        let mut builder = QueryBuilder::new(SELECT_CPU_SPECS);
+        builder.push(" WHERE cpu_spec.id = ").push_bind(id);
+
+        let row = builder
+            .build_query_as::<CpuSpecRow>()
+            .fetch_optional(&self.pool)
+            .await?;
+
+        Ok(row.map(StoredCpuSpecDTO::from))
+    }
+
+    async fn find_cpu_specs(&self) -> Result<Vec<StoredCpuSpecDTO>, Box<dyn Error>> {
+        let rows = sqlx::query_as::<_, CpuSpecRow>(&format!("{} ORDER BY name", SELECT_CPU_SPECS))
+            .fetch_all(&self.pool)
+            .await?;
+
+        Ok(rows.into_iter().map(StoredCpuSpecDTO::from).collect())
+    }
+
+    async fn find_model_cpu_specs(
+        &self,
+        model_normalized: &str,
+    ) -> Result<Vec<StoredCpuSpecDTO>, Box<dyn Error>> {
+        let mut builder = QueryBuilder::new(SELECT_CPU_SPECS);
+        builder
+            .push(" WHERE ")
+            .push_bind(model_normalized)
+            .push(MATCHING_MODEL);
+
+        let rows = builder
+            .build_query_as::<CpuSpecRow>()
+            .fetch_all(&self.pool)
+            .await?;
+
+        Ok(rows.into_iter().map(StoredCpuSpecDTO::from).collect())
+    }
+
+    async fn find_unmatched_models(&self) -> Result<Vec<UnmatchedModelDTO>, Box<dyn Error>> {
+        let rows = sqlx::query_as::<_, UnmatchedModelRow>(SELECT_UNMATCHED_MODELS)
+            .fetch_all(&self.pool)
+            .await?;
+
+        Ok(rows.into_iter().map(UnmatchedModelDTO::from).collect())
+    }
+}
+
+/*
+ * TESTS
+ */
+
+#[cfg(test)]
+mod tests {
+    use chrono::{Duration, Utc};
+    use futures::FutureExt;
+    use serde_json::json;
+
+    use common::benchmark::BenchmarkType;
+    use common::dto::chart::ChartDTO;
+    use common::dto::cpu::{CpuSpecDTO, UnmatchedModelDTO};
+    use common::dto::result::{ResultDTO, SCHEMA_VERSION};
+    use common::fingerprint::Fingerprint;
+    use common::model::{normalize_model, RULES_VERSION};
+
+    use crate::repository::cpu::AliasTaken;
+    use crate::repository::result::{ResultFilter, StoredFingerprint, UserAgentFamily};
+    use crate::repository::sqlite::SqliteRepository;
+    use crate::repository::{CpuSpecRepository, ResultRepository};
+
+    async fn repository() -> SqliteRepository {
+        SqliteRepository::connect("sqlite::memory:", 1)
+            .await
+            .unwrap()
+    }
+
+    async fn fingerprints(repository: &SqliteRepository) -> Vec<StoredFingerprint> {
+        let mut fingerprints = vec![];
+        repository
+            .for_each_fingerprint(&mut |stored| fingerprints.push(stored))
+            .await
+            .unwrap();
+        fingerprints
+    }
+
+    fn result(model: &str, benchmarks: Vec<BenchmarkType>) -> ResultDTO {
+        ResultDTO {
+            model: model.to_string(),
+            user_agent: "Mozilla/5.0 Firefox/110.0".to_string(),
+            benchmark_results: benchmarks
+                .iter()
+                .map(|_| json!([{"x": 512, "y": 3}]))
+                .collect(),
+            times: benchmarks.iter().map(|_| 2.5).collect(),
+            benchmarks,
+            capabilities: None,
+            seed: Some(u64::MAX),
+            schema_version: SCHEMA_VERSION.to_string(),
+            frontend_version: "0.1.0".to_string(),
+            parameters: None,
+            model_check: None,
+        }
+    }
+
+    fn cpu_spec(name: &str, alias: Option<&str>) -> CpuSpecDTO {
+        CpuSpecDTO {
+            manufacturer: "Intel".to_string(),
+            name: name.to_string(),
+            alias: alias.map(str::to_string),
+            cores: 4,
+            threads: 8,
+            base_ghz: 3.6,
+            boost_ghz: 4.2,
+            l1_kib: 32,
+            l2_kib: 256,
+            l3_mib: Some(8),
+            l1_associativity: Some(8),
+            page_size: 4096,
+            load_buffer: None,
+            l1_dtlb: Some(64),
+            microarchitecture: "Skylake".to_string(),
+        }
+    }
+
+    #[actix_web::test]
+    async fn result_round_trips() {
+        // prepare
+        let repository = repository().await;
+        let uploaded = result(
+            "Intel Core i7",
+            vec![BenchmarkType::TlbSize, BenchmarkType::PageSize],
+        );
+
+        let fingerprint = Fingerprint::of(&uploaded);
+
+        // execute
+        let id = repository
+            .insert_result(&uploaded, "Intel(R) Core(TM) i7", &fingerprint, &[])
+            .await
+            .unwrap();
+        let stored = repository.find_result(id).await.unwrap().unwrap();
+        let fingerprints = fingerprints(&repository).await;
+        let by_id = repository.find_fingerprint(id).await.unwrap();
+        let by_hash = repository
+            .find_fingerprints_by_hash(&fingerprint.hash)
+            .await
+            .unwrap();
+
+        // assert
+        assert_eq!(stored.id, id);
+        assert!(Utc::now() - stored.created_at.unwrap() < Duration::minutes(1));
+        assert_eq!(stored.result.model, uploaded.model);
+        assert_eq!(stored.model_normalized, "Intel(R) Core(TM) i7");
+        assert_eq!(stored.result.benchmarks, uploaded.benchmarks);
+        assert_eq!(stored.result.benchmark_results, uploaded.benchmark_results);
+        assert_eq!(stored.result.times, uploaded.times);
+        assert_eq!(stored.result.seed, Some(u64::MAX));
+        assert!(repository.find_result(id + 1).await.unwrap().is_none());
+        assert_eq!(fingerprints.len(), 1);
+        assert_eq!(fingerprints[0].id, id);
+        assert_eq!(fingerprints[0].fingerprint, fingerprint);
+        assert_eq!(by_id.as_ref(), fingerprints.first());
+        assert!(repository.find_fingerprint(id + 1).await.unwrap().is_none());
+        assert_eq!(by_hash, fingerprints);
+    }
+
+    #[actix_web::test]
+    async fn outdated_results_are_processed_again() {
+        // prepare
+        let repository = repository().await;
+        let uploaded = result("Intel Core i7 @ 3.60GHz", vec![BenchmarkType::PageSize]);
+        let id = repository
+            .insert_result(&uploaded, "stale", &Fingerprint::of(&uploaded), &[])
+            .await
+            .unwrap();
+        sqlx::query(
+            "UPDATE upload_benchmarkresult SET fingerprint = 'stale', model_rules_version = ?, fingerprint_version = NULL",
+        )
+        .bind(RULES_VERSION - 1)
+        .execute(&repository.pool)
+        .await
+        .unwrap();
+
+        // execute
+        let outdated = fingerprints(&repository).await;
+        repository.normalize_models().await.unwrap();
+        repository.fingerprint_results().await.unwrap();
+        let stored = repository.find_result(id).await.unwrap().unwrap();
+        let fingerprints = fingerprints(&repository).await;
+
+        // assert
+        assert!(outdated.is_empty());
+        assert!(repository.find_fingerprint(id).await.unwrap().is_some());
+        assert_eq!(stored.model_normalized, normalize_model(&uploaded.model));
+        assert_eq!(fingerprints[0].fingerprint, Fingerprint::of(&uploaded));
+    }
+
+    #[actix_web::test]
+    async fn results_with_broken_runs_are_errors() {
+        // prepare
+        let repository = repository().await;
+        let uploaded = result("model", vec![BenchmarkType::PageSize]);
+        let id = repository
+            .insert_result(&uploaded, "model", &Fingerprint::of(&uploaded), &[])
+            .await
+            .unwrap();
+        sqlx::query(
+            "UPDATE benchmark_run SET benchmark = 'Unknown';
+            UPDATE upload_benchmarkresult SET fingerprint_version = NULL",
+        )
+        .execute(&repository.pool)
+        .await
+        .unwrap();
+
+        // execute
+        let found = repository.find_result(id).await;
+        let listed = repository
+            .find_results(&ResultFilter::default(), None, 10)
+            .await;
+        let fingerprinted = repository.fingerprint_results().await;
+
+        // assert
+        assert!(found.unwrap_err().to_string().contains(&id.to_string()));
+        assert!(listed.is_err());
+        assert!(fingerprinted.is_ok());
+    }
+
+    #[actix_web::test]
+    async fn charts_are_updated() {
+        // prepare
+        let repository = repository().await;
+        let uploaded = result("model", vec![BenchmarkType::PageSize]);
+        let id = repository
+            .insert_result(&uploaded, "model", &Fingerprint::of(&uploaded), &[])
+            .await
+            .unwrap();
+        let charts = vec![ChartDTO {
+            benchmark: BenchmarkType::PageSize,
+            content_type: "image/svg+xml".to_string(),
+            data: "PHN2Zy8+".to_string(),
+        }];
+
+        // execute
+        let before = repository.find_charts(id).await.unwrap();
+        repository.update_charts(id, &charts).await.unwrap();
+        let after = repository.find_charts(id).await.unwrap();
+
+        // assert
+        assert_eq!(before, Some(vec![]));
+        assert_eq!(after, Some(charts));
+    }
+
+    #[actix_web::test]
+    async fn results_are_filtered_and_paged() {
+        // prepare
+        let repository = repository().await;
+        for model in ["AMD Ryzen 5", "Intel Core i5", "Intel Core i7"] {
+            let uploaded = result(model, vec![BenchmarkType::CacheSize]);
+            repository
+                .insert_result(&uploaded, model, &Fingerprint::of(&uploaded), &[])
+                .await
+                .unwrap();
+        }
+        let uploaded = result("Intel Xeon", vec![BenchmarkType::TlbSize]);
+        repository
+            .insert_result(&uploaded, "Intel Xeon", &Fingerprint::of(&uploaded), &[])
+            .await
+            .unwrap();
+        let filter = ResultFilter {
+            model: Some("INTEL".to_string()),
+            user_agent_family: Some(UserAgentFamily::Firefox),
+            benchmark: Some(BenchmarkType::CacheSize),
+            ..ResultFilter::default()
+        };
+
+        // execute
+        let first = repository.find_results(&filter, None, 1).await.unwrap();
+        let second = repository
+            .find_results(&filter, first.next_cursor, 1)
+            .await
+            .unwrap();
+
+        // assert
+        assert_eq!(first.results[0].result.model, "Intel Core i7");
+        assert_eq!(second.results[0].result.model, "Intel Core i5");
+        assert_eq!(second.next_cursor, None);
+    }
+
+    #[actix_web::test]
+    async fn results_are_visited_oldest_first() {
+        // prepare
+        let repository = repository().await;
+        for model in ["first", "second"] {
+            let uploaded = result(model, vec![BenchmarkType::PageSize]);
+            repository
+                .insert_result(&uploaded, model, &Fingerprint::of(&uploaded), &[])
+                .await
+                .unwrap();
+        }
+        let filter = ResultFilter {
+            from: Some(Utc::now() - Duration::minutes(1)),
+            ..ResultFilter::default()
+        };
+
+        // execute
+        let mut models = vec![];
+        repository
+            .for_each_result(&filter, &mut |result| {
+                models.push(result.result.model);
+                async { Ok(()) }.boxed_local()
+            })
+            .await
+            .unwrap();
+
+        // assert
+        assert_eq!(models, vec!["first", "second"]);
+    }
+
+    #[actix_web::test]
+    async fn legacy_results_have_no_upload_time() {
+        // prepare
+        let repository = repository().await;
+        let uploaded = result("legacy", vec![BenchmarkType::PageSize]);
+        let id = repository
+            .insert_result(&uploaded, "legacy", &Fingerprint::of(&uploaded), &[])
+            .await
+            .unwrap();
+        sqlx::query("UPDATE upload_benchmarkresult SET created_at = NULL")
+            .execute(&repository.pool)
+            .await
+            .unwrap();
+        let range = ResultFilter {
+            from: Some(Utc::now() - Duration::days(365 * 10)),
+            ..ResultFilter::default()
+        };
+
+        // execute
+        let stored = repository.find_result(id).await.unwrap().unwrap();
+        let all = repository
+            .find_results(&ResultFilter::default(), None, 10)
+            .await
+            .unwrap();
+        let in_range = repository.find_results(&range, None, 10).await.unwrap();
+
+        // assert
+        assert_eq!(stored.created_at, None);
+        assert_eq!(all.results.len(), 1);
+        assert!(in_range.results.is_empty());
+    }
+
+    #[actix_web::test]
+    async fn aliases_are_unique() {
+        // prepare
+        let repository = repository().await;
+        let id = repository
+            .insert_cpu_spec(&cpu_spec("Intel Core i7-7700", Some("Kaby Lake")))
+            .await
+            .unwrap()
+            .unwrap();
+        let other = repository
+            .insert_cpu_spec(&cpu_spec("Intel Core i5-7600", None))
+            .await
+            .unwrap()
+            .unwrap();
+
+        // execute
+        let inserted = repository
+            .insert_cpu_spec(&cpu_spec("Intel Core i3-7100", Some("Kaby Lake")))
+            .await;
+        let updated = repository
+            .update_cpu_spec(other, &cpu_spec("Intel Core i5-7600", Some("Kaby Lake")))
+            .await;
+        // the alias moves between the imported specifications
+        let moved = repository
+            .import_cpu_specs(&[
+                cpu_spec("Intel Core i5-7600", Some("Kaby Lake")),
+                cpu_spec("Intel Core i7-7700", None),
+            ])
+            .await;
+        let matched = repository.find_model_cpu_specs("Kaby Lake").await.unwrap();
+
+        // assert
+        for error in [inserted.unwrap_err(), updated.unwrap_err()] {
+            assert_eq!(error.downcast::<AliasTaken>().unwrap().alias, "Kaby Lake");
+        }
+        assert!(moved.is_ok());
+        assert_eq!(matched.len(), 1);
+        assert_eq!(matched[0].id, other);
+        assert_ne!(matched[0].id, id);
+    }
+
+    #[actix_web::test]
+    async fn cpu_specs_are_stored_and_matched() {
+        // prepare
+        let repository = repository().await;
+        for model in ["Intel Core i7-7700", "Kaby Lake", "unknown"] {
+            let uploaded = result(model, vec![]);
+            repository
+                .insert_result(&uploaded, model, &Fingerprint::of(&uploaded), &[])
+                .await
+                .unwrap();
+        }
+
+        // execute
+        let id = repository
+            .insert_cpu_spec(&cpu_spec("Intel Core i7-6700", None))
+            .await
+            .unwrap()
+            .unwrap();
+        let duplicate = repository
+            .insert_cpu_spec(&cpu_spec("Intel Core i7-6700", None))
+            .await
+            .unwrap();
+        repository
+            .import_cpu_specs(&[
+                cpu_spec("Intel Core i7-7700", Some("Kaby Lake")),
+                CpuSpecDTO {
+                    cores: 2,
+                    ..cpu_spec("Intel Core i7-6700", None)
+                },
+            ])
+            .await
+            .unwrap();
+        let renamed = repository
+            .update_cpu_spec(id, &cpu_spec("Intel Core i7-7700", None))
+            .await
+            .unwrap();
+        let updated = repository
+            .update_cpu_spec(id, &cpu_spec("Intel Core i5-6600", None))
+            .await
+            .unwrap();
+        let specs = repository.find_cpu_specs().await.unwrap();
+        let matched = repository.find_model_cpu_specs("Kaby Lake").await.unwrap();
+        let unmatched = repository.find_unmatched_models().await.unwrap();
+        let deleted = repository.delete_cpu_spec(id).await.unwrap();
+
+        // assert
+        assert_eq!(duplicate, None);
+        assert!(!renamed);
+        assert!(updated);
+        let specs = specs
+            .iter()
+            .map(|stored| {
+                (
+                    stored.spec.name.as_str(),
+                    stored.spec.cores,
+                    stored.result_count,
+                )
+            })
+            .collect::<Vec<_>>();
+        assert_eq!(
+            specs,
+            vec![("Intel Core i5-6600", 4, 0), ("Intel Core i7-7700", 4, 2)]
+        );
+        assert_eq!(matched.len(), 1);
+        assert_eq!(matched[0].spec.name, "Intel Core i7-7700");
+        assert_eq!(
+            unmatched,
+            vec![UnmatchedModelDTO {
+                model_normalized: "unknown".to_string(),
+                result_count: 1,
+            }]
+        );
+        assert!(deleted);
+        assert!(repository.find_cpu_spec(id).await.unwrap().is_none());
+    }
+}
@@ -0,0 +1,122 @@
+use crate::repository::Repository;
+use common::dto::cpu::ModelSuggestionDTO;
+use std::error::Error;
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+use tokio::sync::Mutex;
+
+/// Models of the results outside the catalogue need this many results to be known,
+/// so a typo of a single user is neither suggested nor accepted as known
+pub const MIN_RESULT_COUNT: i64 = 2;
+/// Age after which the known models are loaded again, to count the new results
+const MAX_AGE: Duration = Duration::from_secs(60);
+
+/// Known models suggested for the typed ones, loaded once for all the keystrokes.
+/// They are loaded again after a change of the catalogue or after `MAX_AGE`.
+#[derive(Default)]
+pub struct KnownModelCache {
+    models: Mutex<Option<(Instant, Arc<Vec<ModelSuggestionDTO>>)>>,
+}
+
+impl KnownModelCache {
+    pub async fn get_or_load(
+        &self,
+        repository: &dyn Repository,
+    ) -> Result<Arc<Vec<ModelSuggestionDTO>>, Box<dyn Error>> {
+        // held while loading, so concurrent requests do not run the same queries
+        let mut models = self.models.lock().await;
+        if let Some((loaded_at, models)) = models.as_ref() {
+            if loaded_at.elapsed() < MAX_AGE {
+                return Ok(models.clone());
+            }
+        }
+
+        let loaded = Arc::new(load_known_models(repository).await?);
+        *models = Some((Instant::now(), loaded.clone()));
+
+        Ok(loaded)
+    }
+
+    /// Drops the models after a change of the catalogue
+    pub async fn invalidate(&self) {
+        *self.models.lock().await = None;
+    }
+}
+
+/// Names of the catalogue and the normalized models of at least `MIN_RESULT_COUNT` results outside it
+async fn load_known_models(
+    repository: &dyn Repository,
+) -> Result<Vec<ModelSuggestionDTO>, Box<dyn Error>> {
+    // the unmatched models are the models of the results missing in the catalogue
+    let catalogue = repository.find_cpu_specs().await?;
+    let unmatched = repository.find_unmatched_models().await?;
+
+    Ok(catalogue
+        .into_iter()
+        .map(|stored| ModelSuggestionDTO {
+            model: stored.spec.name,
+            in_catalogue: true,
+            result_count: stored.result_count,
+        })
+        .chain(
+            unmatched
+                .into_iter()
+                .filter(|unmatched| unmatched.result_count >= MIN_RESULT_COUNT)
+                .map(|unmatched| ModelSuggestionDTO {
+                    model: unmatched.model_normalized,
+                    in_catalogue: false,
+                    result_count: unmatched.result_count,
+                }),
+        )
+        .collect())
+}
+
+/*
+ * TESTS
+ */
+
+#[cfg(test)]
+mod tests {
+    use common::dto::cpu::CpuSpecDTO;
+
+    use crate::repository::memory::InMemoryRepository;
+    use crate::repository::CpuSpecRepository;
+    use crate::suggestions::KnownModelCache;
+
+    #[actix_web::test]
+    async fn models_are_cached_until_invalidated() {
+        // prepare
+        let repository = InMemoryRepository::default();
+        let cache = KnownModelCache::default();
+        let spec = CpuSpecDTO {
+            manufacturer: "AMD".to_string(),
+            name: "AMD Ryzen 7 3700X".to_string(),
+            alias: None,
+            cores: 8,
+            threads: 16,
+            base_ghz: 3.6,
+            boost_ghz: 4.4,
+            l1_kib: 32,
+            l2_kib: 512,
+            l3_mib: Some(32),
+            l1_associativity: Some(8),
+            page_size: 4096,
+            load_buffer: None,
+            l1_dtlb: Some(64),
+            microarchitecture: "Zen 2".to_string(),
+        };
+
+        // execute
+        let before = cache.get_or_load(&repository).await.unwrap();
+        repository.insert_cpu_spec(&spec).await.unwrap();
+        let cached = cache.get_or_load(&repository).await.unwrap();
+        cache.invalidate().await;
+        let reloaded = cache.get_or_load(&repository).await.unwrap();
+
+        // assert
+        assert!(before.is_empty());
+        assert!(cached.is_empty());
+        assert_eq!(reloaded.len(), 1);
+        assert_eq!(reloaded[0].model, "AMD Ryzen 7 3700X");
+    }
+}
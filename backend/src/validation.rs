@@ -0,0 +1,542 @@
+use common::benchmark::BenchmarkType;
+use common::dto::cpu::CpuSpecDTO;
+use common::dto::error::FieldErrorDTO;
+use common::dto::result::{ResultDTO, LEGACY_VERSION};
+use common::model::normalize_model;
+use serde_json::Value;
+
+/// Length limits of the `varchar(255)` columns, counted in characters like in Postgres
+const MAX_MODEL_LENGTH: usize = 255;
+const MAX_USER_AGENT_LENGTH: usize = 255;
+const MAX_SCHEMA_VERSION_LENGTH: usize = 32;
+const MAX_FRONTEND_VERSION_LENGTH: usize = 64;
+const MAX_MANUFACTURER_LENGTH: usize = 64;
+const MAX_CPU_NAME_LENGTH: usize = 255;
+
+/// Checks the uploaded result before it is stored. All invalid fields are reported at once.
+pub fn validate_result(result: &ResultDTO) -> Result<(), Vec<FieldErrorDTO>> {
+    let mut errors = vec![];
+    let mut report = |field: String, message: String| {
+        errors.push(FieldErrorDTO { field, message });
+    };
+
+    if result.model.trim().is_empty() {
+        report("model".to_string(), "must not be empty".to_string());
+    } else if result.model.chars().count() > MAX_MODEL_LENGTH {
+        report(
+            "model".to_string(),
+            format!("must have at most {} characters", MAX_MODEL_LENGTH),
+        );
+    } else if normalize_model(&result.model).chars().count() > MAX_MODEL_LENGTH {
+        // the normalization may add characters, e.g. the manufacturer
+        report(
+            "model".to_string(),
+            format!(
+                "must have at most {} characters after the normalization",
+                MAX_MODEL_LENGTH
+            ),
+        );
+    }
+
+    if result.user_agent.chars().count() > MAX_USER_AGENT_LENGTH {
+        report(
+            "user_agent".to_string(),
+            format!("must have at most {} characters", MAX_USER_AGENT_LENGTH),
+        );
+    }
+
+    // only results stored before the versions were recorded may lack them
+    if is_missing_version(&result.schema_version) {
+        report(
+            "schema_version".to_string(),
+            "must not be missing or legacy".to_string(),
+        );
+    } else if result.schema_version.chars().count() > MAX_SCHEMA_VERSION_LENGTH {
+        report(
+            "schema_version".to_string(),
+            format!("must have at most {} characters", MAX_SCHEMA_VERSION_LENGTH),
+        );
+    }
+
+    if is_missing_version(&result.frontend_version) {
+        report(
+            "frontend_version".to_string(),
+            "must not be missing or legacy".to_string(),
+        );
+    } else if result.frontend_version.chars().count() > MAX_FRONTEND_VERSION_LENGTH {
+        report(
+            "frontend_version".to_string(),
+            format!(
+                "must have at most {} characters",
+                MAX_FRONTEND_VERSION_LENGTH
+            ),
+        );
+    }
+
+    if result.benchmark_results.is_empty() {
+        report(
+            "benchmark_results".to_string(),
+            "must not be empty".to_string(),
+        );
+    } else if result.benchmark_results.len() > BenchmarkType::ALL.len() {
+        report(
+            "benchmark_results".to_string(),
+            format!(
+                "must have at most {} results, one for each benchmark",
+                BenchmarkType::ALL.len()
+            ),
+        );
+    }
+
+    if result.times.len() != result.benchmark_results.len() {
+        report(
+            "times".to_string(),
+            format!(
+                "must have as many items as benchmark_results ({})",
+                result.benchmark_results.len()
+            ),
+        );
+    }
+
+    // results of older frontends are identified by their position instead
+    if !result.benchmarks.is_empty() {
+        if result.benchmarks.len() != result.benchmark_results.len() {
+            report(
+                "benchmarks".to_string(),
+                format!(
+                    "must have as many items as benchmark_results ({})",
+                    result.benchmark_results.len()
+                ),
+            );
+        }
+        for (i, benchmark) in result.benchmarks.iter().enumerate() {
+            if result.benchmarks[..i].contains(benchmark) {
+                report(
+                    format!("benchmarks[{}]", i),
+                    format!("{:?} is listed more than once", benchmark),
+                );
+            }
+        }
+    }
+
+    for (i, benchmark_result) in result.benchmark_results.iter().enumerate() {
+        if let Err(message) = check_points(benchmark_result) {
+            report(format!("benchmark_results[{}]", i), message);
+        }
+    }
+
+    for (i, time) in result.times.iter().enumerate() {
+        if !time.is_finite() || *time < 0.0 {
+            report(
+                format!("times[{}]", i),
+                "must be a non-negative number".to_string(),
+            );
+        }
+    }
+
+    if errors.is_empty() {
+        Ok(())
+    } else {
+        Err(errors)
+    }
+}
+
+/// Checks the CPU specification before it is stored. All invalid fields are reported at once.
+pub fn validate_cpu_spec(spec: &CpuSpecDTO) -> Result<(), Vec<FieldErrorDTO>> {
+    let mut errors = vec![];
+    let mut report = |field: &str, message: String| {
+        errors.push(FieldErrorDTO {
+            field: field.to_string(),
+            message,
+        });
+    };
+
+    let names = [
+        (
+            "manufacturer",
+            Some(&spec.manufacturer),
+            MAX_MANUFACTURER_LENGTH,
+        ),
+        ("name", Some(&spec.name), MAX_CPU_NAME_LENGTH),
+        ("alias", spec.alias.as_ref(), MAX_CPU_NAME_LENGTH),
+        (
+            "microarchitecture",
+            Some(&spec.microarchitecture),
+            MAX_CPU_NAME_LENGTH,
+        ),
+    ];
+    for (field, value, max_length) in names {
+        let Some(value) = value else {
+            continue;
+        };
+        if value.trim().is_empty() {
+            report(field, "must not be empty".to_string());
+        } else if value.chars().count() > max_length {
+            report(
+                field,
+                format!("must have at most {} characters", max_length),
+            );
+        }
+    }
+
+    if spec.cores == 0 {
+        report("cores", "must be positive".to_string());
+    }
+    if spec.threads < spec.cores {
+        report(
+            "threads",
+            "must be at least the number of cores".to_string(),
+        );
+    }
+    for (field, clock) in [("base_ghz", spec.base_ghz), ("boost_ghz", spec.boost_ghz)] {
+        if !clock.is_finite() || clock <= 0.0 {
+            report(field, "must be a positive number".to_string());
+        }
+    }
+    if spec.boost_ghz < spec.base_ghz {
+        report("boost_ghz", "must be at least the base clock".to_string());
+    }
+    if !spec.page_size.is_power_of_two() {
+        report("page_size", "must be a power of two".to_string());
+    }
+
+    if errors.is_empty() {
+        Ok(())
+    } else {
+        Err(errors)
+    }
+}
+
+/// Other specification with the same alias, the results with the alias would match both of them
+pub fn find_shared_alias<'a>(
+    spec: &CpuSpecDTO,
+    others: impl IntoIterator<Item = &'a CpuSpecDTO>,
+) -> Option<&'a CpuSpecDTO> {
+    let alias = spec.alias.as_ref()?;
+    others
+        .into_iter()
+        .find(|other| other.alias.as_ref() == Some(alias))
+}
+
+/// Checks that the imported specifications neither repeat a name nor share an alias,
+/// with each other or with the stored ones they do not replace. All conflicts are reported at once.
+pub fn validate_cpu_import(
+    specs: &[CpuSpecDTO],
+    catalogue: &[CpuSpecDTO],
+) -> Result<(), Vec<FieldErrorDTO>> {
+    let kept = catalogue
+        .iter()
+        .filter(|stored| !specs.iter().any(|spec| spec.name == stored.name))
+        .collect::<Vec<_>>();
+
+    let mut errors = vec![];
+    for (i, spec) in specs.iter().enumerate() {
+        if specs[..i].iter().any(|other| other.name == spec.name) {
+            errors.push(FieldErrorDTO {
+                field: format!("[{}].name", i),
+                message: "must not repeat the name of another imported specification".to_string(),
+            });
+        }
+        let others = specs[..i]
+            .iter()
+            .chain(specs[i + 1..].iter())
+            .filter(|other| other.name != spec.name)
+            .chain(kept.iter().copied());
+        if let Some(other) = find_shared_alias(spec, others) {
+            errors.push(FieldErrorDTO {
+                field: format!("[{}].alias", i),
+                message: format!("must not be the alias of '{}' as well", other.name),
+            });
+        }
+    }
+
+    if errors.is_empty() {
+        Ok(())
+    } else {
+        Err(errors)
+    }
+}
+
+/// Missing versions are deserialized as `LEGACY_VERSION`
+fn is_missing_version(version: &str) -> bool {
+    version.trim().is_empty() || version == LEGACY_VERSION
+}
+
+/// A benchmark result is either a list of points, or an object with the list under `points`
+fn check_points(result: &Value) -> Result<(), String> {
+    let points = match result {
+        Value::Array(points) => points,
+        Value::Object(object) => match object.get("points") {
+            Some(Value::Array(points)) => points,
+            _ => return Err("must contain a list of points under `points`".to_string()),
+        },
+        _ => return Err("must be a list of points or an object with them".to_string()),
+    };
+
+    let is_number = |point: &Value, axis: &str| point.get(axis).map_or(false, Value::is_number);
+    match points
+        .iter()
+        .position(|point| !is_number(point, "x") || !is_number(point, "y"))
+    {
+        Some(i) => Err(format!("point {} must have numeric `x` and `y`", i)),
+        None => Ok(()),
+    }
+}
+
+/*
+ * TESTS
+ */
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+
+    use common::benchmark::BenchmarkType;
+    use common::dto::cpu::CpuSpecDTO;
+    use common::dto::result::{ResultDTO, LEGACY_VERSION, SCHEMA_VERSION};
+
+    use crate::validation::{validate_cpu_import, validate_cpu_spec, validate_result};
+
+    fn valid_result() -> ResultDTO {
+        ResultDTO {
+            model: "Intel(R) Core(TM) i7-8700".to_string(),
+            user_agent: "Mozilla/5.0".to_string(),
+            benchmark_results: vec![
+                json!([{"x": 512, "y": 1}, {"x": 516, "y": 2}]),
+                json!({"points": [{"x": 1024, "y": 5}], "boundaries": []}),
+            ],
+            times: vec![1.0, 2.5],
+            benchmarks: vec![BenchmarkType::CacheSize, BenchmarkType::PageSize],
+            capabilities: None,
+            seed: Some(42),
+            schema_version: SCHEMA_VERSION.to_string(),
+            frontend_version: "0.1.0".to_string(),
+            parameters: None,
+            model_check: None,
+        }
+    }
+
+    fn invalid_fields(result: &ResultDTO) -> Vec<String> {
+        validate_result(result)
+            .unwrap_err()
+            .into_iter()
+            .map(|error| error.field)
+            .collect()
+    }
+
+    #[test]
+    fn valid_result_passes() {
+        // execute & assert
+        assert!(validate_result(&valid_result()).is_ok());
+    }
+
+    #[test]
+    fn model_must_not_be_blank_or_too_long() {
+        // prepare
+        let mut blank = valid_result();
+        blank.model = "  ".to_string();
+        let mut long = valid_result();
+        long.model = "é".repeat(256);
+        let mut longest = valid_result();
+        longest.model = "é".repeat(255);
+
+        // execute & assert
+        assert_eq!(invalid_fields(&blank), vec!["model"]);
+        assert_eq!(invalid_fields(&long), vec!["model"]);
+        assert!(validate_result(&longest).is_ok());
+    }
+
+    #[test]
+    fn normalized_model_must_not_be_too_long() {
+        // prepare
+        let mut prefixed = valid_result();
+        prefixed.model = format!("Core {}", "i".repeat(250));
+        let mut shortened = valid_result();
+        shortened.model = format!("Core {} @ 3.20GHz", "i".repeat(230));
+
+        // execute
+        let errors = validate_result(&prefixed).unwrap_err();
+
+        // assert
+        assert_eq!(prefixed.model.chars().count(), 255);
+        assert_eq!(errors[0].field, "model");
+        assert!(errors[0].message.contains("after the normalization"));
+        assert!(validate_result(&shortened).is_ok());
+    }
+
+    #[test]
+    fn versions_must_not_be_missing_or_legacy() {
+        // prepare
+        let mut missing = serde_json::to_value(valid_result()).unwrap();
+        missing.as_object_mut().unwrap().remove("schema_version");
+        missing.as_object_mut().unwrap().remove("frontend_version");
+        let missing = serde_json::from_value::<ResultDTO>(missing).unwrap();
+        let mut legacy = valid_result();
+        legacy.schema_version = LEGACY_VERSION.to_string();
+        legacy.frontend_version = " ".to_string();
+
+        // execute & assert
+        assert_eq!(
+            invalid_fields(&missing),
+            vec!["schema_version", "frontend_version"]
+        );
+        assert_eq!(
+            invalid_fields(&legacy),
+            vec!["schema_version", "frontend_version"]
+        );
+    }
+
+    #[test]
+    fn times_must_match_results() {
+        // prepare
+        let mut result = valid_result();
+        result.times = vec![1.0, 2.0, 3.0];
+
+        // execute & assert
+        assert_eq!(invalid_fields(&result), vec!["times"]);
+    }
+
+    #[test]
+    fn benchmarks_must_name_each_result_once() {
+        // prepare
+        let mut missing = valid_result();
+        missing.benchmarks = vec![BenchmarkType::CacheSize];
+        let mut repeated = valid_result();
+        repeated.benchmarks = vec![BenchmarkType::PageSize, BenchmarkType::PageSize];
+        let mut positional = valid_result();
+        positional.benchmarks = vec![];
+
+        // execute & assert
+        assert_eq!(invalid_fields(&missing), vec!["benchmarks"]);
+        assert_eq!(invalid_fields(&repeated), vec!["benchmarks[1]"]);
+        assert!(validate_result(&positional).is_ok());
+    }
+
+    #[test]
+    fn malformed_results_are_reported_by_index() {
+        // prepare
+        let mut result = valid_result();
+        result.user_agent = "a".repeat(300);
+        result.frontend_version = "1".repeat(65);
+        result.benchmark_results = vec![
+            json!([{"x": 512}]),
+            json!({"boundaries": []}),
+            json!("points"),
+        ];
+        result.times = vec![1.0, f32::NAN, -1.0];
+        result.benchmarks = vec![];
+
+        // execute & assert
+        assert_eq!(
+            invalid_fields(&result),
+            vec![
+                "user_agent",
+                "frontend_version",
+                "benchmark_results[0]",
+                "benchmark_results[1]",
+                "benchmark_results[2]",
+                "times[1]",
+                "times[2]"
+            ]
+        );
+    }
+
+    #[test]
+    fn result_count_is_limited() {
+        // prepare
+        let mut empty = valid_result();
+        empty.benchmark_results = vec![];
+        empty.times = vec![];
+        empty.benchmarks = vec![];
+        let mut too_many = valid_result();
+        too_many.benchmark_results = vec![json!([]); 6];
+        too_many.times = vec![1.0; 6];
+        too_many.benchmarks = vec![];
+
+        // execute & assert
+        assert_eq!(invalid_fields(&empty), vec!["benchmark_results"]);
+        assert_eq!(invalid_fields(&too_many), vec!["benchmark_results"]);
+    }
+
+    #[test]
+    fn invalid_cpu_spec_fields_are_reported() {
+        // prepare
+        let spec = CpuSpecDTO {
+            manufacturer: "Intel".to_string(),
+            name: " ".to_string(),
+            alias: Some("x".repeat(256)),
+            cores: 4,
+            threads: 2,
+            base_ghz: 3.0,
+            boost_ghz: f32::NAN,
+            l1_kib: 32,
+            l2_kib: 256,
+            l3_mib: None,
+            l1_associativity: Some(8),
+            page_size: 4000,
+            load_buffer: None,
+            l1_dtlb: Some(64),
+            microarchitecture: "Skylake".to_string(),
+        };
+
+        // execute
+        let fields = validate_cpu_spec(&spec)
+            .unwrap_err()
+            .into_iter()
+            .map(|error| error.field)
+            .collect::<Vec<_>>();
+
+        // assert
+        assert_eq!(
+            fields,
+            vec!["name", "alias", "threads", "boost_ghz", "page_size"]
+        );
+    }
+
+    #[test]
+    fn conflicting_cpu_imports_are_reported() {
+        // prepare
+        let spec = |name: &str, alias: Option<&str>| CpuSpecDTO {
+            manufacturer: "Intel".to_string(),
+            name: name.to_string(),
+            alias: alias.map(str::to_string),
+            cores: 4,
+            threads: 8,
+            base_ghz: 3.0,
+            boost_ghz: 4.0,
+            l1_kib: 32,
+            l2_kib: 256,
+            l3_mib: None,
+            l1_associativity: Some(8),
+            page_size: 4096,
+            load_buffer: None,
+            l1_dtlb: Some(64),
+            microarchitecture: "Skylake".to_string(),
+        };
+        let catalogue = vec![
+            spec("stored", Some("stored alias")),
+            spec("replaced", Some("freed")),
+        ];
+        let specs = vec![
+            spec("first", Some("shared")),
+            spec("second", Some("shared")),
+            spec("first", None),
+            spec("third", Some("stored alias")),
+            spec("replaced", Some("other")),
+            spec("fourth", Some("freed")),
+        ];
+
+        // execute
+        let fields = validate_cpu_import(&specs, &catalogue)
+            .unwrap_err()
+            .into_iter()
+            .map(|error| error.field)
+            .collect::<Vec<_>>();
+
+        // assert
+        assert_eq!(
+            fields,
+            vec!["[0].alias", "[1].alias", "[2].name", "[3].alias"]
+        );
+    }
+}
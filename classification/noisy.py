@@ -81,12 +81,11 @@ execution_times = []
 def load_test_data():
     global db_models_test, execution_times, cacheasso_benchmarks, cachesize_benchmarks_small, cachesize_benchmarks_large
     global tlb_benchmarks, timer_diff, singleperf_benchmarks, cores
-    con = sqlite3.connect('/path/to/db_noisy.sqlite3')
-    cursor = con.execute("SELECT * FROM upload_benchmarkresult;")
-    for row in cursor.fetchall():
-        db_models_test += [row[1]]
-        execution_times += [json.loads(row[5])]
-        benchmarks = json.loads(row[3])
+    # the noisy dataset was recorded by an older frontend with more benchmarks, so it
+    # is only readable from a database created before the benchmark runs were stored separately
+    for model, benchmarks, times in load_sqlite_results('/path/to/db_noisy.sqlite3'):
+        db_models_test += [model]
+        execution_times += [times]
         cacheasso_benchmarks += [benchmarks[2]]
         cachesize_benchmarks_small += [benchmarks[3]]
         cachesize_benchmarks_large += [benchmarks[4]]
@@ -7,6 +7,8 @@ import math
 import random
 from itertools import chain, combinations
 
+from utils.results import load_sqlite_results
+
 
 def grouped(iterable, n):
     return zip(*[iter(iterable)] * n)
@@ -40,16 +42,13 @@ def determine_pagesize(benchmark, print_it):
 
 print('[-]  Loading from sqlite3 database')
 start_time = time.time()
-con = sqlite3.connect('/path/to/db.sqlite3')
 
 db_models = []
 pagesize_benchmarks = []
 
 # sqlite3
-cursor = con.execute("SELECT * FROM upload_benchmarkresult;")
-for row in cursor.fetchall():
-    db_models += [row[1]]
-    benchmarks = json.loads(row[3])
+for model, benchmarks, times in load_sqlite_results('/path/to/db.sqlite3'):
+    db_models += [model]
     pagesize_benchmarks += [benchmarks[0]]
 
 # large filter because of people not following the standard procedure
@@ -1,3 +1,4 @@
 from .cache import *
 from .preparation import *
-from .uarch import *
\ No newline at end of file
+from .results import *
+from .uarch import *
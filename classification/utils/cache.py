@@ -7,6 +7,8 @@ from pathlib import Path
 import pickle
 import os
 
+from .results import load_pgdump_results
+
 
 def model_filter(db_models):
     # large filter because of people not following the standard procedure
@@ -201,12 +203,10 @@ def load_database():
     execution_times = []
 
     print('[-]  Loading from PostgreSQL database')
-    dump = pgdumplib.load('new_backend.pgdump')
-    for row in dump.table_data('public', 'upload_benchmarkresult'):
-        db_models += [row[1]]
+    for model, benchmarks, times in load_pgdump_results('new_backend.pgdump'):
+        db_models += [model]
 
-        execution_times += [json.loads(row[5])]
-        benchmarks = json.loads(row[3])
+        execution_times += [times]
 
         #   0: "PageSize"
         #   1: "CacheSize"
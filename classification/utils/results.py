@@ -0,0 +1,57 @@
+import json
+import sqlite3
+from collections import defaultdict
+
+import pgdumplib
+from pgdumplib import exceptions
+
+# Order of the benchmarks in the benchmark_results and times columns, which were
+# replaced by the benchmark_run table
+BENCHMARK_ORDER = ['PageSize', 'CacheSize', 'TlbSize', 'CacheAssociativity', 'SinglePerformance']
+
+
+def assemble_results(results, runs):
+    # results are (id, model) rows of upload_benchmarkresult,
+    # runs are (result_id, benchmark, duration, data) rows of benchmark_run
+    by_result = defaultdict(dict)
+    for result_id, benchmark, duration, data in runs:
+        by_result[str(result_id)][benchmark] = (float(duration), json.loads(data) if isinstance(data, str) else data)
+
+    assembled = []
+    for result_id, model in results:
+        result_runs = by_result[str(result_id)]
+        # benchmarks missing from the result have no data points
+        benchmarks = [result_runs[name][1] if name in result_runs else [] for name in BENCHMARK_ORDER]
+        times = [result_runs[name][0] if name in result_runs else 0 for name in BENCHMARK_ORDER]
+        assembled += [(model, benchmarks, times)]
+
+    return assembled
+
+
+def load_pgdump_results(path):
+    # returns (model, benchmark_results, times) of every result, in the layout of the old columns
+    dump = pgdumplib.load(path)
+    try:
+        runs = [(row[1], row[2], row[4], row[5]) for row in dump.table_data('public', 'benchmark_run')]
+    except exceptions.EntityNotFoundError:
+        # dumps taken before the benchmark runs were stored separately
+        return [(row[1], json.loads(row[3]), json.loads(row[5]))
+                for row in dump.table_data('public', 'upload_benchmarkresult')]
+
+    results = [(row[0], row[1]) for row in dump.table_data('public', 'upload_benchmarkresult')]
+    return assemble_results(results, runs)
+
+
+def load_sqlite_results(path):
+    # returns (model, benchmark_results, times) of every result, in the layout of the old columns
+    con = sqlite3.connect(path)
+    has_runs = con.execute(
+        "SELECT count(*) FROM sqlite_master WHERE type = 'table' AND name = 'benchmark_run';").fetchone()[0] > 0
+    if not has_runs:
+        # databases created before the benchmark runs were stored separately
+        return [(row[1], json.loads(row[3]), json.loads(row[5]))
+                for row in con.execute("SELECT * FROM upload_benchmarkresult;").fetchall()]
+
+    results = con.execute("SELECT id, model FROM upload_benchmarkresult ORDER BY id;").fetchall()
+    runs = con.execute("SELECT result_id, benchmark, duration, data FROM benchmark_run;").fetchall()
+    return assemble_results(results, runs)
@@ -0,0 +1,403 @@
+use std::cmp::Ordering;
+use std::collections::HashMap;
+use std::fmt;
+
+use rand::seq::SliceRandom;
+use rand::SeedableRng;
+use rand_chacha::ChaCha8Rng;
+
+use common::classification::{base_microarchitecture, CpuLabels, KnnClassifier, TrainingSample};
+use common::dto::classification::{CandidateDTO, ClassificationDTO};
+
+/// Larger confusion matrices are summarised by the most common confusions
+const MAX_MATRIX_CLASSES: usize = 16;
+const LISTED_CONFUSIONS: usize = 10;
+/// Shown for samples without any prediction
+const NO_PREDICTION: &str = "-";
+
+/// Property of the CPU evaluated separately
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Target {
+    L1,
+    L2,
+    L3,
+    /// Microarchitecture merged with the ones derived from it
+    Microarchitecture,
+    Model,
+}
+
+impl Target {
+    pub const ALL: [Target; 5] = [
+        Target::L1,
+        Target::L2,
+        Target::L3,
+        Target::Microarchitecture,
+        Target::Model,
+    ];
+
+    fn label(&self, labels: &CpuLabels) -> Option<String> {
+        match self {
+            Target::L1 => Some(labels.l1_kib.to_string()),
+            Target::L2 => Some(labels.l2_kib.to_string()),
+            Target::L3 => labels.l3_mib.map(|l3_mib| l3_mib.to_string()),
+            Target::Microarchitecture => {
+                Some(base_microarchitecture(&labels.microarchitecture).to_string())
+            }
+            Target::Model => Some(labels.model.clone()),
+        }
+    }
+
+    fn prediction(&self, classification: &ClassificationDTO) -> Option<String> {
+        match self {
+            Target::L1 => first(&classification.l1_kib).map(|value| value.to_string()),
+            Target::L2 => first(&classification.l2_kib).map(|value| value.to_string()),
+            Target::L3 => first(&classification.l3_mib).map(|value| value.to_string()),
+            Target::Microarchitecture => first(&classification.microarchitectures)
+                .map(|microarchitecture| base_microarchitecture(microarchitecture).to_string()),
+            Target::Model => first(&classification.models).cloned(),
+        }
+    }
+}
+
+impl fmt::Display for Target {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Target::L1 => write!(f, "L1 size (KiB)"),
+            Target::L2 => write!(f, "L2 size (KiB)"),
+            Target::L3 => write!(f, "L3 size (MiB)"),
+            Target::Microarchitecture => write!(f, "Microarchitecture family"),
+            Target::Model => write!(f, "Model"),
+        }
+    }
+}
+
+fn first<T>(candidates: &[CandidateDTO<T>]) -> Option<&T> {
+    candidates.first().map(|candidate| &candidate.value)
+}
+
+/// Parameters of the cross-validation
+#[derive(Clone, Copy, Debug)]
+pub struct Validation {
+    pub folds: usize,
+    pub neighbours: usize,
+    /// Seed of the assignment of the samples to the folds
+    pub seed: u64,
+}
+
+/// Keeps at most `max_per_class` samples of every model, as `max_count` in
+/// `classification/utils/preparation.py`
+pub fn balance(samples: Vec<TrainingSample>, max_per_class: usize) -> Vec<TrainingSample> {
+    let mut class_counts = HashMap::new();
+    samples
+        .into_iter()
+        .filter(|sample| {
+            let count = class_counts.entry(sample.labels.model.clone()).or_insert(0);
+            *count += 1;
+            *count <= max_per_class
+        })
+        .collect()
+}
+
+/// k-fold cross-validation of the classifier trained the same way as the exported one.
+/// Each fold is classified by the classifier trained on the other ones, every target is
+/// evaluated on the same predictions. Returns the confusion matrices in the order of
+/// `Target::ALL`.
+pub fn cross_validate(
+    samples: &[TrainingSample],
+    validation: &Validation,
+) -> Vec<(Target, ConfusionMatrix)> {
+    let folds = assign_folds(samples, validation);
+
+    let mut matrices = Target::ALL
+        .iter()
+        .map(|target| (*target, ConfusionMatrix::default()))
+        .collect::<Vec<_>>();
+    for fold in 0..validation.folds {
+        let training = samples
+            .iter()
+            .zip(folds.iter())
+            .filter(|(_, sample_fold)| **sample_fold != fold)
+            .map(|(sample, _)| sample.clone())
+            .collect::<Vec<_>>();
+        if training.is_empty() {
+            continue;
+        }
+        let classifier = KnnClassifier::train(training, validation.neighbours);
+
+        for (sample, _) in samples
+            .iter()
+            .zip(folds.iter())
+            .filter(|(_, sample_fold)| **sample_fold == fold)
+        {
+            let classification = classifier.classify(&sample.features);
+            for (target, confusion) in matrices.iter_mut() {
+                // samples without a label of the target are skipped
+                if let Some(label) = target.label(&sample.labels) {
+                    let prediction = target.prediction(&classification);
+                    confusion.add(&label, prediction.as_deref().unwrap_or(NO_PREDICTION));
+                }
+            }
+        }
+    }
+
+    matrices
+}
+
+/// Folds of the samples, stratified by the model. The samples are shuffled by the seed,
+/// then the samples of every model are assigned to the folds in turns.
+fn assign_folds(samples: &[TrainingSample], validation: &Validation) -> Vec<usize> {
+    let mut order = (0..samples.len()).collect::<Vec<_>>();
+    order.shuffle(&mut ChaCha8Rng::seed_from_u64(validation.seed));
+    // stable, so the samples of a model stay shuffled
+    order.sort_by(|a, b| samples[*a].labels.model.cmp(&samples[*b].labels.model));
+
+    let mut folds = vec![0; samples.len()];
+    for (turn, i) in order.into_iter().enumerate() {
+        folds[i] = turn % validation.folds;
+    }
+    folds
+}
+
+/// Counts of the predicted classes of every actual class
+#[derive(Default, Debug)]
+pub struct ConfusionMatrix {
+    counts: HashMap<(String, String), usize>,
+}
+
+impl ConfusionMatrix {
+    pub fn add(&mut self, actual: &str, predicted: &str) {
+        *self
+            .counts
+            .entry((actual.to_string(), predicted.to_string()))
+            .or_insert(0) += 1;
+    }
+
+    pub fn total(&self) -> usize {
+        self.counts.values().sum()
+    }
+
+    pub fn accuracy(&self) -> f64 {
+        let correct = self
+            .counts
+            .iter()
+            .filter(|((actual, predicted), _)| actual == predicted)
+            .map(|(_, count)| count)
+            .sum::<usize>();
+        correct as f64 / self.total().max(1) as f64
+    }
+
+    /// Actual and predicted classes, the numeric ones in numeric order
+    fn classes(&self) -> Vec<&str> {
+        let mut classes = self
+            .counts
+            .keys()
+            .flat_map(|(actual, predicted)| [actual.as_str(), predicted.as_str()])
+            .collect::<Vec<_>>();
+        classes.sort_by(|a, b| match (a.parse::<f64>(), b.parse::<f64>()) {
+            (Ok(a), Ok(b)) => a.total_cmp(&b),
+            (Ok(_), Err(_)) => Ordering::Less,
+            (Err(_), Ok(_)) => Ordering::Greater,
+            (Err(_), Err(_)) => a.cmp(b),
+        });
+        classes.dedup();
+        classes
+    }
+
+    fn count(&self, actual: &str, predicted: &str) -> usize {
+        self.counts
+            .get(&(actual.to_string(), predicted.to_string()))
+            .copied()
+            .unwrap_or(0)
+    }
+}
+
+/// Rows of the actual classes and columns of the predicted ones
+impl fmt::Display for ConfusionMatrix {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let classes = self.classes();
+
+        if classes.len() > MAX_MATRIX_CLASSES {
+            let mut confusions = self
+                .counts
+                .iter()
+                .filter(|((actual, predicted), _)| actual != predicted)
+                .collect::<Vec<_>>();
+            confusions
+                .sort_by(|(a_classes, a), (b_classes, b)| b.cmp(a).then(a_classes.cmp(b_classes)));
+            writeln!(f, "{} classes, the most common confusions:", classes.len())?;
+            for ((actual, predicted), count) in confusions.into_iter().take(LISTED_CONFUSIONS) {
+                writeln!(f, "{:>5}  {} predicted as {}", count, actual, predicted)?;
+            }
+            return Ok(());
+        }
+
+        let width = classes
+            .iter()
+            .map(|class| class.len())
+            .max()
+            .unwrap_or(0)
+            .max(5);
+        write!(f, "{:>width$}", "", width = width)?;
+        for predicted in classes.iter() {
+            write!(f, " {:>width$}", predicted, width = width)?;
+        }
+        writeln!(f)?;
+        for actual in classes.iter() {
+            write!(f, "{:>width$}", actual, width = width)?;
+            for predicted in classes.iter() {
+                write!(
+                    f,
+                    " {:>width$}",
+                    self.count(actual, predicted),
+                    width = width
+                )?;
+            }
+            writeln!(f)?;
+        }
+        Ok(())
+    }
+}
+
+/*
+ * TESTS
+ */
+
+#[cfg(test)]
+mod tests {
+    use common::classification::{CpuLabels, TrainingSample, FEATURE_COUNT};
+
+    use crate::evaluation::{
+        assign_folds, balance, cross_validate, ConfusionMatrix, Target, Validation,
+    };
+
+    fn sample(value: f64, model: &str, l1_kib: u32, microarchitecture: &str) -> TrainingSample {
+        TrainingSample {
+            features: vec![value; FEATURE_COUNT],
+            labels: CpuLabels {
+                model: model.to_string(),
+                microarchitecture: microarchitecture.to_string(),
+                l1_kib,
+                l2_kib: 256,
+                l3_mib: None,
+            },
+        }
+    }
+
+    fn confusion(matrices: &[(Target, ConfusionMatrix)], target: Target) -> &ConfusionMatrix {
+        &matrices.iter().find(|(t, _)| *t == target).unwrap().1
+    }
+
+    #[test]
+    fn separable_classes_are_validated() {
+        // prepare
+        let samples = (0..12)
+            .map(|i| match i % 2 {
+                0 => sample(i as f64 * 0.01, "first", 32, "Coffee Lake"),
+                _ => sample(10.0 + i as f64 * 0.01, "second", 48, "Skylake"),
+            })
+            .collect::<Vec<_>>();
+        let validation = Validation {
+            folds: 3,
+            neighbours: 3,
+            seed: 42,
+        };
+
+        // execute
+        let matrices = cross_validate(&samples, &validation);
+
+        // assert
+        let l1 = confusion(&matrices, Target::L1);
+        assert_eq!(l1.total(), 12);
+        assert_eq!(l1.accuracy(), 1.0);
+        assert_eq!(l1.count("48", "48"), 6);
+        // Coffee Lake is merged into Skylake
+        let microarchitecture = confusion(&matrices, Target::Microarchitecture);
+        assert_eq!(microarchitecture.count("Skylake", "Skylake"), 12);
+        // samples without a label are skipped
+        assert_eq!(confusion(&matrices, Target::L3).total(), 0);
+    }
+
+    #[test]
+    fn folds_are_stratified_by_model() {
+        // prepare
+        let samples = (0..12)
+            .map(|i| match i {
+                0..=5 => sample(0.0, "first", 32, "Zen 2"),
+                _ => sample(1.0, "second", 32, "Zen 3"),
+            })
+            .collect::<Vec<_>>();
+        let validation = Validation {
+            folds: 3,
+            neighbours: 1,
+            seed: 42,
+        };
+
+        // execute
+        let folds = assign_folds(&samples, &validation);
+        let reseeded = assign_folds(
+            &samples,
+            &Validation {
+                seed: 7,
+                ..validation
+            },
+        );
+
+        // assert
+        for fold in 0..3 {
+            let models = samples
+                .iter()
+                .zip(folds.iter())
+                .filter(|(_, sample_fold)| **sample_fold == fold)
+                .map(|(sample, _)| sample.labels.model.as_str())
+                .collect::<Vec<_>>();
+            assert_eq!(models.iter().filter(|model| **model == "first").count(), 2);
+            assert_eq!(models.iter().filter(|model| **model == "second").count(), 2);
+        }
+        assert_eq!(folds, assign_folds(&samples, &validation));
+        assert_ne!(folds, reseeded);
+    }
+
+    #[test]
+    fn classes_are_balanced() {
+        // prepare
+        let samples = (0..10)
+            .map(|i| match i {
+                0..=7 => sample(0.0, "common", 32, "Zen 2"),
+                _ => sample(1.0, "rare", 32, "Zen 3"),
+            })
+            .collect::<Vec<_>>();
+
+        // execute
+        let balanced = balance(samples, 3);
+
+        // assert
+        assert_eq!(balanced.len(), 5);
+        assert_eq!(
+            balanced
+                .iter()
+                .filter(|sample| sample.labels.model == "rare")
+                .count(),
+            2
+        );
+    }
+
+    #[test]
+    fn matrix_lists_numeric_classes_in_order() {
+        // prepare
+        let mut confusion = ConfusionMatrix::default();
+        confusion.add("256", "256");
+        confusion.add("1024", "256");
+        confusion.add("1024", "1024");
+        confusion.add("512", "1024");
+
+        // execute
+        let printed = confusion.to_string();
+
+        // assert
+        assert_eq!(confusion.accuracy(), 0.5);
+        assert_eq!(
+            printed,
+            "        256   512  1024\n  256     1     0     0\n  512     0     0     1\n 1024     1     0     1\n"
+        );
+    }
+}
@@ -0,0 +1,171 @@
+mod evaluation;
+
+use std::env;
+use std::error::Error;
+use std::fs::{self, File};
+use std::io::{BufRead, BufReader, BufWriter};
+use std::process::exit;
+
+use common::classification::{
+    catalogue_labels, feature_vector, KnnClassifier, TrainingSample, DEFAULT_NEIGHBOURS,
+};
+use common::cpu::parse_cpu_specs;
+use common::dto::result::StoredResultDTO;
+
+use crate::evaluation::{balance, cross_validate, Validation};
+
+const USAGE: &str =
+    "Usage: classify --export EXPORT --catalogue CATALOGUE [--folds FOLDS] [--neighbours K] [--seed SEED] [--max-per-class COUNT] [--no-balance] [--output MODEL]
+
+Evaluates the CPU classifier of the backend by k-fold cross-validation
+on the results exported from /api/export in the ndjson format, labelled
+by the catalogue in the format of classification/cpus.csv. The folds are
+shuffled by the seed and stratified by the model. Prints the accuracy and
+the confusion matrix of the L1, L2 and L3 sizes, the microarchitecture
+family and the model. With --output, the classifier trained on all the
+evaluated results is written to MODEL, which the backend loads when
+`classifier_model` is configured.";
+
+const DEFAULT_FOLDS: usize = 5;
+const DEFAULT_SEED: u64 = 42;
+const DEFAULT_MAX_PER_CLASS: usize = 30;
+
+struct Arguments {
+    export: String,
+    catalogue: String,
+    validation: Validation,
+    max_per_class: Option<usize>,
+    output: Option<String>,
+}
+
+fn main() -> Result<(), Box<dyn Error>> {
+    let arguments = parse_arguments().unwrap_or_else(|error| {
+        eprintln!("{}\n\n{}", error, USAGE);
+        exit(1);
+    });
+
+    let specs = parse_cpu_specs(&fs::read_to_string(&arguments.catalogue)?)?;
+    let labels = catalogue_labels(&specs);
+
+    let mut result_count = 0;
+    let mut samples = vec![];
+    for (i, line) in BufReader::new(File::open(&arguments.export)?)
+        .lines()
+        .enumerate()
+    {
+        let line = line?;
+        if line.trim().is_empty() {
+            continue;
+        }
+        let result = serde_json::from_str::<StoredResultDTO>(&line)
+            .map_err(|e| format!("line {} of the export: {}", i + 1, e))?;
+        result_count += 1;
+        if let Some(labels) = labels.get(&result.model_normalized) {
+            samples.push(TrainingSample {
+                features: feature_vector(&result.result),
+                labels: labels.clone(),
+            });
+        }
+    }
+    println!(
+        "{} of {} results match the {} CPU specifications",
+        samples.len(),
+        result_count,
+        specs.len()
+    );
+    // the exported classifier is trained on the same samples as the evaluated ones
+    if let Some(max_per_class) = arguments.max_per_class {
+        samples = balance(samples, max_per_class);
+        println!(
+            "{} results are kept with at most {} of each model",
+            samples.len(),
+            max_per_class
+        );
+    }
+    println!();
+
+    for (target, confusion) in cross_validate(&samples, &arguments.validation) {
+        println!(
+            "{}: accuracy {:.3} of {} results",
+            target,
+            confusion.accuracy(),
+            confusion.total()
+        );
+        println!("{}", confusion);
+    }
+
+    if let Some(output) = arguments.output {
+        let classifier = KnnClassifier::train(samples, arguments.validation.neighbours);
+        serde_json::to_writer(BufWriter::new(File::create(&output)?), &classifier)?;
+        println!(
+            "Wrote the classifier trained on {} results to {}",
+            classifier.len(),
+            output
+        );
+    }
+
+    Ok(())
+}
+
+fn parse_arguments() -> Result<Arguments, String> {
+    let mut export = None;
+    let mut catalogue = None;
+    let mut validation = Validation {
+        folds: DEFAULT_FOLDS,
+        neighbours: DEFAULT_NEIGHBOURS,
+        seed: DEFAULT_SEED,
+    };
+    let mut max_per_class = Some(DEFAULT_MAX_PER_CLASS);
+    let mut output = None;
+
+    let mut args = env::args().skip(1);
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "--export" => export = Some(args.next().ok_or("Missing export path")?),
+            "--catalogue" => catalogue = Some(args.next().ok_or("Missing catalogue path")?),
+            "--folds" => {
+                validation.folds = args
+                    .next()
+                    .and_then(|folds| folds.parse().ok())
+                    .filter(|folds| *folds >= 2)
+                    .ok_or("Folds must be an integer of at least 2")?
+            }
+            "--neighbours" => {
+                validation.neighbours = args
+                    .next()
+                    .and_then(|neighbours| neighbours.parse().ok())
+                    .filter(|neighbours| *neighbours >= 1)
+                    .ok_or("Neighbours must be a positive integer")?
+            }
+            "--seed" => {
+                validation.seed = args
+                    .next()
+                    .and_then(|seed| seed.parse().ok())
+                    .ok_or("Seed must be a non-negative integer")?
+            }
+            "--max-per-class" => {
+                max_per_class = Some(
+                    args.next()
+                        .and_then(|count| count.parse().ok())
+                        .filter(|count| *count >= 1)
+                        .ok_or("Max per class must be a positive integer")?,
+                )
+            }
+            "--no-balance" => max_per_class = None,
+            "--output" => output = Some(args.next().ok_or("Missing model path")?),
+            "--help" | "-h" => {
+                println!("{}", USAGE);
+                exit(0);
+            }
+            arg => return Err(format!("Unknown argument {}", arg)),
+        }
+    }
+
+    Ok(Arguments {
+        export: export.ok_or("Missing --export")?,
+        catalogue: catalogue.ok_or("Missing --catalogue")?,
+        validation,
+        max_per_class,
+        output,
+    })
+}
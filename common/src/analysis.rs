@@ -0,0 +1,596 @@
+use serde::{Deserialize, Serialize};
+
+use crate::benchmark::BenchmarkType;
+use crate::chart::{parse_boundaries, parse_points};
+use crate::classification::base_microarchitecture;
+use crate::dto::cpu::CpuSpecDTO;
+use crate::dto::result::{CpuProperty, ModelCheckDTO, ResultDTO};
+use crate::steps::{rising_steps, sorted, steps_at, Step};
+
+/// Page sizes considered by the estimator, in 4 B elements (1 KiB to 256 KiB)
+const PAGE_SIZE_CANDIDATES: [u64; 9] = [256, 512, 1024, 2048, 4096, 8192, 16384, 32768, 65536];
+/// Page boundary spikes are at least this many times slower than the median access
+const SPIKE_RATIO: f64 = 2.0;
+/// Spikes closer than this many measurements belong to the same page boundary
+const SPIKE_GAP: u64 = 2;
+
+/// Number of the cache levels estimated from the cache size benchmark
+pub const CACHE_LEVELS: usize = 3;
+
+/// Minimal confidence of the estimates compared with the specifications
+pub const CHECK_CONFIDENCE: f64 = 0.5;
+/// Cache sizes within this many octaves of the specification agree with it,
+/// as the benchmarks measure only a few sizes between the powers of two
+const SIZE_TOLERANCE_OCTAVES: f64 = 0.75;
+/// Minimal share of the agreeing specifications with the same microarchitecture family
+const MICROARCHITECTURE_SHARE: f64 = 0.5;
+
+/// Value estimated from a benchmark, with the confidence of the estimate between 0 and 1
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, PartialEq)]
+pub struct Estimate<T> {
+    pub value: T,
+    pub confidence: f64,
+}
+
+/// Sizes of the data caches in bytes
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, Default, PartialEq)]
+pub struct CacheSizes {
+    pub l1: Option<Estimate<u64>>,
+    pub l2: Option<Estimate<u64>>,
+    pub l3: Option<Estimate<u64>>,
+}
+
+/// Features of the CPU estimated from all the benchmarks of a result
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, Default, PartialEq)]
+pub struct Features {
+    /// Page size in bytes
+    pub page_size: Option<Estimate<u64>>,
+    pub cache_sizes: CacheSizes,
+    pub tlb_entries: Option<Estimate<u32>>,
+    pub l1_associativity: Option<Estimate<u32>>,
+}
+
+/// Estimates the features from the benchmarks of the result. Missing or malformed
+/// benchmarks leave their features empty.
+pub fn extract_features(result: &ResultDTO) -> Features {
+    let mut features = Features::default();
+    for (benchmark, data) in result
+        .benchmark_types()
+        .into_iter()
+        .zip(result.benchmark_results.iter())
+    {
+        let points = parse_points(data);
+        match benchmark {
+            BenchmarkType::PageSize => features.page_size = estimate_page_size(&points),
+            BenchmarkType::CacheSize => {
+                features.cache_sizes = match parse_boundaries(data) {
+                    Some(boundaries) => recorded_cache_sizes(&points, &boundaries),
+                    None => estimate_cache_sizes(&points),
+                }
+            }
+            BenchmarkType::TlbSize => features.tlb_entries = estimate_tlb_entries(&points),
+            BenchmarkType::CacheAssociativity => {
+                features.l1_associativity = estimate_l1_associativity(&points)
+            }
+            BenchmarkType::SinglePerformance => {}
+        }
+    }
+    features
+}
+
+/// Estimates the page size in bytes from the page size benchmark, whose `x` is the offset
+/// in 4 B elements. Accesses crossing a page boundary are slow, so the spikes repeat with
+/// the period of the page size. The confidence is the share of the spikes in phase with
+/// the period, times the share of the page boundaries showing a spike.
+pub fn estimate_page_size(points: &[(f64, f64)]) -> Option<Estimate<u64>> {
+    let points = sorted(points);
+    let step = points
+        .windows(2)
+        .map(|w| (w[1].0 - w[0].0) as u64)
+        .filter(|step| *step > 0)
+        .min()?;
+    let (first, last) = (points.first()?.0 as u64, points.last()?.0 as u64);
+
+    let mut ys = points.iter().map(|(_, y)| *y).collect::<Vec<_>>();
+    ys.sort_by(f64::total_cmp);
+    let median = ys[ys.len() / 2];
+    let threshold = (median * SPIKE_RATIO).max(median + 1.0);
+
+    // a boundary may slow down a few neighbouring accesses, only the first one is kept
+    let mut spikes: Vec<u64> = vec![];
+    for (x, _) in points.iter().filter(|(_, y)| *y >= threshold) {
+        let x = *x as u64;
+        if spikes
+            .last()
+            .map_or(true, |last| x - last > SPIKE_GAP * step)
+        {
+            spikes.push(x);
+        }
+    }
+    if spikes.len() < 3 {
+        return None;
+    }
+
+    PAGE_SIZE_CANDIDATES
+        .iter()
+        .filter(|period| (last - first) / **period >= 2)
+        .filter_map(|period| {
+            // spikes of the same phase, counted with a tolerance of one measurement
+            let mut phases = spikes.iter().map(|x| x % period).collect::<Vec<_>>();
+            phases.sort_unstable();
+            let (phase, in_phase) = phases
+                .iter()
+                .map(|phase| {
+                    let count = phases
+                        .iter()
+                        .filter(|other| circular_distance(**other, *phase, *period) <= step)
+                        .count();
+                    (*phase, count)
+                })
+                .max_by_key(|(_, count)| *count)?;
+
+            let first_boundary = first + (phase + period - first % period) % period;
+            let boundaries = (last.saturating_sub(first_boundary)) / period + 1;
+            let precision = in_phase as f64 / spikes.len() as f64;
+            let recall = (in_phase as f64 / boundaries as f64).min(1.0);
+            Some(Estimate {
+                // offsets are counted in 4 B elements
+                value: period * 4,
+                confidence: precision * recall,
+            })
+        })
+        .max_by(|a, b| a.confidence.total_cmp(&b.confidence))
+}
+
+fn circular_distance(a: u64, b: u64, period: u64) -> u64 {
+    let distance = a.abs_diff(b);
+    distance.min(period - distance)
+}
+
+/// Estimates the cache sizes in bytes from the cache size benchmark, whose `x` is the size
+/// of the walked list. Every rising step of the latency is a cache level, which is as large
+/// as the last list before the step.
+pub fn estimate_cache_sizes(points: &[(f64, f64)]) -> CacheSizes {
+    cache_levels(rising_steps(points, CACHE_LEVELS))
+}
+
+/// Cache sizes at the boundaries recorded by the adaptive cache size search,
+/// with the confidence of the steps measured there
+pub fn recorded_cache_sizes(points: &[(f64, f64)], boundaries: &[f64]) -> CacheSizes {
+    cache_levels(steps_at(points, boundaries))
+}
+
+fn cache_levels(steps: Vec<Step>) -> CacheSizes {
+    let mut levels = steps.into_iter().map(|step| Estimate {
+        value: step.x as u64,
+        confidence: step.confidence,
+    });
+
+    CacheSizes {
+        l1: levels.next(),
+        l2: levels.next(),
+        l3: levels.next(),
+    }
+}
+
+/// Estimates the number of the L1 data TLB entries from the TLB size benchmark, whose `x`
+/// is the number of the walked pages and `y` the time of walking all of them
+pub fn estimate_tlb_entries(points: &[(f64, f64)]) -> Option<Estimate<u32>> {
+    let per_page = points
+        .iter()
+        .filter(|(x, _)| *x > 0.0)
+        .map(|(x, y)| (*x, y / x))
+        .collect::<Vec<_>>();
+
+    first_rising_step(&per_page)
+}
+
+/// Estimates the L1 associativity from the cache associativity benchmark, whose `x` is the
+/// number of lines mapped to the same set. Lines over the associativity evict each other.
+pub fn estimate_l1_associativity(points: &[(f64, f64)]) -> Option<Estimate<u32>> {
+    first_rising_step(points)
+}
+
+fn first_rising_step(points: &[(f64, f64)]) -> Option<Estimate<u32>> {
+    rising_steps(points, 1).first().map(|step| Estimate {
+        value: step.x.round() as u32,
+        confidence: step.confidence,
+    })
+}
+
+/// Compares the confident estimates with the specification of the entered model.
+/// Without a specification, there is nothing to contradict.
+pub fn check_model(features: &Features, spec: Option<&CpuSpecDTO>) -> ModelCheckDTO {
+    ModelCheckDTO {
+        spec_name: spec.map(|spec| spec.name.clone()),
+        mismatches: spec.map_or(vec![], |spec| mismatches(features, spec)),
+    }
+}
+
+/// Family of the microarchitecture most common among the specifications agreeing
+/// with the estimated cache sizes, if it is the family of the majority of them
+pub fn likely_microarchitecture(features: &Features, specs: &[CpuSpecDTO]) -> Option<String> {
+    let caches = &features.cache_sizes;
+    if [caches.l1, caches.l2, caches.l3]
+        .iter()
+        .all(|estimate| !is_confident(estimate))
+    {
+        return None;
+    }
+
+    let agreeing = specs
+        .iter()
+        .filter(|spec| {
+            !mismatches(features, spec).iter().any(|property| {
+                matches!(
+                    property,
+                    CpuProperty::L1Size | CpuProperty::L2Size | CpuProperty::L3Size
+                )
+            })
+        })
+        .collect::<Vec<_>>();
+
+    let mut families: Vec<(&str, usize)> = vec![];
+    for spec in agreeing.iter() {
+        let family = base_microarchitecture(&spec.microarchitecture);
+        match families.iter_mut().find(|(name, _)| *name == family) {
+            Some((_, count)) => *count += 1,
+            None => families.push((family, 1)),
+        }
+    }
+
+    families
+        .into_iter()
+        .max_by_key(|(_, count)| *count)
+        .filter(|(_, count)| *count as f64 >= MICROARCHITECTURE_SHARE * agreeing.len() as f64)
+        .map(|(family, _)| family.to_string())
+}
+
+/// Properties whose confident estimates contradict the specification
+fn mismatches(features: &Features, spec: &CpuSpecDTO) -> Vec<CpuProperty> {
+    let caches = &features.cache_sizes;
+    let sizes = [
+        (
+            CpuProperty::L1Size,
+            caches.l1,
+            Some(spec.l1_kib as u64 * 1024),
+        ),
+        (
+            CpuProperty::L2Size,
+            caches.l2,
+            Some(spec.l2_kib as u64 * 1024),
+        ),
+        (
+            CpuProperty::L3Size,
+            caches.l3,
+            spec.l3_mib.map(|l3_mib| l3_mib as u64 * 1024 * 1024),
+        ),
+    ];
+    let exact = [
+        (
+            CpuProperty::PageSize,
+            features.page_size,
+            Some(spec.page_size as u64),
+        ),
+        (
+            CpuProperty::L1Associativity,
+            features.l1_associativity.map(|estimate| Estimate {
+                value: estimate.value as u64,
+                confidence: estimate.confidence,
+            }),
+            spec.l1_associativity.map(u64::from),
+        ),
+    ];
+
+    let size_mismatches = sizes.into_iter().filter(|(_, estimate, specified)| {
+        disagrees(estimate, specified, |estimated, specified| {
+            (estimated as f64 / specified as f64).log2().abs() <= SIZE_TOLERANCE_OCTAVES
+        })
+    });
+    let exact_mismatches = exact.into_iter().filter(|(_, estimate, specified)| {
+        disagrees(estimate, specified, |estimated, specified| {
+            estimated == specified
+        })
+    });
+
+    size_mismatches
+        .chain(exact_mismatches)
+        .map(|(property, _, _)| property)
+        .collect()
+}
+
+/// Whether the estimate is confident and does not agree with the specified value
+fn disagrees(
+    estimate: &Option<Estimate<u64>>,
+    specified: &Option<u64>,
+    agree: impl Fn(u64, u64) -> bool,
+) -> bool {
+    match (estimate, specified) {
+        (Some(estimate), Some(specified)) if estimate.confidence >= CHECK_CONFIDENCE => {
+            !agree(estimate.value, *specified)
+        }
+        _ => false,
+    }
+}
+
+fn is_confident<T>(estimate: &Option<Estimate<T>>) -> bool {
+    estimate
+        .as_ref()
+        .map_or(false, |estimate| estimate.confidence >= CHECK_CONFIDENCE)
+}
+
+/*
+ * TESTS
+ */
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+
+    use crate::analysis::{
+        check_model, estimate_cache_sizes, estimate_l1_associativity, estimate_page_size,
+        estimate_tlb_entries, extract_features, likely_microarchitecture, CacheSizes, Estimate,
+        Features, SIZE_TOLERANCE_OCTAVES,
+    };
+    use crate::benchmark::BenchmarkType;
+    use crate::chart::parse_points;
+    use crate::cpu::parse_cpu_specs;
+    use crate::dto::result::{CpuProperty, ResultDTO, SCHEMA_VERSION};
+
+    /// Deterministic noise of the given relative amplitude
+    fn jitter(i: usize, amplitude: f64) -> f64 {
+        1.0 + amplitude * (((i * 7919) % 13) as f64 / 6.0 - 1.0)
+    }
+
+    /// Page size benchmark of the frontend, with spikes every `page` bytes
+    fn page_size_points(page: u64) -> Vec<(f64, f64)> {
+        (0..65_000u64)
+            .map(|i| {
+                let x = 512 + i * 4;
+                let crossing = (x * 4 + 1000) % page < 16;
+                (x as f64, if crossing { 30.0 } else { 5.0 })
+            })
+            .collect()
+    }
+
+    /// Cache size benchmark of the frontend, with latency steps after 32 KiB, 1 MiB and 16 MiB
+    fn cache_size_points() -> Vec<(f64, f64)> {
+        let sizes = std::iter::once(1)
+            .chain((4..=512).step_by(4))
+            .chain(std::iter::once(1024))
+            .chain((2..=32).step_by(2).map(|mib| mib * 1024));
+        sizes
+            .enumerate()
+            .map(|(i, kib)| {
+                let latency = match kib {
+                    ..=32 => 10.0,
+                    33..=1024 => 25.0,
+                    1025..=16384 => 60.0,
+                    _ => 150.0,
+                };
+                ((kib * 1024) as f64, latency * jitter(i, 0.05))
+            })
+            .collect()
+    }
+
+    #[test]
+    fn page_size_is_period_of_spikes() {
+        // execute
+        let small = estimate_page_size(&page_size_points(4096)).unwrap();
+        let large = estimate_page_size(&page_size_points(16384)).unwrap();
+
+        // assert
+        assert_eq!(small.value, 4096);
+        assert!(small.confidence > 0.9);
+        assert_eq!(large.value, 16384);
+    }
+
+    #[test]
+    fn page_size_needs_spikes() {
+        // prepare
+        let flat = (0..1000).map(|i| (i as f64 * 4.0, 5.0)).collect::<Vec<_>>();
+
+        // execute & assert
+        assert_eq!(estimate_page_size(&flat), None);
+        assert_eq!(estimate_page_size(&[]), None);
+    }
+
+    #[test]
+    fn cache_sizes_are_latency_steps() {
+        // execute
+        let sizes = estimate_cache_sizes(&cache_size_points());
+
+        // assert
+        assert_eq!(sizes.l1.unwrap().value, 32 * 1024);
+        assert_eq!(sizes.l2.unwrap().value, 1024 * 1024);
+        assert_eq!(sizes.l3.unwrap().value, 16 * 1024 * 1024);
+        assert!(sizes.l1.unwrap().confidence > 0.9);
+    }
+
+    #[test]
+    fn flat_latency_has_no_caches() {
+        // prepare
+        let points = (1..100)
+            .map(|i| (i as f64 * 4096.0, 10.0 * jitter(i, 0.05)))
+            .collect::<Vec<_>>();
+
+        // execute
+        let sizes = estimate_cache_sizes(&points);
+
+        // assert
+        assert_eq!(sizes.l1, None);
+        assert_eq!(sizes.l2, None);
+    }
+
+    #[test]
+    fn tlb_entries_are_step_of_latency_per_page() {
+        // prepare
+        let points = (2..126)
+            .step_by(4)
+            .enumerate()
+            .map(|(i, pages)| {
+                let per_page = if pages <= 66 { 20.0 } else { 45.0 };
+                (pages as f64, pages as f64 * per_page * jitter(i, 0.1))
+            })
+            .collect::<Vec<_>>();
+
+        // execute
+        let entries = estimate_tlb_entries(&points).unwrap();
+
+        // assert
+        assert_eq!(entries.value, 66);
+        assert!(entries.confidence > 0.5);
+    }
+
+    #[test]
+    fn associativity_is_last_line_count_before_step() {
+        // prepare
+        let points = (1..32)
+            .map(|lines| {
+                let latency = if lines <= 12 { 4.0 } else { 11.0 };
+                (lines as f64, latency * jitter(lines, 0.05))
+            })
+            .collect::<Vec<_>>();
+
+        // execute
+        let associativity = estimate_l1_associativity(&points).unwrap();
+
+        // assert
+        assert_eq!(associativity.value, 12);
+    }
+
+    #[test]
+    fn features_are_extracted_from_result() {
+        // prepare
+        let to_json = |points: Vec<(f64, f64)>| {
+            json!(points
+                .iter()
+                .map(|(x, y)| json!({"x": x, "y": y}))
+                .collect::<Vec<_>>())
+        };
+        let result = ResultDTO {
+            model: "model".to_string(),
+            user_agent: "agent".to_string(),
+            benchmark_results: vec![
+                to_json(cache_size_points()),
+                json!({"points": [], "boundaries": []}),
+                to_json(page_size_points(4096)),
+            ],
+            times: vec![1.0, 1.0, 1.0],
+            benchmarks: vec![
+                BenchmarkType::CacheSize,
+                BenchmarkType::TlbSize,
+                BenchmarkType::PageSize,
+            ],
+            capabilities: None,
+            seed: None,
+            schema_version: SCHEMA_VERSION.to_string(),
+            frontend_version: "0.1.0".to_string(),
+            parameters: None,
+            model_check: None,
+        };
+
+        // execute
+        let features = extract_features(&result);
+
+        // assert
+        assert_eq!(features.page_size.unwrap().value, 4096);
+        assert_eq!(features.cache_sizes.l1.unwrap().value, 32 * 1024);
+        assert_eq!(features.tlb_entries, None);
+        assert_eq!(features.l1_associativity, None);
+    }
+
+    #[test]
+    fn features_are_extracted_from_recorded_run() {
+        // prepare
+        // adaptive run of the native benchmark on a VM with a 48 KiB L1 and a 2 MiB L2
+        let result: ResultDTO =
+            serde_json::from_str(include_str!("../fixtures/native_run.json")).unwrap();
+
+        // execute
+        let features = extract_features(&result);
+
+        // assert
+        let cache_sizes = features.cache_sizes;
+        // the coarse sweep of this run placed the L1 step after 64 KiB instead of 32 KiB,
+        // so the recorded boundary is an octave above the L1
+        assert_eq!(cache_sizes.l1.unwrap().value, 112 * 1024);
+        assert_eq!(cache_sizes.l2.unwrap().value, 1664 * 1024);
+        assert_eq!(cache_sizes.l3, None);
+        let l2_octaves = (cache_sizes.l2.unwrap().value as f64 / (2048.0 * 1024.0)).log2();
+        assert!(l2_octaves.abs() <= SIZE_TOLERANCE_OCTAVES);
+        // the whole series shows the L1 step, which the boundaries missed
+        let points = parse_points(&result.benchmark_results[0]);
+        assert_eq!(estimate_cache_sizes(&points).l1.unwrap().value, 32 * 1024);
+        assert_eq!(features.tlb_entries.unwrap().value, 10);
+        assert_eq!(features.l1_associativity.unwrap().value, 6);
+    }
+
+    fn features(l1_kib: u64, l2_kib: u64, confidence: f64) -> Features {
+        let estimate = |value| Some(Estimate { value, confidence });
+        Features {
+            page_size: estimate(4096),
+            cache_sizes: CacheSizes {
+                l1: estimate(l1_kib * 1024),
+                l2: estimate(l2_kib * 1024),
+                l3: None,
+            },
+            tlb_entries: None,
+            l1_associativity: None,
+        }
+    }
+
+    const CATALOGUE: &str = "manufacturer;name;rname;cores;threads;base;boost;l1;l2;l3;l1asso;pagesize;loadbuffer;l1dtlb;microarchitecture
+AMD;AMD Ryzen 5 5600X;;6;12;3.7;4.6;32;512;32;8;4096;;64;Zen 3
+AMD;AMD Ryzen 7 5800X;;8;16;3.8;4.7;32;512;32;8;4096;;64;Zen 3
+Intel;Intel(R) Core(TM) i7-8700;;6;12;3.2;4.6;32;256;12;8;4096;;64;Coffee Lake
+Intel;Intel(R) Core(TM) i7-7700;;4;8;3.6;4.2;32;256;8;8;4096;;64;Kaby Lake
+Intel;Intel(R) Core(TM) i7-6700;;4;8;3.4;4.0;32;256;8;8;4096;;64;Skylake";
+
+    #[test]
+    fn confident_estimates_are_checked_against_specification() {
+        // prepare
+        let specs = parse_cpu_specs(CATALOGUE).unwrap();
+
+        // execute
+        let agreeing = check_model(&features(48, 320, 0.9), Some(&specs[2]));
+        let contradicting = check_model(&features(32, 512, 0.9), Some(&specs[2]));
+        let unsure = check_model(&features(32, 512, 0.2), Some(&specs[2]));
+        let unknown = check_model(&features(32, 512, 0.9), None);
+
+        // assert
+        assert_eq!(
+            agreeing.spec_name.as_deref(),
+            Some("Intel(R) Core(TM) i7-8700")
+        );
+        assert!(!agreeing.is_mismatch());
+        assert_eq!(contradicting.mismatches, vec![CpuProperty::L2Size]);
+        assert!(!unsure.is_mismatch());
+        assert_eq!(unknown.spec_name, None);
+        assert!(!unknown.is_mismatch());
+    }
+
+    #[test]
+    fn microarchitecture_is_family_of_agreeing_specifications() {
+        // prepare
+        let specs = parse_cpu_specs(CATALOGUE).unwrap();
+
+        // execute & assert
+        assert_eq!(
+            likely_microarchitecture(&features(32, 512, 0.9), &specs).as_deref(),
+            Some("Zen 3")
+        );
+        assert_eq!(
+            likely_microarchitecture(&features(32, 256, 0.9), &specs).as_deref(),
+            Some("Skylake")
+        );
+        assert_eq!(
+            likely_microarchitecture(&features(32, 512, 0.2), &specs),
+            None
+        );
+        assert_eq!(
+            likely_microarchitecture(&features(64, 2048, 0.9), &specs),
+            None
+        );
+    }
+}
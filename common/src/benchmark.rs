@@ -0,0 +1,92 @@
+use std::fmt::{Display, Formatter};
+
+use serde::{Deserialize, Serialize};
+
+#[derive(Serialize, Deserialize, Copy, Clone, Debug, PartialEq, Eq)]
+pub enum BenchmarkType {
+    PageSize,
+    CacheSize,
+    TlbSize,
+    SinglePerformance,
+    CacheAssociativity,
+}
+
+impl Display for BenchmarkType {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            BenchmarkType::PageSize => write!(f, "Page size"),
+            BenchmarkType::CacheSize => write!(f, "Cache size"),
+            BenchmarkType::TlbSize => write!(f, "TLB size"),
+            BenchmarkType::SinglePerformance => write!(f, "Single core performance"),
+            BenchmarkType::CacheAssociativity => write!(f, "Cache associativity"),
+        }
+    }
+}
+
+impl BenchmarkType {
+    /// All benchmarks, in the order in which they are run and uploaded
+    pub const ALL: [BenchmarkType; 5] = [
+        BenchmarkType::PageSize,
+        BenchmarkType::CacheSize,
+        BenchmarkType::TlbSize,
+        BenchmarkType::CacheAssociativity,
+        BenchmarkType::SinglePerformance,
+    ];
+
+    /// Benchmarks whose working set doesn't fit in the private caches of a core. They disturb
+    /// each other through the shared cache and the memory bus when run in parallel.
+    pub fn uses_shared_cache(&self) -> bool {
+        match self {
+            BenchmarkType::PageSize => true,
+            BenchmarkType::CacheSize => true,
+            BenchmarkType::TlbSize => true,
+            BenchmarkType::SinglePerformance => false,
+            // at most 32 lines mapping to the same set, they stay in the L1 cache of the core
+            BenchmarkType::CacheAssociativity => false,
+        }
+    }
+
+    /// Whether the benchmarks disturb each other's measurements when run at the same time
+    pub fn interferes_with(&self, other: &BenchmarkType) -> bool {
+        self.uses_shared_cache() && other.uses_shared_cache()
+    }
+
+    /// Rough estimate of the duration in seconds, including the start of the clock worker
+    pub fn expected_duration(&self) -> f32 {
+        match self {
+            BenchmarkType::PageSize => 5.0,
+            BenchmarkType::CacheSize => 60.0,
+            BenchmarkType::TlbSize => 5.0,
+            BenchmarkType::SinglePerformance => 5.0,
+            BenchmarkType::CacheAssociativity => 30.0,
+        }
+    }
+
+    /// Benchmarks measuring time with the clock, which needs to be started before them
+    pub fn needs_clock(&self) -> bool {
+        match self {
+            BenchmarkType::PageSize => true,
+            BenchmarkType::CacheSize => true,
+            BenchmarkType::TlbSize => true,
+            BenchmarkType::SinglePerformance => true,
+            BenchmarkType::CacheAssociativity => true,
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, PartialEq, Eq)]
+pub enum CacheSizeMode {
+    /// Measures a fixed list of sizes up to 32 MiB
+    Fixed,
+    /// Measures a coarse sweep up to 256 MiB and refines it around latency steps
+    Adaptive,
+}
+
+/// Configuration of a benchmark run, uploaded with its results so that results
+/// measured with different settings can be told apart
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq)]
+pub struct BenchmarkConfig {
+    pub cache_size_mode: CacheSizeMode,
+    /// Groups of benchmarks run at the same time, in the order of execution
+    pub stages: Vec<Vec<BenchmarkType>>,
+}
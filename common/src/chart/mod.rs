@@ -0,0 +1,432 @@
+use serde_json::Value;
+
+use crate::analysis::{
+    estimate_cache_sizes, estimate_l1_associativity, estimate_page_size, estimate_tlb_entries,
+    recorded_cache_sizes,
+};
+use crate::benchmark::BenchmarkType;
+
+pub mod svg;
+
+pub const WIDTH: f64 = 600.0;
+pub const HEIGHT: f64 = 300.0;
+const MARGIN_LEFT: f64 = 60.0;
+const MARGIN_RIGHT: f64 = 20.0;
+const MARGIN_TOP: f64 = 20.0;
+const MARGIN_BOTTOM: f64 = 40.0;
+const LINEAR_TICKS: usize = 5;
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Scale {
+    Linear,
+    Log,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Unit {
+    Bytes,
+    Count,
+}
+
+/// Point of the chart worth highlighting, e.g. the size of a cache
+#[derive(Clone, Debug, PartialEq)]
+pub struct Knee {
+    pub x: f64,
+    pub label: String,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+struct Axis {
+    min: f64,
+    max: f64,
+    scale: Scale,
+}
+
+impl Axis {
+    fn new(values: impl Iterator<Item = f64>, scale: Scale) -> Axis {
+        let (min, max) = values.fold((f64::INFINITY, f64::NEG_INFINITY), |(min, max), v| {
+            (min.min(v), max.max(v))
+        });
+        if min > max {
+            // no values, the range only has to be valid for the scale
+            return Axis {
+                min: 1.0,
+                max: 2.0,
+                scale,
+            };
+        }
+        let max = if min == max {
+            min + min.abs().max(1.0)
+        } else {
+            max
+        };
+        Axis { min, max, scale }
+    }
+
+    /// Position of the value on the axis, between 0 and 1
+    fn fraction(&self, value: f64) -> f64 {
+        match self.scale {
+            Scale::Linear => (value - self.min) / (self.max - self.min),
+            Scale::Log => (value.log2() - self.min.log2()) / (self.max.log2() - self.min.log2()),
+        }
+    }
+
+    fn ticks(&self) -> Vec<f64> {
+        match self.scale {
+            Scale::Linear => (0..LINEAR_TICKS)
+                .map(|i| self.min + (self.max - self.min) * i as f64 / (LINEAR_TICKS - 1) as f64)
+                .collect(),
+            Scale::Log => {
+                // every second power of two keeps the labels readable
+                let first = self.min.log2().ceil() as i32;
+                let last = self.max.log2().floor() as i32;
+                (first..=last)
+                    .filter(|exponent| (exponent - first) % 2 == 0)
+                    .map(|exponent| 2f64.powi(exponent))
+                    .collect()
+            }
+        }
+    }
+}
+
+/// Line chart of a single benchmark result, laid out in the `WIDTH` x `HEIGHT` view box
+#[derive(Clone, Debug, PartialEq)]
+pub struct Chart {
+    pub title: String,
+    pub x_label: String,
+    pub y_label: String,
+    pub points: Vec<(f64, f64)>,
+    pub knees: Vec<Knee>,
+    x_unit: Unit,
+    x_axis: Axis,
+    y_axis: Axis,
+}
+
+impl Chart {
+    pub fn new(benchmark: BenchmarkType, result: &Value) -> Chart {
+        let (x_label, x_unit, x_scale) = match benchmark {
+            BenchmarkType::PageSize => ("Offset (4 B elements)", Unit::Count, Scale::Linear),
+            BenchmarkType::CacheSize => ("Buffer size", Unit::Bytes, Scale::Log),
+            BenchmarkType::TlbSize => ("Pages", Unit::Count, Scale::Log),
+            BenchmarkType::CacheAssociativity => ("Accessed lines", Unit::Count, Scale::Linear),
+            BenchmarkType::SinglePerformance => ("Iteration", Unit::Count, Scale::Linear),
+        };
+        let y_label = match benchmark {
+            BenchmarkType::SinglePerformance => "Loop iterations",
+            _ => "Clock ticks",
+        };
+
+        let mut points = parse_points(result);
+        if x_scale == Scale::Log {
+            points.retain(|(x, _)| *x > 0.0);
+        }
+        points.sort_by(|a, b| a.0.total_cmp(&b.0));
+
+        // the knees are the estimates of the analysis, so the chart shows what the prediction uses
+        let knees = match benchmark {
+            BenchmarkType::PageSize => estimate_page_size(&points)
+                .map(|estimate| Knee {
+                    // offsets are counted in 4 B elements
+                    x: (estimate.value / 4) as f64,
+                    label: format!("page size ≈ {}", format_bytes(estimate.value)),
+                })
+                .into_iter()
+                .collect(),
+            BenchmarkType::CacheSize => cache_knees(result, &points),
+            BenchmarkType::TlbSize => estimate_tlb_entries(&points)
+                .map(|estimate| Knee {
+                    x: estimate.value as f64,
+                    label: format!("{} entries", estimate.value),
+                })
+                .into_iter()
+                .collect(),
+            BenchmarkType::CacheAssociativity => estimate_l1_associativity(&points)
+                .map(|estimate| Knee {
+                    x: estimate.value as f64,
+                    label: format!("{}-way", estimate.value),
+                })
+                .into_iter()
+                .collect(),
+            BenchmarkType::SinglePerformance => vec![],
+        };
+
+        Chart {
+            title: benchmark.to_string(),
+            x_label: x_label.to_string(),
+            y_label: y_label.to_string(),
+            x_axis: Axis::new(points.iter().map(|(x, _)| *x), x_scale),
+            y_axis: Axis::new(
+                points.iter().map(|(_, y)| *y).chain(std::iter::once(0.0)),
+                Scale::Linear,
+            ),
+            x_unit,
+            points,
+            knees,
+        }
+    }
+
+    pub fn project_x(&self, x: f64) -> f64 {
+        MARGIN_LEFT + self.x_axis.fraction(x) * (WIDTH - MARGIN_LEFT - MARGIN_RIGHT)
+    }
+
+    pub fn project_y(&self, y: f64) -> f64 {
+        HEIGHT - MARGIN_BOTTOM - self.y_axis.fraction(y) * (HEIGHT - MARGIN_TOP - MARGIN_BOTTOM)
+    }
+
+    /// Value of the `d` attribute of the SVG path drawing the series
+    pub fn path(&self) -> String {
+        self.points
+            .iter()
+            .enumerate()
+            .map(|(i, (x, y))| {
+                let command = if i == 0 { 'M' } else { 'L' };
+                format!(
+                    "{}{:.1},{:.1}",
+                    command,
+                    self.project_x(*x),
+                    self.project_y(*y)
+                )
+            })
+            .collect::<Vec<_>>()
+            .join(" ")
+    }
+
+    /// Positions and labels of the ticks on the x axis
+    pub fn x_ticks(&self) -> Vec<(f64, String)> {
+        self.x_axis
+            .ticks()
+            .into_iter()
+            .map(|x| (self.project_x(x), format_value(x, self.x_unit)))
+            .collect()
+    }
+
+    /// Positions and labels of the ticks on the y axis
+    pub fn y_ticks(&self) -> Vec<(f64, String)> {
+        self.y_axis
+            .ticks()
+            .into_iter()
+            .map(|y| (self.project_y(y), format_value(y, Unit::Count)))
+            .collect()
+    }
+
+    pub fn plot_left(&self) -> f64 {
+        MARGIN_LEFT
+    }
+
+    pub fn plot_bottom(&self) -> f64 {
+        HEIGHT - MARGIN_BOTTOM
+    }
+}
+
+/// Reads the `{x, y}` points of the result. The adaptive cache size search
+/// reports them in the `points` field of an object.
+pub fn parse_points(result: &Value) -> Vec<(f64, f64)> {
+    let points = match result {
+        Value::Object(object) => object.get("points").and_then(Value::as_array),
+        value => value.as_array(),
+    };
+
+    points
+        .into_iter()
+        .flatten()
+        .filter_map(|point| Some((point.get("x")?.as_f64()?, point.get("y")?.as_f64()?)))
+        .collect()
+}
+
+/// Reads the `x` of the boundaries reported by the adaptive cache size search,
+/// `None` for the results of the fixed sweep
+pub fn parse_boundaries(result: &Value) -> Option<Vec<f64>> {
+    let boundaries = result.get("boundaries")?.as_array()?;
+
+    Some(
+        boundaries
+            .iter()
+            .filter_map(|boundary| boundary.get("x")?.as_f64())
+            .collect(),
+    )
+}
+
+fn cache_knees(result: &Value, points: &[(f64, f64)]) -> Vec<Knee> {
+    // the adaptive search already reports the boundaries
+    let sizes = match parse_boundaries(result) {
+        Some(boundaries) => recorded_cache_sizes(points, &boundaries),
+        None => estimate_cache_sizes(points),
+    };
+
+    [sizes.l1, sizes.l2, sizes.l3]
+        .into_iter()
+        .flatten()
+        .map(|estimate| Knee {
+            x: estimate.value as f64,
+            label: format!("≈ {}", format_bytes(estimate.value)),
+        })
+        .collect()
+}
+
+fn format_value(value: f64, unit: Unit) -> String {
+    match unit {
+        Unit::Bytes => format_bytes(value.round() as u64),
+        Unit::Count if value.abs() >= 1_000_000.0 => format!("{:.1}M", value / 1_000_000.0),
+        Unit::Count if value.abs() >= 10_000.0 => format!("{:.0}k", value / 1000.0),
+        Unit::Count => format!("{:.0}", value),
+    }
+}
+
+/// Formats the size with the largest binary unit keeping the value at least 1
+pub fn format_bytes(bytes: u64) -> String {
+    const UNITS: [&str; 4] = ["B", "KiB", "MiB", "GiB"];
+
+    let mut value = bytes as f64;
+    let mut unit = 0;
+    while value >= 1024.0 && unit < UNITS.len() - 1 {
+        value /= 1024.0;
+        unit += 1;
+    }
+
+    if value.fract() == 0.0 {
+        format!("{} {}", value, UNITS[unit])
+    } else {
+        format!("{:.1} {}", value, UNITS[unit])
+    }
+}
+
+/*
+ * TESTS
+ */
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+
+    use crate::analysis::extract_features;
+    use crate::benchmark::BenchmarkType;
+    use crate::chart::{format_bytes, parse_boundaries, parse_points, Chart};
+    use crate::dto::result::ResultDTO;
+
+    #[test]
+    fn points_are_parsed_from_both_result_shapes() {
+        // prepare
+        let fixed = json!([{"x": 1024, "y": 5}, {"x": 2048, "y": 6}]);
+        let adaptive = json!({"points": [{"x": 1024, "y": 5}], "boundaries": []});
+
+        // execute & assert
+        assert_eq!(parse_points(&fixed), vec![(1024.0, 5.0), (2048.0, 6.0)]);
+        assert_eq!(parse_points(&adaptive), vec![(1024.0, 5.0)]);
+        assert!(parse_points(&json!("invalid")).is_empty());
+        assert_eq!(parse_boundaries(&fixed), None);
+        assert_eq!(parse_boundaries(&adaptive), Some(vec![]));
+    }
+
+    #[test]
+    fn page_size_is_distance_between_spikes() {
+        // prepare
+        let points = (0..5000)
+            .map(|i| {
+                let x = 512 + i * 4;
+                let y = if x % 1024 == 0 { 30.0 } else { 2.0 };
+                (x as f64, y)
+            })
+            .collect::<Vec<_>>();
+
+        let result = json!(points
+            .iter()
+            .map(|(x, y)| json!({"x": x, "y": y}))
+            .collect::<Vec<_>>());
+
+        // execute
+        let chart = Chart::new(BenchmarkType::PageSize, &result);
+
+        // assert
+        assert_eq!(chart.knees.len(), 1);
+        assert_eq!(chart.knees[0].x, 1024.0);
+        assert_eq!(chart.knees[0].label, "page size ≈ 4 KiB");
+    }
+
+    #[test]
+    fn adaptive_cache_boundaries_are_used_as_knees() {
+        // prepare
+        let result = json!({
+            "points": [{"x": 1024, "y": 5}, {"x": 65536, "y": 20}],
+            "boundaries": [{"x": 32768, "y_before": 5, "y_after": 20}]
+        });
+
+        // execute
+        let chart = Chart::new(BenchmarkType::CacheSize, &result);
+
+        // assert
+        assert_eq!(chart.knees.len(), 1);
+        assert_eq!(chart.knees[0].x, 32768.0);
+        assert_eq!(chart.knees[0].label, "≈ 32 KiB");
+    }
+
+    #[test]
+    fn knees_are_estimates_of_analysis() {
+        // prepare
+        let result: ResultDTO =
+            serde_json::from_str(include_str!("../../fixtures/native_run.json")).unwrap();
+        let features = extract_features(&result);
+
+        // execute
+        let knees = result
+            .benchmark_types()
+            .into_iter()
+            .zip(result.benchmark_results.iter())
+            .map(|(benchmark, data)| {
+                Chart::new(benchmark, data)
+                    .knees
+                    .iter()
+                    .map(|knee| knee.x)
+                    .collect::<Vec<_>>()
+            })
+            .collect::<Vec<_>>();
+
+        // assert
+        let cache_sizes = features.cache_sizes;
+        assert_eq!(
+            knees,
+            vec![
+                vec![
+                    cache_sizes.l1.unwrap().value as f64,
+                    cache_sizes.l2.unwrap().value as f64
+                ],
+                vec![features.tlb_entries.unwrap().value as f64],
+                vec![features.l1_associativity.unwrap().value as f64],
+            ]
+        );
+    }
+
+    #[test]
+    fn empty_result_has_valid_axes() {
+        // execute
+        let chart = Chart::new(BenchmarkType::CacheSize, &json!([]));
+
+        // assert
+        assert!(chart.points.is_empty());
+        assert!(chart.path().is_empty());
+        assert_eq!(chart.x_ticks().len(), 1);
+        assert!(chart.y_ticks().iter().all(|(y, _)| y.is_finite()));
+    }
+
+    #[test]
+    fn log_scale_spreads_doublings_evenly() {
+        // prepare
+        let result = json!([{"x": 1024, "y": 1}, {"x": 2048, "y": 2}, {"x": 4096, "y": 3}]);
+
+        // execute
+        let chart = Chart::new(BenchmarkType::CacheSize, &result);
+
+        // assert
+        let xs = [1024.0, 2048.0, 4096.0].map(|x| chart.project_x(x));
+        assert!((xs[1] - xs[0] - (xs[2] - xs[1])).abs() < 1e-9);
+        assert_eq!(chart.project_y(0.0), chart.plot_bottom());
+        assert!(chart.path().starts_with("M60.0,"));
+    }
+
+    #[test]
+    fn bytes_are_formatted_with_binary_units() {
+        // execute & assert
+        assert_eq!(format_bytes(512), "512 B");
+        assert_eq!(format_bytes(32 * 1024), "32 KiB");
+        assert_eq!(format_bytes(1536 * 1024), "1.5 MiB");
+    }
+}
@@ -0,0 +1,132 @@
+use std::fmt::Write;
+
+use crate::chart::{Chart, HEIGHT, WIDTH};
+
+/// Renders the chart as a standalone SVG document
+pub fn render_svg(chart: &Chart) -> String {
+    let left = chart.plot_left();
+    let bottom = chart.plot_bottom();
+    let mut svg = String::new();
+
+    // writing into a String cannot fail
+    let _ = write!(
+        svg,
+        r#"<svg xmlns="http://www.w3.org/2000/svg" viewBox="0 0 {WIDTH} {HEIGHT}" font-size="11" font-family="sans-serif">"#
+    );
+    let _ = write!(
+        svg,
+        r#"<rect width="{WIDTH}" height="{HEIGHT}" fill="white"/>"#
+    );
+    let _ = write!(
+        svg,
+        r#"<text x="{left}" y="12" font-weight="bold">{}</text>"#,
+        escape(&chart.title)
+    );
+    let _ = write!(
+        svg,
+        r#"<line x1="{left}" y1="{bottom}" x2="{WIDTH}" y2="{bottom}" stroke="black"/>"#
+    );
+    let _ = write!(
+        svg,
+        r#"<line x1="{left}" y1="0" x2="{left}" y2="{bottom}" stroke="black"/>"#
+    );
+
+    for (x, label) in chart.x_ticks() {
+        let _ = write!(
+            svg,
+            r#"<text x="{x:.1}" y="{:.1}" text-anchor="middle">{}</text>"#,
+            bottom + 15.0,
+            escape(&label)
+        );
+    }
+    for (y, label) in chart.y_ticks() {
+        let _ = write!(
+            svg,
+            r#"<text x="{:.1}" y="{:.1}" text-anchor="end">{}</text>"#,
+            left - 5.0,
+            y + 4.0,
+            escape(&label)
+        );
+    }
+
+    let _ = write!(
+        svg,
+        r#"<text x="{:.1}" y="{:.1}" text-anchor="middle">{}</text>"#,
+        (WIDTH + left) / 2.0,
+        HEIGHT - 5.0,
+        escape(&chart.x_label)
+    );
+    let _ = write!(
+        svg,
+        r#"<text x="12" y="{0:.1}" text-anchor="middle" transform="rotate(-90 12 {0:.1})">{1}</text>"#,
+        bottom / 2.0,
+        escape(&chart.y_label)
+    );
+    let _ = write!(
+        svg,
+        r#"<path d="{}" fill="none" stroke="steelblue" stroke-width="1.5"/>"#,
+        chart.path()
+    );
+
+    for knee in chart.knees.iter() {
+        let x = chart.project_x(knee.x);
+        let _ = write!(
+            svg,
+            r#"<line x1="{x:.1}" y1="10" x2="{x:.1}" y2="{bottom}" stroke="crimson" stroke-dasharray="4 3"/>"#
+        );
+        let _ = write!(
+            svg,
+            r#"<text x="{:.1}" y="24" fill="crimson">{}</text>"#,
+            x + 4.0,
+            escape(&knee.label)
+        );
+    }
+
+    svg.push_str("</svg>");
+    svg
+}
+
+fn escape(text: &str) -> String {
+    text.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+}
+
+/*
+ * TESTS
+ */
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+
+    use crate::benchmark::BenchmarkType;
+    use crate::chart::svg::{escape, render_svg};
+    use crate::chart::Chart;
+
+    #[test]
+    fn svg_contains_series_and_knees() {
+        // prepare
+        let result = json!({
+            "points": [{"x": 1024, "y": 5}, {"x": 65536, "y": 20}],
+            "boundaries": [{"x": 32768, "y_before": 5, "y_after": 20}]
+        });
+        let chart = Chart::new(BenchmarkType::CacheSize, &result);
+
+        // execute
+        let svg = render_svg(&chart);
+
+        // assert
+        assert!(svg.starts_with("<svg xmlns=\"http://www.w3.org/2000/svg\""));
+        assert!(svg.ends_with("</svg>"));
+        assert!(svg.contains(&format!("d=\"{}\"", chart.path())));
+        assert!(svg.contains(">≈ 32 KiB</text>"));
+        assert!(svg.contains(">Cache size</text>"));
+    }
+
+    #[test]
+    fn text_is_escaped() {
+        // execute & assert
+        assert_eq!(escape("a < b & c"), "a &lt; b &amp; c");
+    }
+}
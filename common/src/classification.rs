@@ -0,0 +1,528 @@
+use std::collections::HashMap;
+use std::ops::Range;
+
+use serde::{Deserialize, Serialize};
+
+use crate::analysis::estimate_page_size;
+use crate::benchmark::BenchmarkType;
+use crate::chart::parse_points;
+use crate::cpu::find_cpu_spec;
+use crate::dto::classification::{CandidateDTO, ClassificationDTO};
+use crate::dto::cpu::CpuSpecDTO;
+use crate::dto::result::ResultDTO;
+
+/// Number of the classified neighbours, as in `classification/main.py`
+pub const DEFAULT_NEIGHBOURS: usize = 5;
+/// Sizes of the cache size profile, from 4 KiB to 32 MiB in half-octave steps
+const CACHE_SIZE_PROFILE: usize = 27;
+/// Page counts of the TLB profile, from 2 to 122 in steps of 8
+const TLB_PROFILE: usize = 16;
+/// Line counts of the associativity profile, from 1 to 31 in steps of 2
+const ASSOCIATIVITY_PROFILE: usize = 16;
+/// Length of the feature vector: the three latency profiles and the page size
+pub const FEATURE_COUNT: usize = CACHE_SIZE_PROFILE + TLB_PROFILE + ASSOCIATIVITY_PROFILE + 1;
+/// Positions of the profiles in the feature vector
+pub const CACHE_SIZE_FEATURES: Range<usize> = 0..CACHE_SIZE_PROFILE;
+pub const TLB_FEATURES: Range<usize> =
+    CACHE_SIZE_FEATURES.end..CACHE_SIZE_FEATURES.end + TLB_PROFILE;
+pub const ASSOCIATIVITY_FEATURES: Range<usize> =
+    TLB_FEATURES.end..TLB_FEATURES.end + ASSOCIATIVITY_PROFILE;
+pub const PAGE_SIZE_FEATURE: usize = ASSOCIATIVITY_FEATURES.end;
+/// Microarchitectures merged into the one they are derived from,
+/// as by `replace_uarch_by_base` in `classification/utils/uarch.py`
+const MICROARCHITECTURE_BASES: [(&str, &str); 8] = [
+    ("Kaby Lake", "Skylake"),
+    ("Coffee Lake", "Skylake"),
+    ("Comet Lake", "Skylake"),
+    ("Whiskey Lake", "Skylake"),
+    ("Broadwell", "Haswell"),
+    ("Ivy Bridge", "Sandy Bridge"),
+    ("Ice Lake", "Sunny Cove"),
+    ("Tiger Lake", "Willow Cove"),
+];
+/// Keeps the weight of a neighbour with the same features finite
+const DISTANCE_EPSILON: f64 = 1e-6;
+
+/// Fixed-length feature vector of the result. The latencies are resampled at fixed sizes,
+/// so results of the fixed and the adaptive cache size search are comparable. They are
+/// relative to the fastest access, as the clock ticks differ between the browsers.
+/// Features of missing benchmarks and sizes outside the measured range are NaN.
+pub fn feature_vector(result: &ResultDTO) -> Vec<f64> {
+    let mut cache_size = vec![f64::NAN; CACHE_SIZE_PROFILE];
+    let mut tlb = vec![f64::NAN; TLB_PROFILE];
+    let mut associativity = vec![f64::NAN; ASSOCIATIVITY_PROFILE];
+    let mut page_size = f64::NAN;
+
+    for (benchmark, data) in result
+        .benchmark_types()
+        .into_iter()
+        .zip(result.benchmark_results.iter())
+    {
+        let points = parse_points(data);
+        match benchmark {
+            BenchmarkType::CacheSize => {
+                let grid = (0..CACHE_SIZE_PROFILE)
+                    .map(|i| 12.0 + i as f64 / 2.0)
+                    .collect::<Vec<_>>();
+                let points = points
+                    .iter()
+                    .filter(|(x, _)| *x > 0.0)
+                    .map(|(x, y)| (x.log2(), *y))
+                    .collect::<Vec<_>>();
+                cache_size = latency_profile(&points, &grid);
+            }
+            BenchmarkType::TlbSize => {
+                let grid = (0..TLB_PROFILE)
+                    .map(|i| 2.0 + 8.0 * i as f64)
+                    .collect::<Vec<_>>();
+                // the benchmark reports the time of walking all the pages
+                let per_page = points
+                    .iter()
+                    .filter(|(x, _)| *x > 0.0)
+                    .map(|(x, y)| (*x, y / x))
+                    .collect::<Vec<_>>();
+                tlb = latency_profile(&per_page, &grid);
+            }
+            BenchmarkType::CacheAssociativity => {
+                let grid = (0..ASSOCIATIVITY_PROFILE)
+                    .map(|i| 1.0 + 2.0 * i as f64)
+                    .collect::<Vec<_>>();
+                associativity = latency_profile(&points, &grid);
+            }
+            BenchmarkType::PageSize => {
+                page_size = estimate_page_size(&points)
+                    .map(|estimate| (estimate.value as f64).log2())
+                    .unwrap_or(f64::NAN);
+            }
+            BenchmarkType::SinglePerformance => {}
+        }
+    }
+
+    cache_size
+        .into_iter()
+        .chain(tlb)
+        .chain(associativity)
+        .chain(std::iter::once(page_size))
+        .collect()
+}
+
+/// Log latency interpolated at the grid, relative to the fastest grid value.
+/// Single outliers are removed by the median of every three neighbouring measurements.
+fn latency_profile(points: &[(f64, f64)], grid: &[f64]) -> Vec<f64> {
+    let mut points = points
+        .iter()
+        .filter(|(x, y)| x.is_finite() && y.is_finite() && *y > 0.0)
+        .map(|(x, y)| (*x, y.ln()))
+        .collect::<Vec<_>>();
+    points.sort_by(|a, b| a.0.total_cmp(&b.0));
+    let smoothed = points
+        .iter()
+        .enumerate()
+        .map(|(i, (x, y))| {
+            // the first and the last measurements have a single neighbour
+            if i == 0 || i + 1 == points.len() {
+                return (*x, *y);
+            }
+            let mut ys = [points[i - 1].1, *y, points[i + 1].1];
+            ys.sort_by(f64::total_cmp);
+            (*x, ys[1])
+        })
+        .collect::<Vec<_>>();
+
+    let profile = grid
+        .iter()
+        .map(|x| interpolate(&smoothed, *x))
+        .collect::<Vec<_>>();
+    let fastest = profile
+        .iter()
+        .copied()
+        .filter(|y| !y.is_nan())
+        .fold(f64::INFINITY, f64::min);
+    profile.into_iter().map(|y| y - fastest).collect()
+}
+
+/// Linear interpolation of the sorted points, NaN outside their range
+fn interpolate(points: &[(f64, f64)], x: f64) -> f64 {
+    let after = points.partition_point(|(px, _)| *px < x);
+    match (after.checked_sub(1).map(|i| points[i]), points.get(after)) {
+        (_, Some((px, py))) if *px == x => *py,
+        (Some((x0, y0)), Some((x1, y1))) => y0 + (y1 - y0) * (x - x0) / (x1 - x0),
+        _ => f64::NAN,
+    }
+}
+
+/// Standardisation of the features to zero mean and unit variance, ignoring missing values
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+pub struct StandardScaler {
+    mean: Vec<f64>,
+    scale: Vec<f64>,
+}
+
+impl StandardScaler {
+    pub fn fit(samples: &[Vec<f64>]) -> StandardScaler {
+        let count = samples.first().map_or(0, Vec::len);
+        let (mean, scale) = (0..count)
+            .map(|i| {
+                let values = samples
+                    .iter()
+                    .map(|sample| sample[i])
+                    .filter(|value| !value.is_nan())
+                    .collect::<Vec<_>>();
+                if values.is_empty() {
+                    return (0.0, 1.0);
+                }
+                let mean = values.iter().sum::<f64>() / values.len() as f64;
+                let variance = values
+                    .iter()
+                    .map(|value| (value - mean).powi(2))
+                    .sum::<f64>()
+                    / values.len() as f64;
+                // constant features are only centred
+                let scale = if variance > 0.0 { variance.sqrt() } else { 1.0 };
+                (mean, scale)
+            })
+            .unzip();
+
+        StandardScaler { mean, scale }
+    }
+
+    /// Standardised features, the missing ones are replaced by the mean
+    pub fn transform(&self, features: &[f64]) -> Vec<f64> {
+        features
+            .iter()
+            .zip(self.mean.iter().zip(self.scale.iter()))
+            .map(|(value, (mean, scale))| {
+                if value.is_nan() {
+                    0.0
+                } else {
+                    (value - mean) / scale
+                }
+            })
+            .collect()
+    }
+}
+
+/// Properties of the CPU learned by the classifier
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+pub struct CpuLabels {
+    pub model: String,
+    pub microarchitecture: String,
+    pub l1_kib: u32,
+    pub l2_kib: u32,
+    pub l3_mib: Option<u32>,
+}
+
+impl From<&CpuSpecDTO> for CpuLabels {
+    fn from(spec: &CpuSpecDTO) -> Self {
+        CpuLabels {
+            model: spec.name.clone(),
+            microarchitecture: spec.microarchitecture.clone(),
+            l1_kib: spec.l1_kib,
+            l2_kib: spec.l2_kib,
+            l3_mib: spec.l3_mib,
+        }
+    }
+}
+
+/// Labels of the normalized models matching the specifications by name or by alias.
+/// A name is preferred when another specification has the same alias, as by `find_cpu_spec`.
+pub fn catalogue_labels(specs: &[CpuSpecDTO]) -> HashMap<String, CpuLabels> {
+    specs
+        .iter()
+        .flat_map(|spec| std::iter::once(&spec.name).chain(spec.alias.iter()))
+        .filter_map(|model| {
+            find_cpu_spec(specs, model).map(|spec| (model.clone(), CpuLabels::from(spec)))
+        })
+        .collect()
+}
+
+/// Family of the microarchitecture, which the derived ones are hardly distinguishable from
+pub fn base_microarchitecture(microarchitecture: &str) -> &str {
+    MICROARCHITECTURE_BASES
+        .iter()
+        .find(|(derived, _)| *derived == microarchitecture)
+        .map_or(microarchitecture, |(_, base)| base)
+}
+
+/// Result of a CPU with a known specification
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+pub struct TrainingSample {
+    pub features: Vec<f64>,
+    pub labels: CpuLabels,
+}
+
+/// k-nearest-neighbours classifier over the standardised feature vectors.
+/// The neighbours vote with the inverse of their distance.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+pub struct KnnClassifier {
+    neighbours: usize,
+    scaler: StandardScaler,
+    /// Training samples with standardised features
+    samples: Vec<TrainingSample>,
+}
+
+impl KnnClassifier {
+    pub fn train(samples: Vec<TrainingSample>, neighbours: usize) -> KnnClassifier {
+        let scaler = StandardScaler::fit(
+            &samples
+                .iter()
+                .map(|sample| sample.features.clone())
+                .collect::<Vec<_>>(),
+        );
+        let samples = samples
+            .into_iter()
+            .map(|sample| TrainingSample {
+                features: scaler.transform(&sample.features),
+                labels: sample.labels,
+            })
+            .collect();
+
+        KnnClassifier {
+            neighbours,
+            scaler,
+            samples,
+        }
+    }
+
+    pub fn len(&self) -> usize {
+        self.samples.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.samples.is_empty()
+    }
+
+    /// Length of the feature vectors the classifier was trained on
+    pub fn feature_count(&self) -> usize {
+        self.scaler.mean.len()
+    }
+
+    /// Candidates of every property ranked by the votes of the nearest samples
+    pub fn classify(&self, features: &[f64]) -> ClassificationDTO {
+        let features = self.scaler.transform(features);
+        let mut nearest = self
+            .samples
+            .iter()
+            .map(|sample| (distance(&features, &sample.features), &sample.labels))
+            .collect::<Vec<_>>();
+        nearest.sort_by(|a, b| a.0.total_cmp(&b.0));
+        let votes = nearest
+            .into_iter()
+            .take(self.neighbours)
+            .map(|(distance, labels)| (1.0 / (distance + DISTANCE_EPSILON), labels))
+            .collect::<Vec<_>>();
+
+        ClassificationDTO {
+            models: rank(votes.iter().map(|(w, labels)| (labels.model.clone(), *w))),
+            microarchitectures: rank(
+                votes
+                    .iter()
+                    .map(|(w, labels)| (labels.microarchitecture.clone(), *w)),
+            ),
+            l1_kib: rank(votes.iter().map(|(w, labels)| (labels.l1_kib, *w))),
+            l2_kib: rank(votes.iter().map(|(w, labels)| (labels.l2_kib, *w))),
+            l3_mib: rank(
+                votes
+                    .iter()
+                    .filter_map(|(w, labels)| Some((labels.l3_mib?, *w))),
+            ),
+            training_size: self.samples.len(),
+        }
+    }
+}
+
+fn distance(a: &[f64], b: &[f64]) -> f64 {
+    a.iter()
+        .zip(b.iter())
+        .map(|(a, b)| (a - b).powi(2))
+        .sum::<f64>()
+        .sqrt()
+}
+
+/// Sums the weights of the same values, normalised to the share of all the weights
+fn rank<T: PartialEq>(votes: impl Iterator<Item = (T, f64)>) -> Vec<CandidateDTO<T>> {
+    let mut candidates: Vec<CandidateDTO<T>> = vec![];
+    for (value, weight) in votes {
+        match candidates
+            .iter_mut()
+            .find(|candidate| candidate.value == value)
+        {
+            Some(candidate) => candidate.score += weight,
+            None => candidates.push(CandidateDTO {
+                value,
+                score: weight,
+            }),
+        }
+    }
+
+    let total = candidates
+        .iter()
+        .map(|candidate| candidate.score)
+        .sum::<f64>();
+    for candidate in candidates.iter_mut() {
+        candidate.score /= total;
+    }
+    candidates.sort_by(|a, b| b.score.total_cmp(&a.score));
+    candidates
+}
+
+/*
+ * TESTS
+ */
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+
+    use crate::benchmark::BenchmarkType;
+    use crate::classification::{
+        base_microarchitecture, catalogue_labels, feature_vector, CpuLabels, KnnClassifier,
+        StandardScaler, TrainingSample, ASSOCIATIVITY_FEATURES, FEATURE_COUNT, PAGE_SIZE_FEATURE,
+    };
+    use crate::cpu::parse_cpu_specs;
+    use crate::dto::result::{ResultDTO, SCHEMA_VERSION};
+
+    fn result(
+        benchmarks: Vec<BenchmarkType>,
+        benchmark_results: Vec<serde_json::Value>,
+    ) -> ResultDTO {
+        ResultDTO {
+            model: "model".to_string(),
+            user_agent: "agent".to_string(),
+            times: benchmark_results.iter().map(|_| 1.0).collect(),
+            benchmark_results,
+            benchmarks,
+            capabilities: None,
+            seed: None,
+            schema_version: SCHEMA_VERSION.to_string(),
+            frontend_version: "0.1.0".to_string(),
+            parameters: None,
+            model_check: None,
+        }
+    }
+
+    fn labels(model: &str, l1_kib: u32, l3_mib: Option<u32>) -> CpuLabels {
+        CpuLabels {
+            model: model.to_string(),
+            microarchitecture: format!("{} uarch", model),
+            l1_kib,
+            l2_kib: 512,
+            l3_mib,
+        }
+    }
+
+    #[test]
+    fn features_are_relative_log_latencies() {
+        // prepare
+        let associativity = (1..32)
+            .map(|lines| json!({"x": lines, "y": if lines <= 8 { 10 } else { 40 }}))
+            .collect::<Vec<_>>();
+        let result = result(
+            vec![BenchmarkType::CacheAssociativity],
+            vec![json!(associativity)],
+        );
+
+        // execute
+        let features = feature_vector(&result);
+
+        // assert
+        assert_eq!(features.len(), FEATURE_COUNT);
+        // the cache size and TLB profiles and the page size are missing
+        assert_eq!(
+            features.iter().filter(|value| value.is_nan()).count(),
+            27 + 16 + 1
+        );
+        let profile = &features[ASSOCIATIVITY_FEATURES];
+        assert_eq!(profile[0], 0.0);
+        assert_eq!(profile[3], 0.0);
+        assert!((profile[15] - 4f64.ln()).abs() < 1e-9);
+    }
+
+    #[test]
+    fn profile_is_interpolated_between_sizes() {
+        // prepare
+        let cache_size = json!([{"x": 4096, "y": 10}, {"x": 8192, "y": 40}]);
+        let result = result(vec![BenchmarkType::CacheSize], vec![cache_size]);
+
+        // execute
+        let features = feature_vector(&result);
+
+        // assert
+        assert_eq!(features[0], 0.0);
+        assert!((features[1] - 2f64.ln()).abs() < 1e-9);
+        assert!((features[2] - 4f64.ln()).abs() < 1e-9);
+        assert!(features[3].is_nan());
+    }
+
+    #[test]
+    fn scaler_standardises_and_imputes_mean() {
+        // prepare
+        let scaler = StandardScaler::fit(&[vec![1.0, 5.0, f64::NAN], vec![3.0, 5.0, 2.0]]);
+
+        // execute & assert
+        assert_eq!(scaler.transform(&[3.0, 6.0, 4.0]), vec![1.0, 1.0, 2.0]);
+        assert_eq!(scaler.transform(&[f64::NAN, 5.0, 2.0]), vec![0.0, 0.0, 0.0]);
+    }
+
+    #[test]
+    fn nearest_samples_win_the_vote() {
+        // prepare
+        let sample = |features: Vec<f64>, labels: CpuLabels| TrainingSample { features, labels };
+        let classifier = KnnClassifier::train(
+            vec![
+                sample(vec![0.0, 0.0], labels("first", 32, Some(8))),
+                sample(vec![0.1, 0.0], labels("first", 32, Some(8))),
+                sample(vec![1.0, 1.0], labels("second", 48, None)),
+                sample(vec![5.0, 5.0], labels("third", 64, None)),
+            ],
+            3,
+        );
+
+        // execute
+        let classification = classifier.classify(&[0.05, 0.0]);
+
+        // assert
+        assert_eq!(classification.training_size, 4);
+        let models = classification
+            .models
+            .iter()
+            .map(|candidate| candidate.value.as_str())
+            .collect::<Vec<_>>();
+        assert_eq!(models, vec!["first", "second"]);
+        assert!(classification.models[0].score > 0.9);
+        let total = classification.l1_kib.iter().map(|c| c.score).sum::<f64>();
+        assert!((total - 1.0).abs() < 1e-9);
+        assert_eq!(classification.l1_kib[0].value, 32);
+        assert_eq!(classification.l3_mib.len(), 1);
+        assert_eq!(classification.l3_mib[0].score, 1.0);
+    }
+
+    #[test]
+    fn catalogue_names_are_preferred_over_aliases() {
+        // prepare
+        let specs = parse_cpu_specs(
+            "manufacturer;name;rname;cores;threads;base;boost;l1;l2;l3;l1asso;pagesize;loadbuffer;l1dtlb;microarchitecture
+Intel;Core i5;Coffee Lake;6;6;2.8;4.0;32;256;9;8;4096;;64;Coffee Lake
+Intel;Coffee Lake;;4;4;2.8;4.0;32;256;6;8;4096;;64;Coffee Lake
+Intel;Core i7;;6;12;3.2;4.6;32;256;12;8;4096;;64;Kaby Lake",
+        )
+        .unwrap();
+
+        // execute
+        let labels = catalogue_labels(&specs);
+
+        // assert
+        assert_eq!(labels.len(), 3);
+        assert_eq!(labels["Coffee Lake"].l3_mib, Some(6));
+        assert_eq!(labels["Core i5"].l3_mib, Some(9));
+    }
+
+    #[test]
+    fn derived_microarchitectures_are_merged() {
+        // execute & assert
+        assert_eq!(base_microarchitecture("Comet Lake"), "Skylake");
+        assert_eq!(base_microarchitecture("Ivy Bridge"), "Sandy Bridge");
+        assert_eq!(base_microarchitecture("Zen 2"), "Zen 2");
+        assert_eq!(PAGE_SIZE_FEATURE, FEATURE_COUNT - 1);
+    }
+}
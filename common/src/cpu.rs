@@ -0,0 +1,287 @@
+use std::str::FromStr;
+
+use crate::dto::cpu::CpuSpecDTO;
+
+/// Columns of the CPU catalogue, in the order of `classification/cpus.csv`
+pub const CPU_SPEC_COLUMNS: [&str; 15] = [
+    "manufacturer",
+    "name",
+    "rname",
+    "cores",
+    "threads",
+    "base",
+    "boost",
+    "l1",
+    "l2",
+    "l3",
+    "l1asso",
+    "pagesize",
+    "loadbuffer",
+    "l1dtlb",
+    "microarchitecture",
+];
+
+/// Row of the catalogue, with its values addressed by the column names
+struct Row<'a> {
+    line: usize,
+    header: &'a [&'a str],
+    values: Vec<&'a str>,
+}
+
+impl<'a> Row<'a> {
+    fn optional<T: FromStr>(&self, column: &str) -> Result<Option<T>, String> {
+        // the columns are checked before the rows are parsed
+        let index = self.header.iter().position(|name| *name == column).unwrap();
+        let value = self.values[index].trim();
+        if value.is_empty() {
+            return Ok(None);
+        }
+
+        value.parse().map(Some).map_err(|_| {
+            format!(
+                "line {}: invalid value '{}' of column '{}'",
+                self.line, value, column
+            )
+        })
+    }
+
+    fn required<T: FromStr>(&self, column: &str) -> Result<T, String> {
+        self.optional(column)?
+            .ok_or_else(|| format!("line {}: missing value of column '{}'", self.line, column))
+    }
+}
+
+/// Parses the semicolon-separated CPU catalogue. The columns may be in any order,
+/// but all of `CPU_SPEC_COLUMNS` have to be present.
+pub fn parse_cpu_specs(csv: &str) -> Result<Vec<CpuSpecDTO>, String> {
+    let mut lines = csv
+        .lines()
+        .enumerate()
+        .map(|(i, line)| (i + 1, line.trim_end_matches('\r')))
+        .filter(|(_, line)| !line.trim().is_empty());
+
+    let header = lines
+        .next()
+        .map(|(_, line)| line.split(';').map(str::trim).collect::<Vec<_>>())
+        .ok_or("the catalogue is empty")?;
+    if let Some(missing) = CPU_SPEC_COLUMNS
+        .iter()
+        .find(|column| !header.contains(column))
+    {
+        return Err(format!("missing column '{}'", missing));
+    }
+
+    lines
+        .map(|(line, values)| {
+            let values = values.split(';').collect::<Vec<_>>();
+            if values.len() != header.len() {
+                return Err(format!(
+                    "line {}: expected {} values, found {}",
+                    line,
+                    header.len(),
+                    values.len()
+                ));
+            }
+
+            let row = Row {
+                line,
+                header: &header,
+                values,
+            };
+            Ok(CpuSpecDTO {
+                manufacturer: row.required("manufacturer")?,
+                name: row.required("name")?,
+                alias: row.optional("rname")?,
+                cores: row.required("cores")?,
+                threads: row.required("threads")?,
+                base_ghz: row.required("base")?,
+                boost_ghz: row.required("boost")?,
+                l1_kib: row.required("l1")?,
+                l2_kib: row.required("l2")?,
+                l3_mib: row.optional("l3")?,
+                l1_associativity: row.optional("l1asso")?,
+                page_size: row.required("pagesize")?,
+                load_buffer: row.optional("loadbuffer")?,
+                l1_dtlb: row.optional("l1dtlb")?,
+                microarchitecture: row.required("microarchitecture")?,
+            })
+        })
+        .collect()
+}
+
+/// Drops the repeated rows of the catalogue and the aliases of several specifications,
+/// which do not tell the CPUs apart. Rows repeating a name with different values are kept.
+pub fn dedupe_cpu_specs(specs: Vec<CpuSpecDTO>) -> Vec<CpuSpecDTO> {
+    let mut deduped: Vec<CpuSpecDTO> = vec![];
+    for spec in specs {
+        if !deduped.contains(&spec) {
+            deduped.push(spec);
+        }
+    }
+
+    let shared = deduped
+        .iter()
+        .filter_map(|spec| spec.alias.clone())
+        .filter(|alias| {
+            deduped
+                .iter()
+                .filter(|spec| spec.alias.as_ref() == Some(alias))
+                .count()
+                > 1
+        })
+        .collect::<Vec<_>>();
+    for spec in deduped.iter_mut() {
+        if spec
+            .alias
+            .as_ref()
+            .map_or(false, |alias| shared.contains(alias))
+        {
+            spec.alias = None;
+        }
+    }
+
+    deduped
+}
+
+/// Specification matching the normalized model by name or, unless another one has it as its name, by alias.
+/// The only implementation of the rule, used by the frontend, the backend and the classifiers.
+pub fn find_cpu_spec<'a, T: AsRef<CpuSpecDTO>>(
+    specs: &'a [T],
+    model_normalized: &str,
+) -> Option<&'a T> {
+    specs
+        .iter()
+        .find(|spec| spec.as_ref().name == model_normalized)
+        .or_else(|| {
+            specs
+                .iter()
+                .find(|spec| spec.as_ref().alias.as_deref() == Some(model_normalized))
+        })
+}
+
+/*
+ * TESTS
+ */
+
+#[cfg(test)]
+mod tests {
+    use crate::cpu::{dedupe_cpu_specs, find_cpu_spec, parse_cpu_specs};
+    use crate::dto::cpu::CpuSpecDTO;
+
+    const HEADER: &str = "manufacturer;name;rname;cores;threads;base;boost;l1;l2;l3;l1asso;pagesize;loadbuffer;l1dtlb;microarchitecture";
+
+    #[test]
+    fn row_is_parsed() {
+        // prepare
+        let csv = format!(
+            "{}\r\nHiSilicon;HiSilicon Kirin 960;Cortex-A73/-A53;8;8;2.36;2.80;64;2048;;4;4096;;;Cortex-A73/-A53\r\n\r\n",
+            HEADER
+        );
+
+        // execute
+        let specs = parse_cpu_specs(&csv).unwrap();
+
+        // assert
+        assert_eq!(
+            specs,
+            vec![CpuSpecDTO {
+                manufacturer: "HiSilicon".to_string(),
+                name: "HiSilicon Kirin 960".to_string(),
+                alias: Some("Cortex-A73/-A53".to_string()),
+                cores: 8,
+                threads: 8,
+                base_ghz: 2.36,
+                boost_ghz: 2.8,
+                l1_kib: 64,
+                l2_kib: 2048,
+                l3_mib: None,
+                l1_associativity: Some(4),
+                page_size: 4096,
+                load_buffer: None,
+                l1_dtlb: None,
+                microarchitecture: "Cortex-A73/-A53".to_string(),
+            }]
+        );
+    }
+
+    #[test]
+    fn invalid_rows_are_reported() {
+        // execute & assert
+        assert_eq!(
+            parse_cpu_specs("name;cores\nmodel;4"),
+            Err("missing column 'manufacturer'".to_string())
+        );
+        assert_eq!(
+            parse_cpu_specs(&format!(
+                "{}\nIntel;model;;four;8;1;2;32;256;;8;4096;;64;Skylake",
+                HEADER
+            )),
+            Err("line 2: invalid value 'four' of column 'cores'".to_string())
+        );
+        assert_eq!(
+            parse_cpu_specs(&format!(
+                "{}\nIntel;model;;4;8;1;2;32;256;;8;;;64;Skylake",
+                HEADER
+            )),
+            Err("line 2: missing value of column 'pagesize'".to_string())
+        );
+        assert_eq!(
+            parse_cpu_specs(&format!("{}\nIntel;model;4", HEADER)),
+            Err("line 2: expected 15 values, found 3".to_string())
+        );
+    }
+
+    #[test]
+    fn catalogue_is_parsed() {
+        // execute
+        let specs = parse_cpu_specs(include_str!("../../classification/cpus.csv")).unwrap();
+
+        // assert
+        assert!(specs.len() > 300);
+        assert!(specs.iter().any(|spec| spec.name == "Apple M1"));
+    }
+
+    #[test]
+    fn catalogue_is_deduped() {
+        // prepare
+        let specs = parse_cpu_specs(include_str!("../../classification/cpus.csv")).unwrap();
+
+        // execute
+        let deduped = dedupe_cpu_specs(specs.clone());
+
+        // assert
+        assert_eq!(deduped.len(), specs.len() - 7);
+        for spec in &deduped {
+            assert_eq!(
+                deduped
+                    .iter()
+                    .filter(|other| other.name == spec.name)
+                    .count(),
+                1
+            );
+            assert!(deduped.iter().all(|other| other.name == spec.name
+                || other.alias.is_none()
+                || other.alias != spec.alias));
+        }
+        let kirin = find_cpu_spec(&deduped, "HiSilicon Kirin 960").unwrap();
+        assert_eq!(kirin.alias, None);
+    }
+
+    #[test]
+    fn names_are_matched_before_aliases() {
+        // prepare
+        let csv = format!(
+            "{}\nIntel;Core i5;Coffee Lake;6;6;2.8;4.0;32;256;9;8;4096;;64;Coffee Lake\nIntel;Coffee Lake;;4;4;2.8;4.0;32;256;6;8;4096;;64;Coffee Lake",
+            HEADER
+        );
+        let specs = parse_cpu_specs(&csv).unwrap();
+
+        // execute & assert
+        assert_eq!(
+            find_cpu_spec(&specs, "Coffee Lake").unwrap().l3_mib,
+            Some(6)
+        );
+        assert_eq!(find_cpu_spec(&specs, "Core i5").unwrap().l3_mib, Some(9));
+        assert!(find_cpu_spec(&specs, "core i5").is_none());
+    }
+}
@@ -0,0 +1,13 @@
+use serde::{Deserialize, Serialize};
+
+use crate::benchmark::BenchmarkType;
+
+/// Chart of a single benchmark result, rendered by the backend
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+pub struct ChartDTO {
+    pub benchmark: BenchmarkType,
+    /// MIME type of the image, e.g. `image/svg+xml`
+    pub content_type: String,
+    /// Base64-encoded image
+    pub data: String,
+}
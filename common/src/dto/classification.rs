@@ -0,0 +1,22 @@
+use serde::{Deserialize, Serialize};
+
+/// Candidate value of a classified property, with its share of the votes of the neighbours
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+pub struct CandidateDTO<T> {
+    pub value: T,
+    /// Between 0 and 1, the scores of all the candidates of a property sum up to 1
+    pub score: f64,
+}
+
+/// Properties of the CPU predicted from a result, every list ranked by the score
+#[derive(Serialize, Deserialize, Clone, Debug, Default, PartialEq)]
+pub struct ClassificationDTO {
+    pub models: Vec<CandidateDTO<String>>,
+    pub microarchitectures: Vec<CandidateDTO<String>>,
+    pub l1_kib: Vec<CandidateDTO<u32>>,
+    pub l2_kib: Vec<CandidateDTO<u32>>,
+    /// Only CPUs with an L3 cache vote for its size
+    pub l3_mib: Vec<CandidateDTO<u32>>,
+    /// Number of the results the classifier was trained on
+    pub training_size: usize,
+}
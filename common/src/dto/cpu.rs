@@ -0,0 +1,90 @@
+use serde::{Deserialize, Serialize};
+
+/// Specification of a CPU model, the ground truth for the measured results
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+pub struct CpuSpecDTO {
+    pub manufacturer: String,
+    /// Name matched against the normalized models of the results
+    pub name: String,
+    /// Alternative name the results are also matched by, e.g. the name of the cores
+    #[serde(default)]
+    pub alias: Option<String>,
+    pub cores: u32,
+    pub threads: u32,
+    pub base_ghz: f32,
+    pub boost_ghz: f32,
+    /// L1 data cache size of a core in KiB
+    pub l1_kib: u32,
+    /// L2 cache size of a core in KiB
+    pub l2_kib: u32,
+    /// Shared L3 cache size in MiB
+    #[serde(default)]
+    pub l3_mib: Option<u32>,
+    #[serde(default)]
+    pub l1_associativity: Option<u32>,
+    /// Page size in bytes
+    pub page_size: u32,
+    /// Number of entries of the load buffer
+    #[serde(default)]
+    pub load_buffer: Option<u32>,
+    /// Number of entries of the L1 data TLB
+    #[serde(default)]
+    pub l1_dtlb: Option<u32>,
+    pub microarchitecture: String,
+}
+
+/// CPU specification stored by the backend
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+pub struct StoredCpuSpecDTO {
+    pub id: i64,
+    /// Number of results matched by the specification
+    pub result_count: i64,
+    #[serde(flatten)]
+    pub spec: CpuSpecDTO,
+}
+
+impl AsRef<CpuSpecDTO> for CpuSpecDTO {
+    fn as_ref(&self) -> &CpuSpecDTO {
+        self
+    }
+}
+
+impl AsRef<CpuSpecDTO> for StoredCpuSpecDTO {
+    fn as_ref(&self) -> &CpuSpecDTO {
+        &self.spec
+    }
+}
+
+/// Normalized model of results which match no CPU specification
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+pub struct UnmatchedModelDTO {
+    pub model_normalized: String,
+    pub result_count: i64,
+}
+
+/// Outcome of the import of the CPU catalogue
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+pub struct CpuImportDTO {
+    /// Number of the inserted or changed specifications, the unchanged ones are not counted
+    pub imported: usize,
+}
+
+/// Known model suggested for the one typed by the user
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+pub struct ModelSuggestionDTO {
+    /// Normalized model, either of the CPU catalogue or of the previous results
+    pub model: String,
+    /// Whether the CPU catalogue has the specification of the model
+    pub in_catalogue: bool,
+    pub result_count: i64,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+pub struct ModelSuggestionsDTO {
+    /// The typed model after the normalisation
+    pub model_normalized: String,
+    /// Whether the normalized model is one of the known models
+    pub known: bool,
+    /// Known models similar to the typed one, the most similar first
+    pub suggestions: Vec<ModelSuggestionDTO>,
+}
@@ -0,0 +1,19 @@
+use serde::{Deserialize, Serialize};
+
+/// Body of the error responses of the backend API
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+pub struct ErrorDTO {
+    /// Machine-readable kind of the error, e.g. `validation_failed`
+    pub error: String,
+    pub message: String,
+    /// Invalid fields of the request, only reported by failed validations
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub fields: Vec<FieldErrorDTO>,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+pub struct FieldErrorDTO {
+    /// Path of the field, e.g. `benchmark_results[2]`
+    pub field: String,
+    pub message: String,
+}
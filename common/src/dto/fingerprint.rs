@@ -0,0 +1,32 @@
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+
+use crate::fingerprint::DistanceMetric;
+
+/// Stored result close to the searched one
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+pub struct SimilarResultDTO {
+    pub id: i64,
+    pub created_at: Option<DateTime<Utc>>,
+    pub model_normalized: String,
+    /// Hash of the quantised features, equal only for results in the same steps of all the features
+    pub fingerprint: String,
+    pub distance: f64,
+}
+
+/// Stored result with exactly the searched hash
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+pub struct FingerprintMatchDTO {
+    pub id: i64,
+    pub created_at: Option<DateTime<Utc>>,
+    pub model_normalized: String,
+}
+
+/// Results nearest to the searched one, the nearest first
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+pub struct SimilarResultsDTO {
+    pub id: i64,
+    pub fingerprint: String,
+    pub metric: DistanceMetric,
+    pub similar: Vec<SimilarResultDTO>,
+}
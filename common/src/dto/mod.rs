@@ -1 +1,6 @@
+pub mod chart;
+pub mod classification;
+pub mod cpu;
+pub mod error;
+pub mod fingerprint;
 pub mod result;
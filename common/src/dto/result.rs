@@ -1,9 +1,186 @@
+use chrono::{DateTime, Utc};
 use serde::{Deserialize, Serialize};
 
-#[derive(Serialize, Deserialize)]
+use crate::benchmark::{BenchmarkConfig, BenchmarkType};
+
+/// Version of the result format. Bump it whenever the benchmarks change so much
+/// that their results are not comparable with the ones uploaded before.
+///
+/// History:
+/// - `1`: the pointer chains of the cache size benchmark form a single cycle
+///   and the ones of the TLB benchmark touch one element per page
+pub const SCHEMA_VERSION: &str = "1";
+/// Version of results uploaded before the versions were recorded,
+/// measured with the pointer chains before their fix in version `1`
+pub const LEGACY_VERSION: &str = "legacy";
+
+#[derive(Serialize, Deserialize, Clone, Debug)]
 pub struct ResultDTO {
     pub model: String,
     pub user_agent: String,
+    /// Result of every benchmark, a list of `{x, y}` points. The adaptive cache size search
+    /// uploads `{points, boundaries}` instead, with the list of points under `points`.
+    /// Every boundary `{x, y_before, y_after}` is the size in bytes of the largest list
+    /// measured before a latency step, with the latencies on both sides of the step.
     pub benchmark_results: Vec<serde_json::Value>,
     pub times: Vec<f32>,
+    /// Benchmarks of `benchmark_results`, in the same order.
+    /// Older versions of the frontend sent the results in the order of `BenchmarkType::ALL` instead.
+    #[serde(default)]
+    pub benchmarks: Vec<BenchmarkType>,
+    /// Browser features detected before running the benchmarks.
+    /// Missing for results uploaded by older versions of the frontend.
+    #[serde(default)]
+    pub capabilities: Option<CapabilitiesDTO>,
+    /// Seed of the random number generator used by the benchmarks, allows to replay the run
+    #[serde(default)]
+    pub seed: Option<u64>,
+    #[serde(default = "legacy_version")]
+    pub schema_version: String,
+    /// Version of the frontend which measured the results
+    #[serde(default = "legacy_version")]
+    pub frontend_version: String,
+    #[serde(default)]
+    pub parameters: Option<BenchmarkConfig>,
+    /// Comparison of the CPU estimated on the device with the entered model.
+    /// Missing for results uploaded by older versions of the frontend.
+    #[serde(default)]
+    pub model_check: Option<ModelCheckDTO>,
+}
+
+impl ResultDTO {
+    /// Benchmark of every result, in the order of `benchmark_results`
+    pub fn benchmark_types(&self) -> Vec<BenchmarkType> {
+        if self.benchmarks.is_empty() {
+            BenchmarkType::ALL
+                .into_iter()
+                .take(self.benchmark_results.len())
+                .collect()
+        } else {
+            self.benchmarks.clone()
+        }
+    }
+}
+
+fn legacy_version() -> String {
+    LEGACY_VERSION.to_string()
+}
+
+/// Property of the CPU estimated from the benchmarks
+#[derive(Serialize, Deserialize, Copy, Clone, Debug, PartialEq, Eq)]
+pub enum CpuProperty {
+    L1Size,
+    L2Size,
+    L3Size,
+    PageSize,
+    L1Associativity,
+}
+
+/// Data quality check of the entered model against the CPU estimated by the frontend
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+pub struct ModelCheckDTO {
+    /// Name of the CPU specification of the entered model, `None` if the model is not in the catalogue
+    pub spec_name: Option<String>,
+    /// Properties whose confident estimates contradict the specification
+    pub mismatches: Vec<CpuProperty>,
+}
+
+impl ModelCheckDTO {
+    /// Whether the benchmarks were likely run on another CPU than the entered one
+    pub fn is_mismatch(&self) -> bool {
+        !self.mismatches.is_empty()
+    }
+}
+
+#[derive(Serialize, Deserialize, Copy, Clone, Debug, PartialEq, Eq)]
+pub enum ClockMode {
+    /// Counter incremented by a dedicated worker, shared through SharedArrayBuffer
+    SharedCounter,
+    /// Degraded mode using `performance.now()`, which works without cross-origin isolation
+    PerformanceNow,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq)]
+pub struct CapabilitiesDTO {
+    pub cross_origin_isolated: bool,
+    pub shared_array_buffer: bool,
+    pub atomics: bool,
+    pub worker: bool,
+    /// Clock selected for the benchmarks, `None` if the browser cannot run them at all
+    pub clock_mode: Option<ClockMode>,
+}
+
+/// Result stored by the backend, returned by the query API
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct StoredResultDTO {
+    pub id: i64,
+    /// Time of the upload, `None` for results uploaded before it was recorded
+    pub created_at: Option<DateTime<Utc>>,
+    /// Model cleaned up by `common::model::normalize_model`, `model` is kept as typed by the user
+    #[serde(default)]
+    pub model_normalized: String,
+    #[serde(flatten)]
+    pub result: ResultDTO,
+}
+
+/// Page of the results, newest first
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct ResultPageDTO {
+    pub results: Vec<StoredResultDTO>,
+    /// Passed as the `cursor` parameter to get the next page, missing on the last page
+    pub next_cursor: Option<i64>,
+}
+
+/*
+ * TESTS
+ */
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+
+    use crate::benchmark::BenchmarkType;
+    use crate::dto::result::{ResultDTO, LEGACY_VERSION};
+
+    #[test]
+    fn results_of_old_frontends_are_legacy() {
+        // prepare
+        let body = json!({
+            "model": "model",
+            "user_agent": "agent",
+            "benchmark_results": [[{"x": 1, "y": 2}]],
+            "times": [1.5]
+        });
+
+        // execute
+        let result = serde_json::from_value::<ResultDTO>(body).unwrap();
+
+        // assert
+        assert_eq!(result.schema_version, LEGACY_VERSION);
+        assert_eq!(result.frontend_version, LEGACY_VERSION);
+        assert!(result.parameters.is_none());
+        assert!(result.seed.is_none());
+        assert_eq!(result.benchmark_types(), vec![BenchmarkType::PageSize]);
+    }
+
+    #[test]
+    fn named_results_keep_their_order() {
+        // prepare
+        let body = json!({
+            "model": "model",
+            "user_agent": "agent",
+            "benchmark_results": [[], []],
+            "times": [1.5, 2.5],
+            "benchmarks": ["TlbSize", "PageSize"]
+        });
+
+        // execute
+        let result = serde_json::from_value::<ResultDTO>(body).unwrap();
+
+        // assert
+        assert_eq!(
+            result.benchmark_types(),
+            vec![BenchmarkType::TlbSize, BenchmarkType::PageSize]
+        );
+    }
 }
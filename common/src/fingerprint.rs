@@ -0,0 +1,213 @@
+use serde::{Deserialize, Serialize};
+
+use crate::classification::feature_vector;
+use crate::dto::result::ResultDTO;
+
+/// Width of the quantisation steps of the features. The latency profiles are in natural log
+/// units, so a step is about 28 % of latency, more than the usual noise between the runs.
+pub const QUANTUM: f64 = 0.25;
+/// Results sharing fewer features are not compared
+pub const MIN_SHARED_FEATURES: usize = 8;
+/// Version of the feature layout and the quantisation, bumped whenever they change so the
+/// stored fingerprints are computed again
+pub const FINGERPRINT_VERSION: i32 = 1;
+const FNV_OFFSET: u64 = 0xcbf2_9ce4_8422_2325;
+const FNV_PRIME: u64 = 0x0100_0000_01b3;
+
+/// Fixed-length feature vector of a result and the hash of its quantised features.
+/// The hash identifies identical quantised profiles, e.g. the uploads of the same measurement.
+/// Repeated runs on the same device usually differ in some features and are found by the distance.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+pub struct Fingerprint {
+    /// Features of `common::classification::feature_vector`, `None` where they are missing
+    pub features: Vec<Option<f64>>,
+    /// 64-bit FNV-1a hash of the quantised features, as 16 hexadecimal digits
+    pub hash: String,
+}
+
+impl Fingerprint {
+    pub fn of(result: &ResultDTO) -> Fingerprint {
+        let features = feature_vector(result)
+            .into_iter()
+            .map(|feature| feature.is_finite().then_some(feature))
+            .collect::<Vec<_>>();
+
+        Fingerprint {
+            hash: hash(&quantise(&features)),
+            features,
+        }
+    }
+}
+
+/// Feature values rounded to the multiples of `QUANTUM`
+pub fn quantise(features: &[Option<f64>]) -> Vec<Option<i64>> {
+    features
+        .iter()
+        .map(|feature| feature.map(|value| (value / QUANTUM).round() as i64))
+        .collect()
+}
+
+/// Stable across platforms and releases, unlike the hashers of the standard library
+fn hash(levels: &[Option<i64>]) -> String {
+    let hash = levels.iter().fold(FNV_OFFSET, |hash, level| {
+        let bytes = match level {
+            Some(level) => [&[1u8][..], &level.to_le_bytes()].concat(),
+            None => vec![0u8],
+        };
+        bytes.into_iter().fold(hash, |hash, byte| {
+            (hash ^ byte as u64).wrapping_mul(FNV_PRIME)
+        })
+    });
+    format!("{:016x}", hash)
+}
+
+/// Distance between the feature vectors, computed over the features present in both
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, Default, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum DistanceMetric {
+    /// Root mean square difference
+    #[default]
+    Euclidean,
+    /// Mean absolute difference
+    Manhattan,
+    /// One minus the cosine similarity
+    Cosine,
+    /// Share of the features in different quantisation steps
+    Hamming,
+}
+
+impl DistanceMetric {
+    /// `None` if the vectors share less than `MIN_SHARED_FEATURES` features
+    pub fn distance(&self, a: &[Option<f64>], b: &[Option<f64>]) -> Option<f64> {
+        let shared = a
+            .iter()
+            .zip(b.iter())
+            .filter_map(|(a, b)| Some(((*a)?, (*b)?)))
+            .collect::<Vec<_>>();
+        if shared.len() < MIN_SHARED_FEATURES {
+            return None;
+        }
+        let count = shared.len() as f64;
+
+        Some(match self {
+            DistanceMetric::Euclidean => {
+                (shared.iter().map(|(a, b)| (a - b).powi(2)).sum::<f64>() / count).sqrt()
+            }
+            DistanceMetric::Manhattan => {
+                shared.iter().map(|(a, b)| (a - b).abs()).sum::<f64>() / count
+            }
+            DistanceMetric::Cosine => {
+                let dot = shared.iter().map(|(a, b)| a * b).sum::<f64>();
+                let norm_a = shared.iter().map(|(a, _)| a * a).sum::<f64>().sqrt();
+                let norm_b = shared.iter().map(|(_, b)| b * b).sum::<f64>().sqrt();
+                if norm_a == 0.0 || norm_b == 0.0 {
+                    // flat profiles only resemble each other
+                    if norm_a == norm_b {
+                        0.0
+                    } else {
+                        1.0
+                    }
+                } else {
+                    // rounding may push identical profiles slightly below zero
+                    (1.0 - dot / (norm_a * norm_b)).max(0.0)
+                }
+            }
+            DistanceMetric::Hamming => {
+                let (a, b): (Vec<_>, Vec<_>) =
+                    shared.iter().map(|(a, b)| (Some(*a), Some(*b))).unzip();
+                let differing = quantise(&a)
+                    .into_iter()
+                    .zip(quantise(&b))
+                    .filter(|(a, b)| a != b)
+                    .count();
+                differing as f64 / count
+            }
+        })
+    }
+}
+
+/*
+ * TESTS
+ */
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+
+    use crate::benchmark::BenchmarkType;
+    use crate::classification::FEATURE_COUNT;
+    use crate::dto::result::{ResultDTO, SCHEMA_VERSION};
+    use crate::fingerprint::{quantise, DistanceMetric, Fingerprint};
+
+    fn result(ways: u32, slowdown: f64, noise: f64) -> ResultDTO {
+        let points = (1..32)
+            .map(|lines| {
+                let latency = if lines <= ways { 10.0 } else { 10.0 * slowdown };
+                json!({"x": lines, "y": latency * (1.0 + noise * (lines % 3) as f64)})
+            })
+            .collect::<Vec<_>>();
+        ResultDTO {
+            model: "model".to_string(),
+            user_agent: "agent".to_string(),
+            benchmark_results: vec![json!(points)],
+            times: vec![1.0],
+            benchmarks: vec![BenchmarkType::CacheAssociativity],
+            capabilities: None,
+            seed: None,
+            schema_version: SCHEMA_VERSION.to_string(),
+            frontend_version: "0.1.0".to_string(),
+            parameters: None,
+            model_check: None,
+        }
+    }
+
+    #[test]
+    fn small_noise_keeps_hash() {
+        // execute
+        // ln 3.5 is close to a multiple of the quantum, away from the rounding boundaries
+        let fingerprint = Fingerprint::of(&result(8, 3.5, 0.0));
+        let noisy = Fingerprint::of(&result(8, 3.5, 0.02));
+        let other = Fingerprint::of(&result(12, 3.5, 0.0));
+
+        // assert
+        assert_eq!(fingerprint.features.len(), FEATURE_COUNT);
+        assert_eq!(fingerprint.features.iter().flatten().count(), 16);
+        assert_eq!(fingerprint.hash.len(), 16);
+        assert_eq!(fingerprint.hash, noisy.hash);
+        assert_ne!(fingerprint.hash, other.hash);
+    }
+
+    #[test]
+    fn hash_is_stable() {
+        // execute & assert
+        assert_eq!(
+            Fingerprint::of(&result(8, 4.0, 0.0)).hash,
+            Fingerprint::of(&result(8, 4.0, 0.0)).hash
+        );
+        assert_eq!(
+            quantise(&[Some(0.1), Some(0.2), None, Some(-0.4)]),
+            vec![Some(0), Some(1), None, Some(-2)]
+        );
+    }
+
+    #[test]
+    fn distances_use_shared_features() {
+        // prepare
+        let a = (0..10).map(|i| Some(i as f64)).collect::<Vec<_>>();
+        let mut b = a
+            .iter()
+            .map(|value| value.map(|v| v + 1.0))
+            .collect::<Vec<_>>();
+        b.push(Some(100.0));
+        let sparse = vec![Some(1.0); 5];
+
+        // execute & assert
+        assert_eq!(DistanceMetric::Euclidean.distance(&a, &b), Some(1.0));
+        assert_eq!(DistanceMetric::Manhattan.distance(&a, &b), Some(1.0));
+        assert_eq!(DistanceMetric::Hamming.distance(&a, &b), Some(1.0));
+        assert_eq!(DistanceMetric::Hamming.distance(&a, &a), Some(0.0));
+        assert!(DistanceMetric::Cosine.distance(&a, &a).unwrap().abs() < 1e-12);
+        assert!(DistanceMetric::Cosine.distance(&a, &b).unwrap() > 0.0);
+        assert_eq!(DistanceMetric::Euclidean.distance(&a, &sparse), None);
+    }
+}
@@ -1 +1,9 @@
+pub mod analysis;
+pub mod benchmark;
+pub mod chart;
+pub mod classification;
+pub mod cpu;
 pub mod dto;
+pub mod fingerprint;
+pub mod model;
+pub mod steps;
@@ -0,0 +1,430 @@
+/// Step of the normalisation of the CPU model typed by the user
+enum Rule {
+    /// Replaces all occurrences of the pattern
+    Replace(&'static str, &'static str),
+    /// Drops everything from the first occurrence of the pattern
+    CutFrom(&'static str),
+    /// Drops everything from the pattern, unless the model contains one of the words
+    CutFromUnless(&'static str, &'static [&'static str]),
+    /// Drops everything up to the end of the first occurrence of the pattern
+    CutThrough(&'static str),
+    /// Prepends the vendor to models starting with the pattern
+    Prefix(&'static str, &'static str),
+    /// Replaces the whole model if it contains the pattern
+    Contains(&'static str, &'static str),
+    /// Replaces the whole model if it is one of the known misspellings
+    Corrections(&'static [(&'static str, &'static str)]),
+}
+
+/// Families whose names contain "CPU" as part of the official model name
+const CPU_IN_NAME: &[&str] = &["Pentium", "Xeon", "Celeron", "Duo"];
+
+/// Misspelled models which are matched before the frequency and the core count are cut off
+const EARLY_CORRECTIONS: &[(&str, &str)] = &[
+    (
+        "AMD Athlon(TM) Dual Core Processor 5000B",
+        "AMD Athlon 64 X2 5000B",
+    ),
+    ("9900K", "Intel(R) Core(TM) i9-9900K"),
+    (
+        "intel (R) core (TM) i5-5200U 2.20ghz",
+        "Intel(R) Core(TM) i5-5200U",
+    ),
+    ("AMD A9-9425 Radeon R5, Compute Cores 2C+3G", "AMD A9-9425"),
+    ("Hisilicon Kirin 960 (Huawei P10 VTR-L09)", "Kirin 960"),
+    (
+        "AMD Turion(TM) X2 Ultra Dual-Core Mobile ZM-87",
+        "AMD Turion(TM) X2 Ultra Dual-Core ZM-87",
+    ),
+    ("Intel Core i3-8130U", "Intel(R) Core(TM) i3-8130U"),
+    ("intel core i5-5287U", "Intel(R) Core(TM) i5-5287U"),
+    ("i5-8300H", "Intel(R) Core(TM) i5-8300H"),
+    ("i3-6100", "Intel(R) Core(TM) i3-6100"),
+    ("AMD Ryzen 4800H", "AMD Ryzen 7 4800H"),
+    ("Intel core i5-8250u", "Intel(R) Core(TM) i5-8250U"),
+    ("Intel Core i7-4700", "Intel(R) Core(TM) i7-4700"),
+    ("AMD FX 8320E", "AMD FX(TM)-8320E"),
+    ("Intel(R) Core(TM) 15-2520M", "Intel(R) Core(TM) i5-2520M"),
+    (
+        "AMD A10-9600P RADEON R5, 10 COMPUTE CORES 4C+6G",
+        "AMD A10-9600P",
+    ),
+    (
+        "AMD Athlon(TM) X4 860K Quad Core Processor",
+        "AMD Athlon(TM) X4 860K",
+    ),
+    ("AMD Ryzen 5 2600x 6 core processor", "AMD Ryzen 5 2600X"),
+    ("Intel(R) core (TM) i7-7500U", "Intel(R) Core(TM) i7-7500U"),
+    ("i5-3570", "Intel(R) Core(TM) i5-3570"),
+    ("celeron n4000", "Intel(R) Celeron(R) N4000"),
+    ("Intel i7-7700HQ", "Intel(R) Core(TM) i7-7700HQ"),
+];
+
+/// Misspelled models which are matched after all the other rules
+const LATE_CORRECTIONS: &[(&str, &str)] = &[
+    ("AMD FX 6300", "AMD FX(TM)-6300"),
+    (
+        "Intel(R) Core(TM) i5-8265UCPU",
+        "Intel(R) Core(TM) i5-8265U",
+    ),
+    ("AMD Ryzen5 3500U", "AMD Ryzen 5 3500U"),
+    ("i5-1035G4", "Intel(R) Core(TM) i5-1035G4"),
+    ("i7-855OU", "Intel(R) Core(TM) i7-8550U"),
+    ("AMD Ryzen 3900x", "AMD Ryzen 9 3900X"),
+    ("Intel(R) Core(TM) i-5 2400", "Intel(R) Core(TM) i5-2400"),
+    ("Intel Core i5-6500", "Intel(R) Core(TM) i5-6500"),
+    ("Intel(R) Celeron(R) N4000 CPU", "Intel(R) Celeron(R) N4000"),
+    ("Intel Core-i7-4790K", "Intel(R) Core(TM) i7-4790K"),
+    ("AMD Ryzen 3 325OU", "AMD Ryzen 3 3250U"),
+    ("i5-3470", "Intel(R) Core(TM) i5-3470"),
+    ("i5 6500", "Intel(R) Core(TM) i5-6500"),
+    ("Intel(R) Core(TM) i5-1021OU", "Intel(R) Core(TM) i5-10210U"),
+    ("amd ryzen 7 2700x", "AMD Ryzen 7 2700X"),
+    ("i5-9300H", "Intel(R) Core(TM) i5-9300H"),
+    ("i5 7500", "Intel(R) Core(TM) i5-7500"),
+    ("i5 4690k", "Intel(R) Core(TM) i5-4690K"),
+    ("i7-5820k", "Intel(R) Core(TM) i7-5820K"),
+    ("i5-6500", "Intel(R) Core(TM) i5-6500"),
+    ("i5-4210U", "Intel(R) Core(TM) i5-4210U"),
+    (
+        "AMD FX-7500 Radeon R7, 10 Compute Cores 4C+6G",
+        "AMD FX-7500",
+    ),
+    (
+        "AMD A9-9425 RADEON R5, 5 COMPUTE CORES 2C+3G 3.10 GHz",
+        "AMD A9-9425",
+    ),
+    (
+        "AMD A12-9720P RADEON R7, 12 COMPUTE CORES 4C+8G",
+        "AMD A12-9720P",
+    ),
+    (
+        "AMD A9-9420 RADEON R5, 5 COMPUTE CORES 2C+3G",
+        "AMD A9-9420",
+    ),
+    ("AMD Phenom II X4 925 processor", "AMD Phenom II X4 925"),
+    (
+        "Name Intel(R) Core(TM) i5-5300U",
+        "Intel(R) Core(TM) i5-5300U",
+    ),
+    ("Intel(R) Core(TM) i58265U", "Intel(R) Core(TM) i5-8265U"),
+    ("Intel(R) Core TM I3-403OU", "Intel(R) Core(TM) i3-4030U"),
+    ("Intel Core i5-3470", "Intel(R) Core(TM) i5-3470"),
+    ("Intel Core i7-10750H", "Intel(R) Core(TM) i7-10750H"),
+    ("Intel(R) Core(TM) i7-4770k", "Intel(R) Core(TM) i7-4770K"),
+    ("intel(R) core (TM) i7-8700", "Intel(R) Core(TM) i7-8700"),
+    ("i5-7300HQ", "Intel(R) Core(TM) i5-7300HQ"),
+    (
+        "Intel( R ) Core ( TM ) i3-502ou",
+        "Intel(R) Core(TM) i3-5020U",
+    ),
+    ("Intel(R) core(TM) i5-4210U", "Intel(R) Core(TM) i5-4210U"),
+    ("AMD A8 -4500M APU", "AMD A8-4500M APU"),
+    ("AMD FX-8350", "AMD FX(TM)-8350"),
+    (
+        "Intel(R) Core(TM) i7- 9750HCPU",
+        "Intel(R) Core(TM) i7-9750H",
+    ),
+    ("AMD Ryzen 9 3900x", "AMD Ryzen 9 3900X"),
+    ("intel core i7-6700", "Intel(R) Core(TM) i7-6700"),
+    (
+        "AMD A10-9620P RADEON R5, 10 COMPUTE CORES 4C+6G",
+        "AMD A10-9620P",
+    ),
+    ("Intel Core i5 4670K", "Intel(R) Core(TM) i5-4670K"),
+    ("Intel(R) Core(TM) i7 870", "Intel(R) Core(TM) i7-870"),
+    ("Intel(R) Core TM i7-8565U", "Intel(R) Core(TM) i7-8565U"),
+    (": Intel(R) Core(TM) i5-8400", "Intel(R) Core(TM) i5-8400"),
+    ("AMD FX<tm>-8350", "AMD FX(TM)-8350"),
+    ("AMD Ryzen 3700X", "AMD Ryzen 7 3700X"),
+    ("Intel(R) Core(TM) i5- 8400", "Intel(R) Core(TM) i5-8400"),
+    ("Inter(R) Core(TM) i5-8400", "Intel(R) Core(TM) i5-8400"),
+    ("Intel(R) Core(TM) i5-5200u", "Intel(R) Core(TM) i5-5200U"),
+    ("i5-7200U", "Intel(R) Core(TM) i5-7200U"),
+    ("AMD Ryzen 1700", "AMD Ryzen 7 1700"),
+    ("Intel(R)Core(TM) i5-5287U", "Intel(R) Core(TM) i5-5287U"),
+    ("intel core i5- 3320m", "Intel(R) Core(TM) i5-3320M"),
+    (
+        "AMD A10-8700P Radeon R6, 10 Compute Cores 4C+6G",
+        "AMD A10-8700P",
+    ),
+    (
+        "AMD Turion(TM) II P520 Dual-Core Processor",
+        "AMD Turion(TM) II P520",
+    ),
+    ("Intel Core I5-7300HQcpu", "Intel(R) Core(TM) i5-7300HQ"),
+    ("Intel(R) Core(I) I7-7700HQ", "Intel(R) Core(TM) i7-7700HQ"),
+    ("intel pentium N3710", "Intel(R) Pentium(R) CPU N3710"),
+    ("AMD athlon silver 3050U", "AMD Athlon(TM) Silver 3050U"),
+];
+
+/// Version of `RULES`, bumped whenever they change so the stored models are normalized again
+pub const RULES_VERSION: i32 = 1;
+
+/// Rules applied in order, ported from `model_filter` of the classification tooling
+const RULES: &[Rule] = &[
+    Rule::Replace(" - ", "-"),
+    Rule::CutFromUnless(" CPU", CPU_IN_NAME),
+    Rule::CutFromUnless(" cpu", CPU_IN_NAME),
+    Rule::CutFrom(" w/"),
+    Rule::Replace("(IM)", "(TM)"),
+    Rule::Replace("(tm)", "(TM)"),
+    Rule::Replace("(r)", "(R)"),
+    Rule::Replace("Intel (R)", "Intel(R)"),
+    Rule::Replace("Core (TM)", "Core(TM)"),
+    Rule::Replace("Itel", "Intel"),
+    Rule::Replace("Amd", "AMD"),
+    Rule::Replace("RYZEN", "Ryzen"),
+    Rule::Replace("Processor\\t", ""),
+    Rule::Replace("3700x", "3700X"),
+    Rule::Replace("3250u", "3250U"),
+    Rule::Replace("2700k", "2700K"),
+    Rule::Replace("4790k", "4790K"),
+    Rule::Replace("6700k", "6700K"),
+    Rule::Replace("3770k", "3770K"),
+    Rule::Replace("i7-11657", "i7-1165G7"),
+    Rule::Corrections(EARLY_CORRECTIONS),
+    Rule::CutFrom(" @"),
+    Rule::CutThrough("11th Gen "),
+    Rule::CutThrough("12th Gen "),
+    Rule::CutFrom(" with"),
+    Rule::CutFrom(" Quad-Core"),
+    Rule::CutFrom(" Six-Core"),
+    Rule::CutFrom(" Eight-Core"),
+    Rule::CutFrom(" quad-core"),
+    Rule::CutFrom(" six-core"),
+    Rule::CutFrom(" eight-core"),
+    Rule::CutFrom(" 6-Core"),
+    Rule::CutFrom(" 8-Core"),
+    Rule::CutFrom(" 12-Core"),
+    Rule::CutFrom(" 6-core"),
+    Rule::CutFrom(" 8-core"),
+    Rule::CutFrom(" 12-core"),
+    Rule::CutThrough("machdep.cpu.brand_string: "),
+    Rule::Prefix("Ryzen", "AMD "),
+    Rule::Prefix("Pentium", "Intel(R) "),
+    Rule::Prefix("Core", "Intel(R) "),
+    Rule::Contains("Apple M1", "Apple M1"),
+    Rule::Corrections(LATE_CORRECTIONS),
+];
+
+impl Rule {
+    fn apply(&self, model: String) -> String {
+        match self {
+            Rule::Replace(pattern, replacement) => model.replace(pattern, replacement),
+            Rule::CutFrom(pattern) => cut_from(model, pattern),
+            Rule::CutFromUnless(pattern, words) => {
+                if words.iter().any(|word| model.contains(word)) {
+                    model
+                } else {
+                    cut_from(model, pattern)
+                }
+            }
+            Rule::CutThrough(pattern) => match model.split_once(pattern) {
+                Some((_, rest)) => rest.to_string(),
+                None => model,
+            },
+            Rule::Prefix(start, vendor) if model.starts_with(start) => {
+                format!("{}{}", vendor, model)
+            }
+            Rule::Prefix(_, _) => model,
+            Rule::Contains(pattern, name) if model.contains(pattern) => name.to_string(),
+            Rule::Contains(_, _) => model,
+            Rule::Corrections(corrections) => corrections
+                .iter()
+                .find(|(misspelled, _)| *misspelled == model)
+                .map(|(_, correct)| correct.to_string())
+                .unwrap_or(model),
+        }
+    }
+}
+
+fn cut_from(mut model: String, pattern: &str) -> String {
+    if let Some(position) = model.find(pattern) {
+        model.truncate(position);
+    }
+    model
+}
+
+/// Cleans up the CPU model typed by the user, so the same CPUs have the same model.
+/// Unknown models are only stripped of the frequency, core count and similar suffixes.
+pub fn normalize_model(model: &str) -> String {
+    let model = model.split_whitespace().collect::<Vec<_>>().join(" ");
+
+    RULES
+        .iter()
+        .fold(model, |model, rule| rule.apply(model))
+        .trim()
+        .to_string()
+}
+
+/// Words of the models which do not tell the CPUs apart, e.g. the remains of "(R)" and "(TM)"
+const NOISE_WORDS: [&str; 4] = ["r", "tm", "cpu", "processor"];
+
+/// Minimal similarity of the known model to be suggested for the typed one
+pub const SUGGESTION_THRESHOLD: f32 = 0.75;
+
+/// Similarity of the typed model to a known one, from 0 (unrelated) to 1 (every typed word matches).
+/// Typed words may be misspelled or unfinished, e.g. "ryzn 7 37" is similar to "AMD Ryzen 7 3700X".
+pub fn model_similarity(typed: &str, known: &str) -> f32 {
+    let typed = words(typed);
+    let known = words(known);
+    if typed.is_empty() || known.is_empty() {
+        return 0.0;
+    }
+
+    let total = typed
+        .iter()
+        .map(|typed| {
+            known
+                .iter()
+                .map(|known| word_similarity(typed, known))
+                .fold(0.0, f32::max)
+        })
+        .sum::<f32>();
+    total / typed.len() as f32
+}
+
+fn words(model: &str) -> Vec<String> {
+    model
+        .to_lowercase()
+        .split(|c: char| !c.is_alphanumeric())
+        .filter(|word| !word.is_empty() && !NOISE_WORDS.contains(word))
+        .map(str::to_string)
+        .collect()
+}
+
+fn word_similarity(typed: &str, known: &str) -> f32 {
+    if typed == known {
+        return 1.0;
+    }
+    // the user is still typing the word
+    if known.starts_with(typed) {
+        return 0.9;
+    }
+
+    let typed = typed.chars().collect::<Vec<_>>();
+    let known = known.chars().collect::<Vec<_>>();
+    let distance = edit_distance(&typed, &known);
+    1.0 - distance as f32 / typed.len().max(known.len()) as f32
+}
+
+/// Levenshtein distance of the words
+fn edit_distance(a: &[char], b: &[char]) -> usize {
+    let mut previous = (0..=b.len()).collect::<Vec<_>>();
+    for (i, a) in a.iter().enumerate() {
+        let mut current = vec![i + 1];
+        for (j, b) in b.iter().enumerate() {
+            let substitution = previous[j] + usize::from(a != b);
+            current.push(substitution.min(previous[j + 1] + 1).min(current[j] + 1));
+        }
+        previous = current;
+    }
+    previous[b.len()]
+}
+
+/*
+ * TESTS
+ */
+
+#[cfg(test)]
+mod tests {
+    use crate::model::{model_similarity, normalize_model, SUGGESTION_THRESHOLD};
+
+    #[test]
+    fn suffixes_are_removed() {
+        // execute & assert
+        assert_eq!(
+            normalize_model("Intel(R) Core(TM) i7-8700 CPU @ 3.20GHz"),
+            "Intel(R) Core(TM) i7-8700"
+        );
+        assert_eq!(
+            normalize_model("AMD Ryzen 7 3700X 8-Core Processor"),
+            "AMD Ryzen 7 3700X"
+        );
+        assert_eq!(
+            normalize_model("AMD Ryzen 5 4500U with Radeon Graphics"),
+            "AMD Ryzen 5 4500U"
+        );
+        assert_eq!(
+            normalize_model("11th Gen Intel(R) Core(TM) i5-1135G7 @ 2.40GHz"),
+            "Intel(R) Core(TM) i5-1135G7"
+        );
+    }
+
+    #[test]
+    fn cpu_is_kept_in_official_names() {
+        // execute & assert
+        assert_eq!(
+            normalize_model("Intel(R) Pentium(R) CPU G4560 @ 3.50GHz"),
+            "Intel(R) Pentium(R) CPU G4560"
+        );
+        assert_eq!(
+            normalize_model("Intel(R) Core(TM)2 Duo CPU P8600 @ 2.40GHz"),
+            "Intel(R) Core(TM)2 Duo CPU P8600"
+        );
+    }
+
+    #[test]
+    fn typos_are_fixed() {
+        // execute & assert
+        assert_eq!(
+            normalize_model("Itel(R)  Core(IM) i5-6600k  CPU"),
+            "Intel(R) Core(TM) i5-6600k"
+        );
+        assert_eq!(normalize_model("Amd RYZEN 7 3700x"), "AMD Ryzen 7 3700X");
+        assert_eq!(normalize_model("Ryzen 5 3600"), "AMD Ryzen 5 3600");
+        assert_eq!(normalize_model("Core i5-4590"), "Intel(R) Core i5-4590");
+        assert_eq!(
+            normalize_model("machdep.cpu.brand_string: Intel(R) Core(TM) i7-4870HQ CPU @ 2.50GHz"),
+            "Intel(R) Core(TM) i7-4870HQ"
+        );
+        assert_eq!(normalize_model("Apple M1 Pro (MacBook)"), "Apple M1");
+    }
+
+    #[test]
+    fn known_misspellings_are_corrected() {
+        // execute & assert
+        assert_eq!(normalize_model("9900K"), "Intel(R) Core(TM) i9-9900K");
+        assert_eq!(normalize_model("AMD FX 8320E"), "AMD FX(TM)-8320E");
+        assert_eq!(
+            normalize_model("AMD Athlon(tm) Dual Core Processor 5000B"),
+            "AMD Athlon 64 X2 5000B"
+        );
+        assert_eq!(normalize_model(" i5 6500 "), "Intel(R) Core(TM) i5-6500");
+        assert_eq!(
+            normalize_model("Intel(R) Celeron(R) N4000 CPU"),
+            "Intel(R) Celeron(R) N4000"
+        );
+    }
+
+    #[test]
+    fn normalized_models_are_kept() {
+        // prepare
+        let models = [
+            "Intel(R) Core(TM) i7-8700",
+            "AMD Ryzen 9 3900X",
+            "Intel(R) Xeon(R) CPU E5-2670 v3",
+            "Apple M1",
+        ];
+
+        // execute & assert
+        for model in models {
+            assert_eq!(normalize_model(model), model);
+            assert_eq!(normalize_model(&normalize_model(model)), model);
+        }
+    }
+
+    #[test]
+    fn typed_words_are_matched_fuzzily() {
+        // execute & assert
+        assert_eq!(
+            model_similarity("Intel Core i7-8700", "Intel(R) Core(TM) i7-8700"),
+            1.0
+        );
+        assert!(model_similarity("ryzn 7 37", "AMD Ryzen 7 3700X") >= SUGGESTION_THRESHOLD);
+        assert!(model_similarity("apple m1", "Apple M1") > model_similarity("apple m", "Apple M1"));
+        assert!(model_similarity("Intel Core i7-8700", "AMD Ryzen 7 3700X") < SUGGESTION_THRESHOLD);
+        assert_eq!(model_similarity("(R)", "Apple M1"), 0.0);
+    }
+}
@@ -0,0 +1,273 @@
+/// Minimal number of measurements between two change points
+const MIN_SEGMENT: usize = 2;
+/// Minimal ratio between the latencies after and before a change point considered a step
+const MIN_STEP_RATIO: f64 = 1.15;
+/// Lower limit of the noise of the log latency, so noiseless series still penalise extra changes
+const MIN_NOISE: f64 = 0.01;
+/// Effect size (step height over noise) at which the confidence reaches 1 - 1/e
+const EFFECT_SCALE: f64 = 4.0;
+
+/// Change of the latency between two segments of the series
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Step {
+    /// Last `x` before the change
+    pub x: f64,
+    /// First `x` after the change
+    pub x_after: f64,
+    /// Latency after the change divided by the latency before it
+    pub ratio: f64,
+    /// Height of the step over the noise of the segments around it, between 0 and 1
+    pub confidence: f64,
+}
+
+/// Rising steps of the latency, found by the change point detection with at most
+/// `max_rising` + 1 changes. A falling change may hide among the rising ones, e.g. a warmup.
+/// The detector of the charts, of the analysis and of the adaptive cache size search.
+pub fn rising_steps(points: &[(f64, f64)], max_rising: usize) -> Vec<Step> {
+    let points = latencies(points);
+
+    change_points(&points, max_rising + 1)
+        .into_iter()
+        .filter(|step| step.ratio >= MIN_STEP_RATIO)
+        .take(max_rising)
+        .collect()
+}
+
+/// Steps of the latency after the given sizes, e.g. the boundaries recorded by the adaptive
+/// cache size search, which are reported as the `x` of the steps. Sizes outside the series
+/// or without a measurement since the previous one are skipped.
+pub fn steps_at(points: &[(f64, f64)], xs: &[f64]) -> Vec<Step> {
+    let points = latencies(points);
+    let values = points.iter().map(|(_, y)| y.ln()).collect::<Vec<_>>();
+
+    let mut xs = xs
+        .iter()
+        .copied()
+        .filter(|x| x.is_finite())
+        .collect::<Vec<_>>();
+    xs.sort_by(f64::total_cmp);
+    let mut bounds = vec![0];
+    let mut sizes = vec![];
+    for x in xs {
+        let bound = points.iter().filter(|(px, _)| *px <= x).count();
+        if bound > *bounds.last().unwrap() && bound < points.len() {
+            bounds.push(bound);
+            sizes.push(x);
+        }
+    }
+    bounds.push(points.len());
+
+    measure_steps(&points, &Sums::new(&values), &bounds)
+        .into_iter()
+        .zip(sizes)
+        .map(|(step, x)| Step { x, ..step })
+        .collect()
+}
+
+/// Positive latencies sorted by the size
+fn latencies(points: &[(f64, f64)]) -> Vec<(f64, f64)> {
+    sorted(points)
+        .into_iter()
+        .filter(|(_, y)| *y > 0.0)
+        .collect()
+}
+
+/// Optimal segmentation of the log latency into piecewise constant segments. The number of
+/// the changes is chosen by the penalty `2 σ² ln n`, with the noise σ estimated from the
+/// differences of the neighbouring measurements.
+fn change_points(points: &[(f64, f64)], max_changes: usize) -> Vec<Step> {
+    let values = points.iter().map(|(_, y)| y.ln()).collect::<Vec<_>>();
+    let n = values.len();
+    if n < 2 * MIN_SEGMENT {
+        return vec![];
+    }
+    let sums = Sums::new(&values);
+    let noise = noise(&values);
+    let penalty = 2.0 * noise * noise * (n as f64).ln();
+
+    // cost[k][j] is the cost of splitting the first j values into k + 1 segments
+    let max_changes = max_changes.min(n / MIN_SEGMENT - 1);
+    let mut cost = vec![vec![f64::INFINITY; n + 1]; max_changes + 1];
+    let mut start = vec![vec![0; n + 1]; max_changes + 1];
+    for (j, cost) in cost[0].iter_mut().enumerate().skip(MIN_SEGMENT) {
+        *cost = sums.squared_error(0, j);
+    }
+    for k in 1..=max_changes {
+        for j in (k + 1) * MIN_SEGMENT..=n {
+            for i in k * MIN_SEGMENT..=j - MIN_SEGMENT {
+                let candidate = cost[k - 1][i] + sums.squared_error(i, j);
+                if candidate < cost[k][j] {
+                    cost[k][j] = candidate;
+                    start[k][j] = i;
+                }
+            }
+        }
+    }
+
+    let changes = (0..=max_changes)
+        .min_by(|a, b| {
+            let a = cost[*a][n] + *a as f64 * penalty;
+            let b = cost[*b][n] + *b as f64 * penalty;
+            a.total_cmp(&b)
+        })
+        .unwrap_or(0);
+
+    // boundaries of the segments, from the end
+    let mut bounds = vec![n];
+    for k in (1..=changes).rev() {
+        bounds.push(start[k][*bounds.last().unwrap()]);
+    }
+    bounds.push(0);
+    bounds.reverse();
+
+    measure_steps(points, &sums, &bounds)
+}
+
+/// Steps between the segments delimited by the indices of `bounds`
+fn measure_steps(points: &[(f64, f64)], sums: &Sums, bounds: &[usize]) -> Vec<Step> {
+    bounds
+        .windows(3)
+        .map(|w| {
+            let (before, after) = (sums.mean(w[0], w[1]), sums.mean(w[1], w[2]));
+            let error = sums.squared_error(w[0], w[1]) + sums.squared_error(w[1], w[2]);
+            let deviation = (error / (w[2] - w[0]) as f64).sqrt().max(MIN_NOISE);
+            let effect = (after - before).abs() / deviation;
+            Step {
+                x: points[w[1] - 1].0,
+                x_after: points[w[1]].0,
+                ratio: (after - before).exp(),
+                confidence: 1.0 - (-effect / EFFECT_SCALE).exp(),
+            }
+        })
+        .collect()
+}
+
+/// Standard deviation of the noise, from the median absolute difference of the neighbours
+fn noise(values: &[f64]) -> f64 {
+    let mut differences = values
+        .windows(2)
+        .map(|w| (w[1] - w[0]).abs())
+        .collect::<Vec<_>>();
+    differences.sort_by(f64::total_cmp);
+    let median = differences
+        .get(differences.len() / 2)
+        .copied()
+        .unwrap_or(0.0);
+    // the difference of two values has √2 times the deviation, and the median
+    // absolute deviation is 0.6745 times the standard deviation
+    (median / (0.6745 * 2f64.sqrt())).max(MIN_NOISE)
+}
+
+/// Prefix sums for the mean and the squared error of any segment in constant time
+struct Sums {
+    sum: Vec<f64>,
+    squares: Vec<f64>,
+}
+
+impl Sums {
+    fn new(values: &[f64]) -> Sums {
+        let mut sums = Sums {
+            sum: vec![0.0],
+            squares: vec![0.0],
+        };
+        for value in values {
+            sums.sum.push(sums.sum.last().unwrap() + value);
+            sums.squares
+                .push(sums.squares.last().unwrap() + value * value);
+        }
+        sums
+    }
+
+    fn mean(&self, from: usize, to: usize) -> f64 {
+        (self.sum[to] - self.sum[from]) / (to - from) as f64
+    }
+
+    fn squared_error(&self, from: usize, to: usize) -> f64 {
+        let sum = self.sum[to] - self.sum[from];
+        let squares = self.squares[to] - self.squares[from];
+        // rounding may make the error of a constant segment slightly negative
+        (squares - sum * sum / (to - from) as f64).max(0.0)
+    }
+}
+
+/// Finite points sorted by `x`
+pub(crate) fn sorted(points: &[(f64, f64)]) -> Vec<(f64, f64)> {
+    let mut points = points
+        .iter()
+        .filter(|(x, y)| x.is_finite() && y.is_finite())
+        .copied()
+        .collect::<Vec<_>>();
+    points.sort_by(|a, b| a.0.total_cmp(&b.0));
+    points
+}
+
+/*
+ * TESTS
+ */
+
+#[cfg(test)]
+mod tests {
+    use crate::steps::{rising_steps, steps_at};
+
+    #[test]
+    fn steps_are_reported_once_at_last_fast_size() {
+        // prepare
+        let points = [
+            (1.0, 10.0),
+            (2.0, 11.0),
+            (4.0, 10.0),
+            (8.0, 20.0),
+            (16.0, 21.0),
+            (32.0, 40.0),
+            (64.0, 41.0),
+            (128.0, 90.0),
+            (256.0, 92.0),
+        ];
+
+        // execute
+        let steps = rising_steps(&points, 3)
+            .iter()
+            .map(|step| (step.x, step.x_after))
+            .collect::<Vec<_>>();
+
+        // assert
+        assert_eq!(steps, vec![(4.0, 8.0), (16.0, 32.0), (64.0, 128.0)]);
+    }
+
+    #[test]
+    fn single_outlier_is_not_a_step() {
+        // prepare
+        let points = [(1.0, 10.0), (2.0, 50.0), (4.0, 10.0), (8.0, 11.0)];
+
+        // execute
+        let steps = rising_steps(&points, 3);
+
+        // assert
+        assert!(steps.is_empty());
+    }
+
+    #[test]
+    fn steps_are_measured_at_given_sizes() {
+        // prepare
+        let points = [
+            (1.0, 10.0),
+            (2.0, 11.0),
+            (4.0, 30.0),
+            (8.0, 31.0),
+            (16.0, 80.0),
+            (32.0, 82.0),
+        ];
+
+        // execute
+        let steps = steps_at(&points, &[8.0, 3.0, 64.0, 3.5]);
+
+        // assert
+        let sizes = steps
+            .iter()
+            .map(|step| (step.x, step.x_after))
+            .collect::<Vec<_>>();
+        assert_eq!(sizes, vec![(3.0, 4.0), (8.0, 16.0)]);
+        assert!(steps.iter().all(|step| step.ratio > 2.5));
+        assert!(steps.iter().all(|step| step.confidence > 0.9));
+    }
+}
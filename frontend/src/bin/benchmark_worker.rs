@@ -3,5 +3,6 @@ use yew_agent::PrivateWorker;
 
 fn main() {
     console_error_panic_hook::set_once();
+    wasm_logger::init(wasm_logger::Config::new(log::Level::Info));
     BenchmarkWorker::register();
 }
@@ -1,4 +1,4 @@
-use frontend::clock::{Clock, CLOCK_MESSAGE_READY, CLOCK_MESSAGE_STARTED};
+use frontend::clock::{CounterClock, CLOCK_MESSAGE_READY, CLOCK_MESSAGE_STARTED};
 use gloo_console::info;
 use js_sys::{JsString, SharedArrayBuffer};
 use wasm_bindgen::{prelude::*, JsCast};
@@ -14,7 +14,7 @@ fn main() {
     let onmessage = Closure::wrap(Box::new(move |msg: MessageEvent| {
         info!("Clock worker received shared array buffer");
         let buffer = SharedArrayBuffer::from(msg.data());
-        let clock = Clock::from(buffer);
+        let clock = CounterClock::from(buffer);
 
         scope_clone
             .post_message(&JsString::from(CLOCK_MESSAGE_STARTED))
@@ -0,0 +1,178 @@
+use std::env;
+use std::fs;
+use std::process::exit;
+
+use log::{info, LevelFilter, Log, Metadata, Record};
+use serde_json::Value;
+
+use common::benchmark::{BenchmarkConfig, BenchmarkType, CacheSizeMode};
+use common::dto::result::{ResultDTO, SCHEMA_VERSION};
+use frontend::clock::native::{InstantClock, ThreadClock};
+use frontend::clock::Clock;
+use frontend::prediction::{built_in_catalogue, predict};
+use frontend::upload::FRONTEND_VERSION;
+use frontend::worker::benchmarks::{run_benchmark, BenchmarkParameters};
+use frontend::worker::BenchmarkResult;
+
+const USAGE: &str =
+    "Usage: native_benchmark [--clock counter|instant] [--seed SEED] [--adaptive] [--model MODEL] [BENCHMARK...]
+
+Runs the benchmarks natively and prints the results in the same JSON format
+as the one uploaded by the browser. Benchmarks are given by their names,
+e.g. CacheSize. All benchmarks are run by default. With --adaptive,
+the cache size benchmark searches for the cache boundaries instead of
+using the fixed sweep.";
+
+#[derive(Clone, Copy)]
+enum NativeClock {
+    Counter,
+    Instant,
+}
+
+struct Arguments {
+    clock: NativeClock,
+    parameters: BenchmarkParameters,
+    model: Option<String>,
+    benchmarks: Vec<BenchmarkType>,
+}
+
+fn main() {
+    log::set_logger(&StderrLogger).expect("logger is set only once");
+    log::set_max_level(LevelFilter::Info);
+
+    let arguments = parse_arguments().unwrap_or_else(|error| {
+        eprintln!("{}\n\n{}", error, USAGE);
+        exit(1);
+    });
+
+    let results = arguments
+        .benchmarks
+        .iter()
+        .map(|benchmark| match arguments.clock {
+            // Fresh counter for every benchmark, like in the browser
+            NativeClock::Counter => run(*benchmark, &arguments.parameters, &ThreadClock::start()),
+            NativeClock::Instant => run(*benchmark, &arguments.parameters, &InstantClock::new()),
+        })
+        .collect::<Vec<_>>();
+
+    let clock_name = match arguments.clock {
+        NativeClock::Counter => "counter",
+        NativeClock::Instant => "instant",
+    };
+
+    let mut result = ResultDTO {
+        model: arguments
+            .model
+            .or_else(read_cpu_model)
+            .unwrap_or_else(|| "unknown".to_string()),
+        user_agent: format!("native ({} clock)", clock_name),
+        benchmark_results: results
+            .iter()
+            .map(|result| serde_json::from_str::<Value>(&result.result_json).unwrap())
+            .collect(),
+        times: results.iter().map(|result| result.time).collect(),
+        benchmarks: results.iter().map(|result| result.benchmark).collect(),
+        capabilities: None,
+        seed: Some(arguments.parameters.seed),
+        schema_version: SCHEMA_VERSION.to_string(),
+        frontend_version: FRONTEND_VERSION.to_string(),
+        // the benchmarks are run one after another
+        parameters: Some(BenchmarkConfig {
+            cache_size_mode: arguments.parameters.cache_size_mode,
+            stages: arguments
+                .benchmarks
+                .iter()
+                .map(|benchmark| vec![*benchmark])
+                .collect(),
+        }),
+        model_check: None,
+    };
+    let prediction = predict(&result, &built_in_catalogue());
+    if let Some(summary) = prediction.summary() {
+        info!("{} {}", summary, prediction.comparison());
+    }
+    result.model_check = Some(prediction.check);
+
+    println!("{}", serde_json::to_string(&result).unwrap());
+}
+
+fn run(
+    benchmark: BenchmarkType,
+    parameters: &BenchmarkParameters,
+    clock: &impl Clock,
+) -> BenchmarkResult {
+    info!("Running {} benchmark natively", benchmark);
+    run_benchmark(benchmark, parameters, clock)
+}
+
+fn parse_arguments() -> Result<Arguments, String> {
+    let mut arguments = Arguments {
+        clock: NativeClock::Counter,
+        parameters: BenchmarkParameters {
+            seed: rand::random(),
+            cache_size_mode: CacheSizeMode::Fixed,
+        },
+        model: None,
+        benchmarks: vec![],
+    };
+
+    let mut args = env::args().skip(1);
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "--clock" => {
+                arguments.clock = match args.next().as_deref() {
+                    Some("counter") => NativeClock::Counter,
+                    Some("instant") => NativeClock::Instant,
+                    _ => return Err("Clock must be either counter or instant".to_string()),
+                }
+            }
+            "--seed" => {
+                arguments.parameters.seed = args
+                    .next()
+                    .and_then(|seed| seed.parse().ok())
+                    .ok_or("Seed must be an unsigned 64-bit integer")?
+            }
+            "--adaptive" => arguments.parameters.cache_size_mode = CacheSizeMode::Adaptive,
+            "--model" => arguments.model = Some(args.next().ok_or("Missing model name")?),
+            "--help" | "-h" => {
+                println!("{}", USAGE);
+                exit(0);
+            }
+            name => {
+                let benchmark = serde_json::from_value(Value::String(name.to_string()))
+                    .map_err(|_| format!("Unknown benchmark {}", name))?;
+                arguments.benchmarks.push(benchmark);
+            }
+        }
+    }
+
+    if arguments.benchmarks.is_empty() {
+        arguments.benchmarks = BenchmarkType::ALL.to_vec();
+    }
+
+    Ok(arguments)
+}
+
+/// Reads the CPU model in the same format as the users are asked to provide it on Linux
+fn read_cpu_model() -> Option<String> {
+    let cpuinfo = fs::read_to_string("/proc/cpuinfo").ok()?;
+    cpuinfo
+        .lines()
+        .find(|line| line.starts_with("model name"))
+        .and_then(|line| line.split_once(':'))
+        .map(|(_, model)| model.trim().to_string())
+}
+
+struct StderrLogger;
+
+impl Log for StderrLogger {
+    fn enabled(&self, _metadata: &Metadata) -> bool {
+        true
+    }
+
+    fn log(&self, record: &Record) {
+        eprintln!("[{}] {}", record.level(), record.args());
+    }
+
+    fn flush(&self) {}
+}
@@ -0,0 +1,46 @@
+use js_sys::Reflect;
+use wasm_bindgen::JsValue;
+
+use common::dto::result::{CapabilitiesDTO, ClockMode};
+
+/// Checks which of the browser features required by the benchmarks are available
+/// and selects the clock that can be used with them.
+pub fn probe_capabilities() -> CapabilitiesDTO {
+    let global = js_sys::global();
+
+    let cross_origin_isolated = Reflect::get(&global, &JsValue::from_str("crossOriginIsolated"))
+        .ok()
+        .and_then(|value| value.as_bool())
+        .unwrap_or(false);
+    let shared_array_buffer = has_global(&global, "SharedArrayBuffer");
+    let atomics = has_global(&global, "Atomics");
+    let worker = has_global(&global, "Worker");
+
+    CapabilitiesDTO {
+        cross_origin_isolated,
+        shared_array_buffer,
+        atomics,
+        worker,
+        clock_mode: select_clock_mode(cross_origin_isolated, shared_array_buffer, atomics, worker),
+    }
+}
+
+fn select_clock_mode(
+    cross_origin_isolated: bool,
+    shared_array_buffer: bool,
+    atomics: bool,
+    worker: bool,
+) -> Option<ClockMode> {
+    if !worker {
+        // Benchmarks are always executed in a worker
+        None
+    } else if cross_origin_isolated && shared_array_buffer && atomics {
+        Some(ClockMode::SharedCounter)
+    } else {
+        Some(ClockMode::PerformanceNow)
+    }
+}
+
+fn has_global(global: &JsValue, name: &str) -> bool {
+    Reflect::has(global, &JsValue::from_str(name)).unwrap_or(false)
+}
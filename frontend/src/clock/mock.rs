@@ -0,0 +1,25 @@
+use std::cell::Cell;
+
+use crate::clock::Clock;
+
+/// Deterministic clock for the tests, advancing by a fixed step on every read
+pub struct MockClock {
+    time: Cell<i64>,
+    step: i64,
+}
+
+impl MockClock {
+    pub fn new(step: i64) -> MockClock {
+        MockClock {
+            time: Cell::new(0),
+            step,
+        }
+    }
+}
+
+impl Clock for MockClock {
+    fn read(&self) -> i64 {
+        self.time.set(self.time.get() + self.step);
+        self.time.get()
+    }
+}
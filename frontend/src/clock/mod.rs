@@ -1,23 +1,34 @@
-use js_sys::{Atomics, BigInt64Array, SharedArrayBuffer};
+use js_sys::{Atomics, BigInt64Array, Reflect, SharedArrayBuffer};
+use wasm_bindgen::{JsCast, JsValue};
+use web_sys::Performance;
+
+#[cfg(test)]
+pub mod mock;
+pub mod native;
 
 pub const CLOCK_MESSAGE_READY: &str = "clock_ready";
 pub const CLOCK_MESSAGE_STARTED: &str = "clock_started";
 
+/// Source of time measurements for the benchmarks
+pub trait Clock {
+    fn read(&self) -> i64;
+}
+
 /// Clock implementation using SharedArrayBuffer. Based on
 /// [wasm-rs-shared-channel](https://docs.rs/wasm-rs-shared-channel/0.1.0/src/wasm_rs_shared_channel/spsc.rs.html#128-135)
-pub struct Clock {
+pub struct CounterClock {
     pub shared_buffer: SharedArrayBuffer,
     pub data: BigInt64Array,
 }
 
-impl Clock {
-    pub fn new() -> Clock {
+impl CounterClock {
+    pub fn new() -> CounterClock {
         let shared_buffer = SharedArrayBuffer::new(std::mem::size_of::<i64>() as u32);
         let data = BigInt64Array::new(&shared_buffer);
 
         Atomics::store_bigint(&data, 0, 0).unwrap();
 
-        Clock {
+        CounterClock {
             shared_buffer,
             data,
         }
@@ -27,28 +38,68 @@ impl Clock {
     pub fn increment(&self) {
         let _ = Atomics::add_bigint(&self.data, 0, 1);
     }
+}
 
+impl Default for CounterClock {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Clock for CounterClock {
     #[inline(always)]
-    pub fn read(&self) -> i64 {
+    fn read(&self) -> i64 {
         self.data.get_index(0)
     }
 }
 
-impl From<SharedArrayBuffer> for Clock {
+impl From<SharedArrayBuffer> for CounterClock {
     fn from(value: SharedArrayBuffer) -> Self {
         let data = BigInt64Array::new(&value);
-        Clock {
+        CounterClock {
             shared_buffer: value,
             data,
         }
     }
 }
 
-impl Clone for Clock {
+impl Clone for CounterClock {
     fn clone(&self) -> Self {
-        Clock {
+        CounterClock {
             shared_buffer: self.shared_buffer.clone(),
             data: self.data.clone(),
         }
     }
 }
+
+/// Degraded clock based on `performance.now()`, used when SharedArrayBuffer is not available.
+/// Ticks are microseconds, but the actual resolution is coarsened by the browser.
+#[derive(Clone)]
+pub struct PerformanceClock {
+    performance: Performance,
+}
+
+impl PerformanceClock {
+    pub fn new() -> PerformanceClock {
+        // Works both in the window and in the worker scope
+        let performance = Reflect::get(&js_sys::global(), &JsValue::from_str("performance"))
+            .expect("performance should be available in the global scope");
+
+        PerformanceClock {
+            performance: performance.unchecked_into(),
+        }
+    }
+}
+
+impl Default for PerformanceClock {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Clock for PerformanceClock {
+    #[inline(always)]
+    fn read(&self) -> i64 {
+        (self.performance.now() * 1000.0) as i64
+    }
+}
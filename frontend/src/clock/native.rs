@@ -0,0 +1,74 @@
+use std::sync::atomic::{AtomicBool, AtomicI64, Ordering};
+use std::sync::Arc;
+use std::thread;
+use std::time::Instant;
+
+use crate::clock::Clock;
+
+/// Native counterpart of the shared counter clock: a dedicated thread increments
+/// the counter in a loop until the clock is dropped.
+pub struct ThreadClock {
+    counter: Arc<AtomicI64>,
+    running: Arc<AtomicBool>,
+}
+
+impl ThreadClock {
+    pub fn start() -> ThreadClock {
+        let counter = Arc::new(AtomicI64::new(0));
+        let running = Arc::new(AtomicBool::new(true));
+
+        let counter_clone = counter.clone();
+        let running_clone = running.clone();
+        thread::spawn(move || {
+            while running_clone.load(Ordering::Relaxed) {
+                counter_clone.fetch_add(1, Ordering::Relaxed);
+            }
+        });
+
+        // Wait until the counting thread is scheduled
+        while counter.load(Ordering::Relaxed) == 0 {
+            std::hint::spin_loop();
+        }
+
+        ThreadClock { counter, running }
+    }
+}
+
+impl Clock for ThreadClock {
+    #[inline(always)]
+    fn read(&self) -> i64 {
+        self.counter.load(Ordering::Relaxed)
+    }
+}
+
+impl Drop for ThreadClock {
+    fn drop(&mut self) {
+        self.running.store(false, Ordering::Relaxed);
+    }
+}
+
+/// Ground truth clock based on the monotonic system clock. Ticks are nanoseconds.
+pub struct InstantClock {
+    origin: Instant,
+}
+
+impl InstantClock {
+    pub fn new() -> InstantClock {
+        InstantClock {
+            origin: Instant::now(),
+        }
+    }
+}
+
+impl Default for InstantClock {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Clock for InstantClock {
+    #[inline(always)]
+    fn read(&self) -> i64 {
+        self.origin.elapsed().as_nanos() as i64
+    }
+}
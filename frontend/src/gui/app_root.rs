@@ -2,35 +2,65 @@ use std::collections::VecDeque;
 
 use std::rc::Rc;
 
-use gloo_net::http::Request;
+use gloo_timers::future::TimeoutFuture;
 use serde_json::value::Value;
 use yew::prelude::*;
 use yew_agent::{Bridge, Bridged};
 use yew_bootstrap::util::*;
 
-use common::dto::result::ResultDTO;
+use common::benchmark::{BenchmarkConfig, BenchmarkType, CacheSizeMode};
+use common::dto::cpu::{CpuSpecDTO, ModelSuggestionsDTO};
+use common::dto::result::{CapabilitiesDTO, ResultDTO, SCHEMA_VERSION};
 
+use crate::capabilities::probe_capabilities;
 use crate::gui::renderers::*;
-use crate::worker::{BenchmarkInput, BenchmarkResult, BenchmarkType, BenchmarkWorker};
+use crate::prediction::{built_in_catalogue, fetch_catalogue, predict, Prediction};
+use crate::scheduler::BenchmarkPlan;
+use crate::session::BenchmarkSession;
+use crate::suggestion::{fetch_suggestions, SUGGESTION_DELAY_MS};
+use crate::upload::{
+    dequeue_result, is_queued, queue_result, upload_pending_results, upload_with_retry,
+    UploadError, FRONTEND_VERSION, MAX_ATTEMPTS,
+};
+use crate::worker::benchmarks::BenchmarkParameters;
+use crate::worker::{BenchmarkInput, BenchmarkResult, BenchmarkWorker};
 
 pub enum AppRootMessage {
     ChangeModel(String),
+    /// Suggestions of the request with the given number
+    SuggestionsLoaded(u32, Option<ModelSuggestionsDTO>),
+    CatalogueLoaded(Vec<CpuSpecDTO>),
+    SelectModel(String),
     StartBenchmarks,
+    ResumeBenchmarks,
+    DiscardSession,
     BenchmarkComplete(BenchmarkResult),
-    BenchmarksFinished(u16, String),
+    UploadRetrying(u32, UploadError, u32),
+    UploadFinished(Result<(), UploadError>),
 }
 
 pub enum ExperimentResult {
     NotStarted,
     Running,
     Success,
+    /// Upload failed, the results are stored to be uploaded on the next visit
+    Queued,
     Error,
 }
 
 pub struct AppRoot {
-    bridge: Box<dyn Bridge<BenchmarkWorker>>,
+    bridges: Vec<Box<dyn Bridge<BenchmarkWorker>>>,
+    capabilities: CapabilitiesDTO,
+    session: BenchmarkSession,
+    /// Unfinished session of a previous visit, which the user can resume
+    resumable: bool,
 
     model_input: String,
+    /// Suggestions for the current model input, once they are loaded
+    model_suggestions: Option<ModelSuggestionsDTO>,
+    /// Number of the last suggestion request, the responses of the older ones are ignored
+    suggestion_request: u32,
+    suggestions_open: bool,
     status_label: String,
     button_disabled: bool,
     input_disabled: bool,
@@ -38,9 +68,14 @@ pub struct AppRoot {
     finished_benchmarks: usize,
 
     experiment_result: ExperimentResult,
-
-    benchmark_results: Vec<BenchmarkResult>,
-    remaining_benchmarks: VecDeque<BenchmarkType>,
+    /// CPU estimated from the results of the last run
+    prediction: Option<Prediction>,
+    /// Catalogue the prediction is compared with, the built-in one until the stored one is loaded
+    catalogue: Rc<Vec<CpuSpecDTO>>,
+
+    plan: BenchmarkPlan,
+    remaining_stages: VecDeque<Vec<BenchmarkType>>,
+    running_benchmarks: usize,
 }
 
 impl Component for AppRoot {
@@ -48,21 +83,76 @@ impl Component for AppRoot {
     type Properties = ();
 
     fn create(ctx: &Context<Self>) -> Self {
+        // results which could not be uploaded during the previous visits
+        wasm_bindgen_futures::spawn_local(upload_pending_results());
         let link = ctx.link().clone();
-        let worker_result_callback =
-            move |result| link.send_message(AppRootMessage::BenchmarkComplete(result));
+        wasm_bindgen_futures::spawn_local(async move {
+            if let Some(specs) = fetch_catalogue().await {
+                link.send_message(AppRootMessage::CatalogueLoaded(specs));
+            }
+        });
+
+        let mut stored_session = BenchmarkSession::load();
+        // a complete run whose upload failed is queued and uploaded with the pending results
+        if let Some(session) = &stored_session {
+            let times = session
+                .results
+                .iter()
+                .map(|result| result.time)
+                .collect::<Vec<_>>();
+            if session.is_complete() && is_queued(Some(session.parameters.seed), &times) {
+                BenchmarkSession::clear();
+                stored_session = None;
+            }
+        }
+        // resumed session continues with its own plan
+        let plan = match &stored_session {
+            Some(session) => session.plan.clone(),
+            None => BenchmarkPlan::new(&BenchmarkType::ALL, get_worker_pool_size()),
+        };
+
+        // every bridge spawns a separate worker
+        let bridges = (0..plan.workers())
+            .map(|_| {
+                let link = ctx.link().clone();
+                let worker_result_callback =
+                    move |result| link.send_message(AppRootMessage::BenchmarkComplete(result));
+                BenchmarkWorker::bridge(Rc::new(worker_result_callback))
+            })
+            .collect();
+
+        let resumable = stored_session.is_some();
+        let session = stored_session.unwrap_or_else(|| {
+            BenchmarkSession::new(
+                String::default(),
+                BenchmarkParameters {
+                    seed: 0,
+                    cache_size_mode: CacheSizeMode::Fixed,
+                },
+                plan.clone(),
+            )
+        });
 
         AppRoot {
-            bridge: BenchmarkWorker::bridge(Rc::new(worker_result_callback)),
-            model_input: String::default(),
+            bridges,
+            capabilities: probe_capabilities(),
+            model_input: session.model.clone(),
+            model_suggestions: None,
+            suggestion_request: 0,
+            suggestions_open: false,
+            session,
+            resumable,
             status_label: String::default(),
             button_disabled: false,
             input_disabled: false,
-            benchmark_results: Vec::new(),
-            remaining_benchmarks: VecDeque::new(),
+            plan,
+            remaining_stages: VecDeque::new(),
+            running_benchmarks: 0,
             total_benchmarks: 0,
             finished_benchmarks: 0,
             experiment_result: ExperimentResult::NotStarted,
+            prediction: None,
+            catalogue: built_in_catalogue(),
         }
     }
 
@@ -70,38 +160,94 @@ impl Component for AppRoot {
         match msg {
             AppRootMessage::ChangeModel(new_model) => {
                 self.model_input = new_model;
+                self.suggestions_open = true;
+                self.request_suggestions(ctx, SUGGESTION_DELAY_MS);
+                true
+            }
+            AppRootMessage::SuggestionsLoaded(request, suggestions) => {
+                if request != self.suggestion_request {
+                    return false;
+                }
+                self.model_suggestions = suggestions;
+                true
+            }
+            AppRootMessage::CatalogueLoaded(specs) => {
+                self.catalogue = Rc::new(specs);
+                false
+            }
+            AppRootMessage::SelectModel(model) => {
+                self.model_input = model;
+                self.suggestions_open = false;
+                self.request_suggestions(ctx, 0);
                 true
             }
             AppRootMessage::StartBenchmarks => {
-                self.start_benchmarks();
+                self.start_benchmarks(ctx);
+                true
+            }
+            AppRootMessage::ResumeBenchmarks => {
+                self.resume_benchmarks(ctx);
+                true
+            }
+            AppRootMessage::DiscardSession => {
+                BenchmarkSession::clear();
+                self.resumable = false;
                 true
             }
             AppRootMessage::BenchmarkComplete(result) => {
                 self.handle_benchmark_complete(ctx, result);
                 true
             }
-            AppRootMessage::BenchmarksFinished(status, status_text) => {
-                self.handle_benchmarks_finished(status, status_text);
+            AppRootMessage::UploadRetrying(retry, error, delay) => {
+                self.status_label = format!(
+                    "Upload failed: {}. Retrying in {} s ({}/{})...",
+                    error,
+                    delay / 1000,
+                    retry,
+                    MAX_ATTEMPTS - 1
+                );
+                true
+            }
+            AppRootMessage::UploadFinished(outcome) => {
+                self.handle_upload_finished(outcome);
                 true
             }
         }
     }
 
     fn view(&self, ctx: &Context<Self>) -> Html {
+        if self.capabilities.clock_mode.is_none() {
+            return html! {
+            <>
+                {include_cdn()}
+                {render_unsupported_browser(&self.capabilities)}
+                {render_footer()}
+            </>
+            };
+        }
+
         let button_disabled = self.button_disabled || self.model_input.is_empty();
+        let resumable_session = self.resumable.then_some(&self.session);
 
         html! {
         <>
             {include_cdn()}
             {render_main_container(
+                &self.capabilities,
+                &self.plan,
+                resumable_session,
                 &self.model_input,
                 self.input_disabled,
+                self.model_suggestions.as_ref(),
+                self.suggestions_open,
                 ctx,
                 button_disabled,
                 self.finished_benchmarks,
                 self.total_benchmarks,
                 &self.status_label,
-                &self.experiment_result
+                &self.experiment_result,
+                self.prediction.as_ref(),
+                &self.session.results
             )}
             {include_cdn_js()}
             {render_footer()}
@@ -111,12 +257,51 @@ impl Component for AppRoot {
 }
 
 impl AppRoot {
-    fn start_benchmarks(&mut self) {
+    fn start_benchmarks(&mut self, ctx: &Context<Self>) {
         self.experiment_result = ExperimentResult::Running;
+        self.resumable = false;
         self.disable_controls(true);
         self.initialize_benchmark_data();
 
-        self.start_next_benchmark_or_send(None);
+        self.start_next_stage_or_send(ctx);
+    }
+
+    fn resume_benchmarks(&mut self, ctx: &Context<Self>) {
+        self.experiment_result = ExperimentResult::Running;
+        self.resumable = false;
+        self.disable_controls(true);
+        self.remaining_stages = self.session.remaining_stages();
+
+        self.total_benchmarks = BenchmarkType::ALL.len();
+        self.finished_benchmarks = self.session.results.len();
+
+        // a complete session only needs to be uploaded
+        self.start_next_stage_or_send(ctx);
+    }
+
+    /// Loads the suggestions for the model input after the delay, unless the input changes meanwhile
+    fn request_suggestions(&mut self, ctx: &Context<Self>, delay: u32) {
+        self.suggestion_request += 1;
+        self.model_suggestions = None;
+        if self.model_input.trim().is_empty() {
+            return;
+        }
+
+        let request = self.suggestion_request;
+        let model = self.model_input.clone();
+        let link = ctx.link().clone();
+        wasm_bindgen_futures::spawn_local(async move {
+            TimeoutFuture::new(delay).await;
+            // a newer request was started while waiting
+            if link
+                .get_component()
+                .map_or(false, |root| root.suggestion_request != request)
+            {
+                return;
+            }
+            let suggestions = fetch_suggestions(&model).await;
+            link.send_message(AppRootMessage::SuggestionsLoaded(request, suggestions));
+        });
     }
 
     fn disable_controls(&mut self, disabled: bool) {
@@ -125,80 +310,152 @@ impl AppRoot {
     }
 
     fn initialize_benchmark_data(&mut self) {
-        self.benchmark_results = vec![];
-        self.remaining_benchmarks = VecDeque::from(vec![
-            BenchmarkType::PageSize,
-            BenchmarkType::CacheSize,
-            BenchmarkType::TlbSize,
-            BenchmarkType::CacheAssociativity,
-            BenchmarkType::SinglePerformance,
-        ]);
-
-        self.total_benchmarks = self.remaining_benchmarks.len();
+        let parameters = BenchmarkParameters {
+            seed: get_query_parameter("seed")
+                .and_then(|seed| seed.parse().ok())
+                .unwrap_or_else(rand::random),
+            cache_size_mode: match get_query_parameter("cache_size").as_deref() {
+                Some("adaptive") => CacheSizeMode::Adaptive,
+                _ => CacheSizeMode::Fixed,
+            },
+        };
+        self.session =
+            BenchmarkSession::new(self.model_input.clone(), parameters, self.plan.clone());
+        self.session.save();
+        self.remaining_stages = self.session.remaining_stages();
+
+        self.total_benchmarks = BenchmarkType::ALL.len();
+        self.finished_benchmarks = 0;
     }
 
-    fn start_next_benchmark_or_send(&mut self, ctx: Option<&Context<Self>>) {
-        if let Some(benchmark) = self.remaining_benchmarks.pop_front() {
-            self.update_status_and_progress(benchmark);
-            self.bridge.send(BenchmarkInput {
-                page_origin: get_page_origin(),
-                benchmark,
-            });
-        } else if let Some(ctx) = ctx {
+    fn start_next_stage_or_send(&mut self, ctx: &Context<Self>) {
+        if let Some(stage) = self.remaining_stages.pop_front() {
+            self.update_status_and_progress(&stage);
+            self.running_benchmarks = stage.len();
+
+            let inputs = stage
+                .iter()
+                .map(|benchmark| self.benchmark_input(*benchmark))
+                .collect::<Vec<_>>();
+            for (bridge, input) in self.bridges.iter_mut().zip(inputs) {
+                bridge.send(input);
+            }
+        } else {
             self.send_result(ctx);
         }
     }
 
-    fn update_status_and_progress(&mut self, benchmark: BenchmarkType) {
-        self.status_label = format!("Running: {}", benchmark);
-        self.finished_benchmarks += 1;
+    fn benchmark_input(&self, benchmark: BenchmarkType) -> BenchmarkInput {
+        BenchmarkInput {
+            page_origin: get_page_origin(),
+            benchmark,
+            clock_mode: self
+                .capabilities
+                .clock_mode
+                .expect("benchmarks run only in supported browsers"),
+            parameters: self.session.parameters,
+        }
+    }
+
+    fn update_status_and_progress(&mut self, stage: &[BenchmarkType]) {
+        let names = stage
+            .iter()
+            .map(|benchmark| benchmark.to_string())
+            .collect::<Vec<_>>();
+        self.status_label = format!("Running: {}", names.join(", "));
+        self.finished_benchmarks += stage.len();
     }
 
     fn handle_benchmark_complete(&mut self, ctx: &Context<Self>, result: BenchmarkResult) {
-        self.benchmark_results.push(result);
-        self.start_next_benchmark_or_send(Some(ctx));
+        self.session.results.push(result);
+        self.session.save();
+        self.running_benchmarks -= 1;
+
+        if self.running_benchmarks == 0 {
+            self.start_next_stage_or_send(ctx);
+        }
     }
 
     fn send_result(&mut self, ctx: &Context<Self>) {
+        // parallel benchmarks finish in any order, but the results are uploaded in a fixed one
+        self.session.results.sort_by_key(|result| {
+            BenchmarkType::ALL
+                .iter()
+                .position(|benchmark| *benchmark == result.benchmark)
+        });
+        // stored in the order of the upload, so that the queued result of the session is recognized
+        self.session.save();
         let (results, times) = self.parse_results();
 
-        let result = ResultDTO {
-            model: self.model_input.clone(),
+        let mut result = ResultDTO {
+            model: self.session.model.clone(),
             user_agent: get_user_agent().unwrap_or_else(|| "unknown".to_string()),
             benchmark_results: results,
             times,
+            benchmarks: self
+                .session
+                .results
+                .iter()
+                .map(|result| result.benchmark)
+                .collect(),
+            capabilities: Some(self.capabilities.clone()),
+            seed: Some(self.session.parameters.seed),
+            schema_version: SCHEMA_VERSION.to_string(),
+            frontend_version: FRONTEND_VERSION.to_string(),
+            parameters: Some(BenchmarkConfig {
+                cache_size_mode: self.session.parameters.cache_size_mode,
+                stages: self.session.plan.stages.clone(),
+            }),
+            model_check: None,
         };
+        let prediction = predict(&result, &self.catalogue);
+        result.model_check = Some(prediction.check.clone());
+        self.prediction = Some(prediction);
 
         let link = ctx.link().clone();
 
         self.status_label = String::from("Uploading results...");
 
         wasm_bindgen_futures::spawn_local(async move {
-            let response = Request::post("/api/result/upload")
-                .json(&result)
-                .unwrap()
-                .send()
-                .await
-                .unwrap();
-
-            link.send_message(AppRootMessage::BenchmarksFinished(
-                response.status(),
-                response.status_text(),
-            ));
+            let retry_link = link.clone();
+            let outcome = upload_with_retry(&result, |retry, error, delay| {
+                retry_link.send_message(AppRootMessage::UploadRetrying(retry, error.clone(), delay))
+            })
+            .await;
+
+            match &outcome {
+                Ok(()) => dequeue_result(&result),
+                Err(error) if error.is_rejected() => {}
+                Err(_) => queue_result(result),
+            }
+            link.send_message(AppRootMessage::UploadFinished(outcome));
         });
     }
 
-    fn handle_benchmarks_finished(&mut self, status: u16, status_text: String) {
+    fn handle_upload_finished(&mut self, outcome: Result<(), UploadError>) {
         self.disable_controls(false);
 
-        if status == 200 {
-            // Success
-            self.experiment_result = ExperimentResult::Success;
-            self.status_label = String::from("Benchmarking finished");
-        } else {
-            // Something is wrong
-            self.experiment_result = ExperimentResult::Error;
-            self.status_label = format!("Error: {}. Please try again.", status_text);
+        match outcome {
+            Ok(()) => {
+                BenchmarkSession::clear();
+                self.experiment_result = ExperimentResult::Success;
+                self.status_label = String::from("Benchmarking finished");
+            }
+            Err(error) if error.is_rejected() => {
+                // uploading the same results again can't succeed
+                BenchmarkSession::clear();
+                self.experiment_result = ExperimentResult::Error;
+                self.status_label = format!("Upload failed: {}.", error);
+            }
+            Err(error) => {
+                // the results are queued, the session allows to retry the upload right away
+                self.resumable = true;
+                self.experiment_result = ExperimentResult::Queued;
+                self.status_label = format!(
+                    "Upload failed: {}. The results are saved in your browser and will be uploaded on your next visit, or you can retry now.",
+                    error
+                );
+            }
         }
     }
 
@@ -206,7 +463,7 @@ impl AppRoot {
         let mut results = vec![];
         let mut times = vec![];
 
-        for result in self.benchmark_results.iter() {
+        for result in self.session.results.iter() {
             let value = serde_json::from_str::<Value>(result.result_json.clone().as_str()).unwrap();
 
             // TODO: Cloning the whole result JSON is not very optimal
@@ -220,11 +477,14 @@ impl AppRoot {
 
 fn get_user_agent() -> Option<String> {
     let window = web_sys::window().expect("Missing window");
-    let user_agent = window.navigator().user_agent();
-    match user_agent {
-        Ok(user_agent) => Some(user_agent),
-        Err(_) => None,
-    }
+    window.navigator().user_agent().ok()
+}
+
+/// Every benchmark needs two threads - one for the benchmark worker and one for the clock worker
+fn get_worker_pool_size() -> usize {
+    let window = web_sys::window().expect("Missing window");
+    let threads = window.navigator().hardware_concurrency() as usize;
+    (threads / 2).max(1)
 }
 
 fn get_page_origin() -> String {
@@ -234,3 +494,12 @@ fn get_page_origin() -> String {
         .origin()
         .expect("Missing origin information")
 }
+
+/// Parameters of the run can be overridden in the query string, e.g. `seed` to replay a previous run
+/// or `cache_size=adaptive` to use the adaptive cache size search
+fn get_query_parameter(name: &str) -> Option<String> {
+    let window = web_sys::window().expect("Missing window");
+    let search = window.location().search().ok()?;
+    let params = web_sys::UrlSearchParams::new_with_str(&search).ok()?;
+    params.get(name)
+}
@@ -1,3 +1,4 @@
 pub mod app_root;
 mod components;
 mod renderers;
+mod result_charts;
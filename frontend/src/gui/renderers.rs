@@ -2,29 +2,59 @@ use web_sys::HtmlInputElement;
 use yew::prelude::*;
 use yew::{html, Context, Html};
 use yew_bootstrap::component::*;
+use yew_bootstrap::util::Color;
+
+use common::dto::cpu::ModelSuggestionsDTO;
+use common::dto::result::{CapabilitiesDTO, ClockMode};
 
 use crate::gui::app_root::AppRootMessage;
 use crate::gui::app_root::{AppRoot, ExperimentResult};
 use crate::gui::components::*;
+use crate::gui::result_charts::render_result_charts;
+use crate::prediction::Prediction;
+use crate::scheduler::BenchmarkPlan;
+use crate::session::BenchmarkSession;
+use crate::suggestion::{unknown_model_warning, visible_suggestions};
+use crate::worker::BenchmarkResult;
 
+#[allow(clippy::too_many_arguments)]
 pub fn render_main_container(
+    capabilities: &CapabilitiesDTO,
+    plan: &BenchmarkPlan,
+    resumable_session: Option<&BenchmarkSession>,
     model_input: &str,
     input_disabled: bool,
+    model_suggestions: Option<&ModelSuggestionsDTO>,
+    suggestions_open: bool,
     ctx: &Context<AppRoot>,
     button_disabled: bool,
     finished_benchmarks: usize,
     total_benchmarks: usize,
     status_label: &str,
     experiment_result: &ExperimentResult,
+    prediction: Option<&Prediction>,
+    results: &[BenchmarkResult],
 ) -> Html {
+    let finished = !matches!(
+        experiment_result,
+        ExperimentResult::NotStarted | ExperimentResult::Running
+    );
+
     html! {
         <Container>
             {render_header()}
-            {render_cpu_model_instructions(model_input.to_string(), input_disabled, ctx)}
+            {render_degraded_clock_warning(capabilities)}
+            {render_resume_prompt(resumable_session, ctx)}
+            {render_cpu_model_instructions(model_input.to_string(), input_disabled, model_suggestions, suggestions_open, ctx)}
             {render_benchmark_instructions()}
+            {render_benchmark_plan(plan)}
             {render_start_button(ctx, button_disabled)}
             {render_progress_bar(experiment_result, finished_benchmarks, total_benchmarks, status_label.to_string())}
             {render_next_experiment_button(experiment_result)}
+            if finished {
+                {render_prediction(prediction)}
+                {render_result_charts(results)}
+            }
         </Container>
     }
 }
@@ -57,9 +87,118 @@ fn render_header() -> Html {
     }
 }
 
+pub fn render_unsupported_browser(capabilities: &CapabilitiesDTO) -> Html {
+    html! {
+        <Container>
+            {render_header()}
+            <div style="padding-left: 2rem; padding-right: 2rem; padding-top: 3rem">
+                <Alert style={Color::Danger}>
+                    <h5>{ "Your browser is not supported" }</h5>
+                    <p>
+                        { "The benchmarks cannot run in this browser, because it is missing
+                        the following features:" }
+                    </p>
+                    {render_missing_features(capabilities)}
+                    <p style="margin-bottom: 0">
+                        { "Please open this page in the latest version of Firefox or a Chrome-based browser." }
+                    </p>
+                </Alert>
+            </div>
+        </Container>
+    }
+}
+
+fn render_degraded_clock_warning(capabilities: &CapabilitiesDTO) -> Html {
+    if capabilities.clock_mode != Some(ClockMode::PerformanceNow) {
+        return html! {};
+    }
+
+    html! {
+        <div style="padding-left: 2rem; padding-right: 2rem; padding-top: 2rem">
+            <Alert style={Color::Warning}>
+                <p>
+                    { "Your browser does not allow precise time measurements, because it is missing
+                    the following features:" }
+                </p>
+                {render_missing_features(capabilities)}
+                <p style="margin-bottom: 0">
+                    { "The benchmarks will still run, but their results will be less accurate." }
+                </p>
+            </Alert>
+        </div>
+    }
+}
+
+fn render_resume_prompt(session: Option<&BenchmarkSession>, ctx: &Context<AppRoot>) -> Html {
+    let Some(session) = session else {
+        return html! {};
+    };
+
+    let finished = session.results.len();
+    let total = session
+        .plan
+        .stages
+        .iter()
+        .map(|stage| stage.len())
+        .sum::<usize>();
+    let description = if session.is_complete() {
+        "All benchmarks of your previous run have finished, but their results were not uploaded yet.".to_string()
+    } else {
+        format!(
+            "Your previous run for the CPU model {} was interrupted after {} of {} benchmarks.",
+            session.model, finished, total
+        )
+    };
+
+    html! {
+        <div style="padding-left: 2rem; padding-right: 2rem; padding-top: 2rem">
+            <Alert style={Color::Info}>
+                <p>{ description }</p>
+                <button
+                    id="resumeButton"
+                    class="btn btn-primary"
+                    type="button"
+                    onclick={ctx.link().callback(|_| { AppRootMessage::ResumeBenchmarks })}
+                >
+                    { "Resume" }
+                </button>
+                <button
+                    id="discardButton"
+                    class="btn btn-outline-secondary"
+                    style="margin-left: 1rem"
+                    type="button"
+                    onclick={ctx.link().callback(|_| { AppRootMessage::DiscardSession })}
+                >
+                    { "Discard" }
+                </button>
+            </Alert>
+        </div>
+    }
+}
+
+fn render_missing_features(capabilities: &CapabilitiesDTO) -> Html {
+    let features = [
+        ("Cross-origin isolation", capabilities.cross_origin_isolated),
+        ("SharedArrayBuffer", capabilities.shared_array_buffer),
+        ("Atomics", capabilities.atomics),
+        ("Web Workers", capabilities.worker),
+    ];
+
+    html! {
+        <ul>
+            { for features
+                .iter()
+                .filter(|(_, available)| !available)
+                .map(|(name, _)| html! { <li>{ name }</li> }) }
+        </ul>
+    }
+}
+
 pub fn render_cpu_model_instructions(
     model_input: String,
     input_disabled: bool,
+    model_suggestions: Option<&ModelSuggestionsDTO>,
+    suggestions_open: bool,
     ctx: &Context<AppRoot>,
 ) -> Html {
     html! {
@@ -176,7 +315,7 @@ pub fn render_cpu_model_instructions(
                     </AccordionCollapse>
                 </AccordionItem>
             </Accordion>
-            <div style="padding-left: 2rem; padding-right: 2rem; padding-top: 2rem">
+            <div class="position-relative" style="padding-left: 2rem; padding-right: 2rem; padding-top: 2rem">
                 <label for="model" class="form-label">
                     <strong>{ "CPU model" }</strong>
                 </label>
@@ -194,12 +333,55 @@ pub fn render_cpu_model_instructions(
                     })}
                     disabled={input_disabled}
                     required=true
+                    autocomplete="off"
                 />
+                if !input_disabled {
+                    {render_model_suggestions(model_suggestions, suggestions_open, ctx)}
+                }
             </div>
         </>
     }
 }
 
+fn render_model_suggestions(
+    model_suggestions: Option<&ModelSuggestionsDTO>,
+    suggestions_open: bool,
+    ctx: &Context<AppRoot>,
+) -> Html {
+    let Some(model_suggestions) = model_suggestions else {
+        return html! {};
+    };
+    let suggestions = visible_suggestions(model_suggestions);
+
+    html! {
+        <>
+            if suggestions_open && !suggestions.is_empty() {
+                <ul id="modelSuggestions" class="dropdown-menu show" style="left: 2rem; right: 2rem">
+                    { for suggestions.iter().map(|suggestion| {
+                        let model = suggestion.model.clone();
+                        let label = if suggestion.in_catalogue { "known CPU" } else { "submitted before" };
+                        html! {
+                            <li>
+                                <button
+                                    class="dropdown-item d-flex justify-content-between"
+                                    type="button"
+                                    onclick={ctx.link().callback(move |_| AppRootMessage::SelectModel(model.clone()))}
+                                >
+                                    <span>{ &suggestion.model }</span>
+                                    <small class="text-muted">{ label }</small>
+                                </button>
+                            </li>
+                        }
+                    }) }
+                </ul>
+            }
+            if let Some(warning) = unknown_model_warning(model_suggestions) {
+                <div id="modelWarning" class="form-text text-warning">{ warning }</div>
+            }
+        </>
+    }
+}
+
 fn render_benchmark_instructions() -> Html {
     html! {
         <>
@@ -225,6 +407,25 @@ fn render_benchmark_instructions() -> Html {
     }
 }
 
+fn render_benchmark_plan(plan: &BenchmarkPlan) -> Html {
+    let minutes = (plan.expected_duration() / 60.0).ceil();
+
+    html! {
+        <div style="padding-left: 2rem; padding-right: 2rem">
+            <p>
+                { format!("The benchmarks will take about {} minutes. They are executed in the following order:", minutes) }
+            </p>
+            <ol>
+                { for plan.stages.iter().map(|stage| {
+                    let names = stage.iter().map(|benchmark| benchmark.to_string()).collect::<Vec<_>>();
+                    let parallel = if stage.len() > 1 { " (in parallel)" } else { "" };
+                    html! { <li>{ names.join(", ") }{ parallel }</li> }
+                }) }
+            </ol>
+        </div>
+    }
+}
+
 fn render_start_button(ctx: &Context<AppRoot>, button_disabled: bool) -> Html {
     html! {
         <div style="display: flex; justify-content: center; margin: 3rem">
@@ -244,7 +445,7 @@ fn render_start_button(ctx: &Context<AppRoot>, button_disabled: bool) -> Html {
 
 fn render_next_experiment_button(experiment_result: &ExperimentResult) -> Html {
     let button_visibility = match experiment_result {
-        ExperimentResult::Success => "visible",
+        ExperimentResult::Success | ExperimentResult::Queued => "visible",
         _ => "hidden",
     };
     html! {
@@ -261,6 +462,28 @@ fn render_next_experiment_button(experiment_result: &ExperimentResult) -> Html {
     }
 }
 
+/// What the benchmarks tell about the CPU, shown to the user after the run
+fn render_prediction(prediction: Option<&Prediction>) -> Html {
+    let Some(prediction) = prediction else {
+        return html! {};
+    };
+    let Some(summary) = prediction.summary() else {
+        return html! {};
+    };
+    let color = if prediction.check.is_mismatch() {
+        Color::Warning
+    } else {
+        Color::Info
+    };
+
+    html! {
+        <Alert style={color}>
+            <p class="mb-1"><strong>{ summary }</strong></p>
+            <p class="mb-0">{ prediction.comparison() }</p>
+        </Alert>
+    }
+}
+
 fn render_progress_bar(
     experiment_result: &ExperimentResult,
     finished_benchmarks: usize,
@@ -272,6 +495,7 @@ fn render_progress_bar(
     let progress_bar_classes = match experiment_result {
         ExperimentResult::Running => "progress-bar-striped progress-bar-animated",
         ExperimentResult::Success => "bg-success",
+        ExperimentResult::Queued => "bg-warning",
         ExperimentResult::Error => "bg-danger",
         _ => "",
     };
@@ -0,0 +1,40 @@
+use serde_json::Value;
+use yew::{html, Html};
+
+use common::chart::svg::render_svg;
+use common::chart::Chart;
+
+use crate::worker::BenchmarkResult;
+
+/// Charts of the results of the finished run. They are drawn as inline SVG, the same one
+/// the backend stores for the uploaded results, so they work without network.
+pub fn render_result_charts(results: &[BenchmarkResult]) -> Html {
+    if results.is_empty() {
+        return html! {};
+    }
+
+    html! {
+        <div style="padding-left: 2rem; padding-right: 2rem">
+            <h5 style="padding-top: 1rem">{ "Your results" }</h5>
+            { for results.iter().filter_map(|result| {
+                let value = serde_json::from_str::<Value>(&result.result_json).ok()?;
+                Some(render_chart(&Chart::new(result.benchmark, &value)))
+            }) }
+        </div>
+    }
+}
+
+fn render_chart(chart: &Chart) -> Html {
+    let document = web_sys::window()
+        .and_then(|window| window.document())
+        .expect("Missing document");
+    let container = document
+        .create_element("div")
+        .expect("div element can be created");
+    container
+        .set_attribute("style", "padding-top: 1rem; max-width: 50rem")
+        .ok();
+    container.set_inner_html(&render_svg(chart));
+
+    Html::VRef(container.into())
+}
@@ -1,3 +1,9 @@
+pub mod capabilities;
 pub mod clock;
 pub mod gui;
+pub mod prediction;
+pub mod scheduler;
+pub mod session;
+pub mod suggestion;
+pub mod upload;
 pub mod worker;
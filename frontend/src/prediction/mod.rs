@@ -0,0 +1,242 @@
+use std::rc::Rc;
+
+use gloo_net::http::Request;
+use log::warn;
+
+use common::analysis::{
+    check_model, extract_features, likely_microarchitecture, Estimate, Features, CHECK_CONFIDENCE,
+};
+use common::cpu::{find_cpu_spec, parse_cpu_specs};
+use common::dto::cpu::{CpuSpecDTO, StoredCpuSpecDTO};
+use common::dto::result::{CpuProperty, ModelCheckDTO, ResultDTO};
+use common::model::normalize_model;
+
+/// CPU catalogue built into the frontend, so the prediction works without the backend
+const CATALOGUE: &str = include_str!("../../../classification/cpus.csv");
+const CATALOGUE_URL: &str = "/api/cpu";
+
+thread_local! {
+    // the built-in catalogue is checked by the tests
+    static BUILT_IN_CATALOGUE: Rc<Vec<CpuSpecDTO>> =
+        Rc::new(parse_cpu_specs(CATALOGUE).unwrap_or_default());
+}
+
+const KIB: u64 = 1024;
+const MIB: u64 = 1024 * KIB;
+
+/// CPU estimated on the device from the finished benchmarks
+#[derive(Clone, Debug, PartialEq)]
+pub struct Prediction {
+    pub features: Features,
+    /// Family of the microarchitecture, if the cache sizes point to one
+    pub microarchitecture: Option<String>,
+    /// Comparison with the specification of the entered model
+    pub check: ModelCheckDTO,
+}
+
+/// Catalogue built into the frontend, parsed once. Used until the stored one is loaded
+/// and by the native runner.
+pub fn built_in_catalogue() -> Rc<Vec<CpuSpecDTO>> {
+    BUILT_IN_CATALOGUE.with(Rc::clone)
+}
+
+/// Catalogue stored in the backend, which the uploads are checked against.
+/// The built-in one is kept if it can't be loaded, so failures are only logged.
+pub async fn fetch_catalogue() -> Option<Vec<CpuSpecDTO>> {
+    let response = match Request::get(CATALOGUE_URL).send().await {
+        Ok(response) if response.ok() => response,
+        Ok(response) => {
+            warn!("Catalogue not loaded: {}", response.status_text());
+            return None;
+        }
+        Err(error) => {
+            warn!("Catalogue not loaded: {}", error);
+            return None;
+        }
+    };
+
+    let specs = response
+        .json::<Vec<StoredCpuSpecDTO>>()
+        .await
+        .map_err(|error| warn!("Malformed catalogue: {}", error))
+        .ok()?;
+    // an empty catalogue has not been imported yet
+    (!specs.is_empty()).then(|| specs.into_iter().map(|stored| stored.spec).collect())
+}
+
+/// Estimates the CPU by the analytical estimators and compares it with the catalogue
+pub fn predict(result: &ResultDTO, specs: &[CpuSpecDTO]) -> Prediction {
+    let features = extract_features(result);
+    let spec = find_cpu_spec(specs, &normalize_model(&result.model));
+
+    Prediction {
+        microarchitecture: likely_microarchitecture(&features, specs),
+        check: check_model(&features, spec),
+        features,
+    }
+}
+
+impl Prediction {
+    /// What the user is told about their CPU, `None` if nothing was estimated confidently
+    pub fn summary(&self) -> Option<String> {
+        let caches = &self.features.cache_sizes;
+        let cache_levels = [("L1", caches.l1), ("L2", caches.l2), ("L3", caches.l3)]
+            .into_iter()
+            .filter_map(|(level, estimate)| {
+                confident(estimate).map(|size| format!("{} {}", format_size(size), level))
+            })
+            .collect::<Vec<_>>();
+
+        let mut properties = vec![];
+        if !cache_levels.is_empty() {
+            properties.push(format!("a {} cache", join(&cache_levels)));
+        }
+        if let Some(page_size) = confident(self.features.page_size) {
+            properties.push(format!("{} pages", format_size(page_size)));
+        }
+        if let Some(microarchitecture) = &self.microarchitecture {
+            properties.push(format!("the {} microarchitecture", microarchitecture));
+        }
+
+        (!properties.is_empty()).then(|| format!("We think your CPU has {}.", join(&properties)))
+    }
+
+    /// Comparison of the estimates with the model entered by the user
+    pub fn comparison(&self) -> String {
+        let Some(spec_name) = &self.check.spec_name else {
+            return "The model you entered is not in our catalogue yet, so we cannot compare it."
+                .to_string();
+        };
+        if !self.check.is_mismatch() {
+            return format!("This agrees with the specification of {}.", spec_name);
+        }
+
+        let properties = self
+            .check
+            .mismatches
+            .iter()
+            .map(|property| property_name(*property).to_string())
+            .collect::<Vec<_>>();
+        format!(
+            "This differs from the specification of {} in the {}. Please check that you entered the model of this device.",
+            spec_name,
+            join(&properties)
+        )
+    }
+}
+
+fn confident(estimate: Option<Estimate<u64>>) -> Option<u64> {
+    estimate
+        .filter(|estimate| estimate.confidence >= CHECK_CONFIDENCE)
+        .map(|estimate| estimate.value)
+}
+
+fn property_name(property: CpuProperty) -> &'static str {
+    match property {
+        CpuProperty::L1Size => "L1 cache size",
+        CpuProperty::L2Size => "L2 cache size",
+        CpuProperty::L3Size => "L3 cache size",
+        CpuProperty::PageSize => "page size",
+        CpuProperty::L1Associativity => "L1 cache associativity",
+    }
+}
+
+/// Size in the largest unit, e.g. 1.5 MiB
+fn format_size(bytes: u64) -> String {
+    let (unit, name) = match bytes {
+        bytes if bytes >= MIB => (MIB, "MiB"),
+        bytes if bytes >= KIB => (KIB, "KiB"),
+        _ => (1, "B"),
+    };
+    let value = bytes as f64 / unit as f64;
+    let value = format!("{:.2}", value);
+    format!(
+        "{} {}",
+        value.trim_end_matches('0').trim_end_matches('.'),
+        name
+    )
+}
+
+/// Enumeration of the items, e.g. "a, b and c"
+fn join(items: &[String]) -> String {
+    match items {
+        [] => String::new(),
+        [item] => item.clone(),
+        [init @ .., last] => format!("{} and {}", init.join(", "), last),
+    }
+}
+
+/*
+ * TESTS
+ */
+
+#[cfg(test)]
+mod tests {
+    use common::analysis::{CacheSizes, Estimate, Features};
+    use common::dto::result::{CpuProperty, ModelCheckDTO};
+
+    use crate::prediction::{built_in_catalogue, format_size, Prediction};
+
+    fn prediction(check: ModelCheckDTO) -> Prediction {
+        let estimate = |value, confidence| Some(Estimate { value, confidence });
+        Prediction {
+            features: Features {
+                page_size: estimate(4096, 0.9),
+                cache_sizes: CacheSizes {
+                    l1: estimate(32 * 1024, 0.9),
+                    l2: estimate(1024 * 1024, 0.8),
+                    l3: estimate(16 * 1024 * 1024, 0.1),
+                },
+                tlb_entries: None,
+                l1_associativity: None,
+            },
+            microarchitecture: Some("Zen 3".to_string()),
+            check,
+        }
+    }
+
+    #[test]
+    fn catalogue_is_parsed() {
+        // execute & assert
+        assert!(built_in_catalogue().len() > 100);
+    }
+
+    #[test]
+    fn confident_estimates_are_summarised() {
+        // prepare
+        let prediction = prediction(ModelCheckDTO {
+            spec_name: None,
+            mismatches: vec![],
+        });
+
+        // execute & assert
+        assert_eq!(
+            prediction.summary().unwrap(),
+            "We think your CPU has a 32 KiB L1 and 1 MiB L2 cache, 4 KiB pages and the Zen 3 microarchitecture."
+        );
+        assert!(prediction.comparison().contains("not in our catalogue"));
+    }
+
+    #[test]
+    fn mismatches_are_explained() {
+        // prepare
+        let prediction = prediction(ModelCheckDTO {
+            spec_name: Some("AMD Ryzen 5 5600X".to_string()),
+            mismatches: vec![CpuProperty::L2Size, CpuProperty::PageSize],
+        });
+
+        // execute & assert
+        assert_eq!(
+            prediction.comparison(),
+            "This differs from the specification of AMD Ryzen 5 5600X in the L2 cache size and page size. Please check that you entered the model of this device."
+        );
+    }
+
+    #[test]
+    fn sizes_are_in_largest_unit() {
+        // execute & assert
+        assert_eq!(format_size(48 * 1024), "48 KiB");
+        assert_eq!(format_size(1280 * 1024), "1.25 MiB");
+        assert_eq!(format_size(512), "512 B");
+    }
+}
@@ -0,0 +1,155 @@
+use serde::{Deserialize, Serialize};
+
+use common::benchmark::BenchmarkType;
+
+/// Order in which the benchmarks are executed. Every stage is a group of benchmarks
+/// running at the same time in separate workers, stages are executed one after another.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+pub struct BenchmarkPlan {
+    pub stages: Vec<Vec<BenchmarkType>>,
+}
+
+impl BenchmarkPlan {
+    /// Plans the benchmarks for the given number of workers. Benchmarks interfering
+    /// with each other never share a stage. The longest benchmarks are placed first,
+    /// every benchmark joins the first stage with a free worker and no interfering benchmark.
+    pub fn new(benchmarks: &[BenchmarkType], workers: usize) -> BenchmarkPlan {
+        let mut benchmarks = benchmarks.to_vec();
+        // the sort is stable, benchmarks of the same duration keep their order
+        benchmarks.sort_by(|a, b| b.expected_duration().total_cmp(&a.expected_duration()));
+
+        let mut stages: Vec<Vec<BenchmarkType>> = vec![];
+        for benchmark in benchmarks {
+            let free_stage = stages.iter_mut().find(|stage| {
+                stage.len() < workers.max(1)
+                    && stage.iter().all(|other| !benchmark.interferes_with(other))
+            });
+            match free_stage {
+                Some(stage) => stage.push(benchmark),
+                None => stages.push(vec![benchmark]),
+            }
+        }
+
+        BenchmarkPlan { stages }
+    }
+
+    /// Number of workers needed to execute the largest stage
+    pub fn workers(&self) -> usize {
+        self.stages
+            .iter()
+            .map(|stage| stage.len())
+            .max()
+            .unwrap_or(0)
+    }
+
+    /// Estimated duration of the whole plan in seconds
+    pub fn expected_duration(&self) -> f32 {
+        self.stages
+            .iter()
+            .map(|stage| {
+                stage
+                    .iter()
+                    .map(|benchmark| benchmark.expected_duration())
+                    .fold(0.0, f32::max)
+            })
+            .sum()
+    }
+}
+
+/*
+ * TESTS
+ */
+
+#[cfg(test)]
+mod tests {
+    use common::benchmark::BenchmarkType;
+
+    use crate::scheduler::BenchmarkPlan;
+
+    #[test]
+    fn plan_for_all_overlaps_benchmarks() {
+        // execute
+        let plan = BenchmarkPlan::new(&BenchmarkType::ALL, 4);
+
+        // assert
+        assert_eq!(
+            plan.stages,
+            vec![
+                vec![
+                    BenchmarkType::CacheSize,
+                    BenchmarkType::CacheAssociativity,
+                    BenchmarkType::SinglePerformance,
+                ],
+                vec![BenchmarkType::PageSize],
+                vec![BenchmarkType::TlbSize],
+            ]
+        );
+        assert_eq!(plan.workers(), 3);
+    }
+
+    #[test]
+    fn interfering_benchmarks_never_share_stage() {
+        for workers in 1..=5 {
+            // execute
+            let plan = BenchmarkPlan::new(&BenchmarkType::ALL, workers);
+
+            // assert
+            assert_eq!(plan.stages.concat().len(), BenchmarkType::ALL.len());
+            assert!(plan.workers() <= workers);
+            for stage in &plan.stages {
+                for (i, benchmark) in stage.iter().enumerate() {
+                    assert!(stage[i + 1..]
+                        .iter()
+                        .all(|other| !benchmark.interferes_with(other)));
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn independent_benchmarks_share_stage() {
+        // prepare
+        let benchmarks = [
+            BenchmarkType::SinglePerformance,
+            BenchmarkType::CacheSize,
+            BenchmarkType::SinglePerformance,
+            BenchmarkType::SinglePerformance,
+        ];
+
+        // execute
+        let plan = BenchmarkPlan::new(&benchmarks, 2);
+
+        // assert
+        assert_eq!(
+            plan.stages,
+            vec![
+                vec![BenchmarkType::CacheSize, BenchmarkType::SinglePerformance],
+                vec![
+                    BenchmarkType::SinglePerformance,
+                    BenchmarkType::SinglePerformance
+                ],
+            ]
+        );
+        assert_eq!(plan.workers(), 2);
+    }
+
+    #[test]
+    fn expected_duration_counts_longest_benchmark_of_stage() {
+        // prepare
+        let single = BenchmarkType::SinglePerformance.expected_duration();
+        let cache = BenchmarkType::CacheSize.expected_duration();
+
+        // execute
+        let plan = BenchmarkPlan::new(
+            &[
+                BenchmarkType::CacheSize,
+                BenchmarkType::SinglePerformance,
+                BenchmarkType::SinglePerformance,
+            ],
+            2,
+        );
+
+        // assert
+        assert_eq!(plan.expected_duration(), cache + single);
+    }
+}
@@ -0,0 +1,184 @@
+use std::collections::VecDeque;
+
+use gloo_storage::{LocalStorage, Storage};
+use log::warn;
+use serde::{Deserialize, Serialize};
+
+use common::benchmark::BenchmarkType;
+
+use crate::scheduler::BenchmarkPlan;
+use crate::worker::benchmarks::BenchmarkParameters;
+use crate::worker::BenchmarkResult;
+
+const SESSION_KEY: &str = "benchmark_session";
+
+/// Progress of a benchmark run. It is persisted after every finished benchmark,
+/// so that the run can be resumed when the page is reloaded in the middle of it.
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct BenchmarkSession {
+    pub model: String,
+    pub parameters: BenchmarkParameters,
+    pub plan: BenchmarkPlan,
+    pub results: Vec<BenchmarkResult>,
+}
+
+impl BenchmarkSession {
+    pub fn new(
+        model: String,
+        parameters: BenchmarkParameters,
+        plan: BenchmarkPlan,
+    ) -> BenchmarkSession {
+        BenchmarkSession {
+            model,
+            parameters,
+            plan,
+            results: vec![],
+        }
+    }
+
+    /// Stages of the plan with the benchmarks that have not finished yet. Benchmarks
+    /// of a stage interrupted in the middle are run again.
+    pub fn remaining_stages(&self) -> VecDeque<Vec<BenchmarkType>> {
+        self.plan
+            .stages
+            .iter()
+            .map(|stage| {
+                stage
+                    .iter()
+                    .filter(|benchmark| !self.is_finished(**benchmark))
+                    .copied()
+                    .collect::<Vec<_>>()
+            })
+            .filter(|stage| !stage.is_empty())
+            .collect()
+    }
+
+    pub fn is_complete(&self) -> bool {
+        self.remaining_stages().is_empty()
+    }
+
+    fn is_finished(&self, benchmark: BenchmarkType) -> bool {
+        self.results
+            .iter()
+            .any(|result| result.benchmark == benchmark)
+    }
+
+    /// Loads the session stored by a previous visit of the page, if there is one
+    pub fn load() -> Option<BenchmarkSession> {
+        LocalStorage::get(SESSION_KEY).ok()
+    }
+
+    pub fn save(&self) {
+        // losing the session only prevents resuming, so the run can go on
+        if let Err(error) = LocalStorage::set(SESSION_KEY, self) {
+            warn!("Failed to store the benchmark session: {}", error);
+        }
+    }
+
+    pub fn clear() {
+        LocalStorage::delete(SESSION_KEY);
+    }
+}
+
+/*
+ * TESTS
+ */
+
+#[cfg(test)]
+mod tests {
+    use common::benchmark::{BenchmarkType, CacheSizeMode};
+
+    use crate::scheduler::BenchmarkPlan;
+    use crate::session::BenchmarkSession;
+    use crate::worker::benchmarks::BenchmarkParameters;
+    use crate::worker::BenchmarkResult;
+
+    fn session(stages: Vec<Vec<BenchmarkType>>) -> BenchmarkSession {
+        BenchmarkSession::new(
+            "model".to_string(),
+            BenchmarkParameters {
+                seed: 42,
+                cache_size_mode: CacheSizeMode::Fixed,
+            },
+            BenchmarkPlan { stages },
+        )
+    }
+
+    fn result(benchmark: BenchmarkType) -> BenchmarkResult {
+        BenchmarkResult {
+            benchmark,
+            result_json: "[]".to_string(),
+            time: 1.0,
+        }
+    }
+
+    #[test]
+    fn new_session_runs_whole_plan() {
+        // prepare
+        let plan = BenchmarkPlan::new(&BenchmarkType::ALL, 2);
+
+        // execute
+        let session = session(plan.stages.clone());
+
+        // assert
+        assert_eq!(Vec::from(session.remaining_stages()), plan.stages);
+        assert!(!session.is_complete());
+    }
+
+    #[test]
+    fn finished_benchmarks_are_skipped() {
+        // prepare
+        let mut session = session(vec![
+            vec![BenchmarkType::PageSize],
+            vec![BenchmarkType::SinglePerformance, BenchmarkType::CacheSize],
+            vec![BenchmarkType::TlbSize],
+        ]);
+
+        // execute
+        session.results.push(result(BenchmarkType::PageSize));
+        session.results.push(result(BenchmarkType::CacheSize));
+
+        // assert
+        assert_eq!(
+            Vec::from(session.remaining_stages()),
+            vec![
+                vec![BenchmarkType::SinglePerformance],
+                vec![BenchmarkType::TlbSize]
+            ]
+        );
+    }
+
+    #[test]
+    fn session_with_all_results_is_complete() {
+        // prepare
+        let mut session = session(vec![
+            vec![BenchmarkType::PageSize],
+            vec![BenchmarkType::TlbSize],
+        ]);
+
+        // execute
+        session.results.push(result(BenchmarkType::TlbSize));
+        session.results.push(result(BenchmarkType::PageSize));
+
+        // assert
+        assert!(session.remaining_stages().is_empty());
+        assert!(session.is_complete());
+    }
+
+    #[test]
+    fn session_survives_serialization() {
+        // prepare
+        let mut session = session(vec![vec![BenchmarkType::PageSize]]);
+        session.results.push(result(BenchmarkType::PageSize));
+
+        // execute
+        let json = serde_json::to_string(&session).unwrap();
+        let restored = serde_json::from_str::<BenchmarkSession>(&json).unwrap();
+
+        // assert
+        assert_eq!(restored.model, "model");
+        assert_eq!(restored.parameters.seed, 42);
+        assert_eq!(restored.plan, session.plan);
+        assert_eq!(restored.results.len(), 1);
+    }
+}
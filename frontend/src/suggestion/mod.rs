@@ -0,0 +1,118 @@
+use gloo_net::http::Request;
+use log::warn;
+
+use common::dto::cpu::{ModelSuggestionDTO, ModelSuggestionsDTO};
+
+const SUGGEST_URL: &str = "/api/cpu/suggest";
+/// Delay after the last keystroke before the suggestions are requested
+pub const SUGGESTION_DELAY_MS: u32 = 300;
+
+/// Known models similar to the typed one. The autocomplete is optional,
+/// so failures are only logged.
+pub async fn fetch_suggestions(model: &str) -> Option<ModelSuggestionsDTO> {
+    let url = format!(
+        "{}?model={}",
+        SUGGEST_URL,
+        js_sys::encode_uri_component(model)
+    );
+    let response = match Request::get(&url).send().await {
+        Ok(response) if response.ok() => response,
+        Ok(response) => {
+            warn!("Suggestions not loaded: {}", response.status_text());
+            return None;
+        }
+        Err(error) => {
+            warn!("Suggestions not loaded: {}", error);
+            return None;
+        }
+    };
+
+    response
+        .json()
+        .await
+        .map_err(|error| warn!("Malformed suggestions: {}", error))
+        .ok()
+}
+
+/// Suggestions worth showing, the typed model itself is left out
+pub fn visible_suggestions(suggestions: &ModelSuggestionsDTO) -> Vec<&ModelSuggestionDTO> {
+    suggestions
+        .suggestions
+        .iter()
+        .filter(|suggestion| {
+            !suggestion
+                .model
+                .eq_ignore_ascii_case(&suggestions.model_normalized)
+        })
+        .collect()
+}
+
+/// Warning shown under the model input, when the typed model matches no known CPU
+pub fn unknown_model_warning(suggestions: &ModelSuggestionsDTO) -> Option<&'static str> {
+    if suggestions.known || suggestions.model_normalized.is_empty() {
+        return None;
+    }
+
+    Some(if suggestions.suggestions.is_empty() {
+        "This model does not match any CPU we know. Please check it for typos."
+    } else {
+        "This model does not match any CPU we know. Please check it for typos or pick one of the suggestions."
+    })
+}
+
+/*
+ * TESTS
+ */
+
+#[cfg(test)]
+mod tests {
+    use common::dto::cpu::{ModelSuggestionDTO, ModelSuggestionsDTO};
+
+    use crate::suggestion::{unknown_model_warning, visible_suggestions};
+
+    fn suggestions(model_normalized: &str, known: bool, models: &[&str]) -> ModelSuggestionsDTO {
+        ModelSuggestionsDTO {
+            model_normalized: model_normalized.to_string(),
+            known,
+            suggestions: models
+                .iter()
+                .map(|model| ModelSuggestionDTO {
+                    model: model.to_string(),
+                    in_catalogue: true,
+                    result_count: 0,
+                })
+                .collect(),
+        }
+    }
+
+    #[test]
+    fn typed_model_is_not_suggested() {
+        // prepare
+        let suggestions = suggestions("apple m1", true, &["Apple M1", "Apple M1 Pro"]);
+
+        // execute
+        let visible = visible_suggestions(&suggestions);
+
+        // assert
+        assert_eq!(visible.len(), 1);
+        assert_eq!(visible[0].model, "Apple M1 Pro");
+    }
+
+    #[test]
+    fn only_unknown_models_are_warned_about() {
+        // execute & assert
+        assert_eq!(
+            unknown_model_warning(&suggestions("Apple M1", true, &["Apple M1"])),
+            None
+        );
+        assert_eq!(unknown_model_warning(&suggestions("", false, &[])), None);
+        assert!(unknown_model_warning(&suggestions("Apple M7", false, &[]))
+            .unwrap()
+            .ends_with("typos."));
+        assert!(
+            unknown_model_warning(&suggestions("Apple M", false, &["Apple M1"]))
+                .unwrap()
+                .ends_with("suggestions.")
+        );
+    }
+}
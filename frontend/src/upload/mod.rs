@@ -0,0 +1,321 @@
+use std::fmt::{Display, Formatter};
+
+use gloo_net::http::Request;
+use gloo_storage::{LocalStorage, Storage};
+use gloo_timers::future::TimeoutFuture;
+use log::{info, warn};
+
+use common::dto::error::ErrorDTO;
+use common::dto::result::ResultDTO;
+
+const UPLOAD_URL: &str = "/api/result/upload";
+/// Version of the frontend, uploaded with the results
+pub const FRONTEND_VERSION: &str = env!("CARGO_PKG_VERSION");
+const PENDING_UPLOADS_KEY: &str = "pending_uploads";
+
+/// Number of attempts before the result is queued for the next visit
+pub const MAX_ATTEMPTS: u32 = 5;
+const INITIAL_BACKOFF_MS: u32 = 1000;
+const MAX_BACKOFF_MS: u32 = 30_000;
+
+#[derive(Clone, Debug, PartialEq)]
+pub enum UploadError {
+    /// The request did not reach the server, e.g. because the device is offline
+    Network(String),
+    /// The server rejected the result (4xx)
+    Client(u16, String),
+    /// The server failed to store the result (5xx)
+    Server(u16, String),
+    /// The server answered with a status that doesn't belong to an upload (1xx, 3xx).
+    /// Fetch follows redirects and hides informational responses, so this points to a proxy.
+    Unexpected(u16, String),
+    /// The result could not be serialized
+    Encoding(String),
+}
+
+impl UploadError {
+    /// Errors which can disappear when the upload is repeated later
+    pub fn is_transient(&self) -> bool {
+        match self {
+            UploadError::Network(_) => true,
+            // timeouts and rate limiting are reported as client errors
+            UploadError::Client(status, _) => *status == 408 || *status == 429,
+            UploadError::Server(_, _) => true,
+            UploadError::Unexpected(_, _) => false,
+            UploadError::Encoding(_) => false,
+        }
+    }
+
+    /// Errors caused by the result itself, so uploading it again can't succeed
+    pub fn is_rejected(&self) -> bool {
+        match self {
+            UploadError::Network(_) => false,
+            // malformed, too large or invalid results
+            UploadError::Client(status, _) => matches!(status, 400 | 413 | 422),
+            UploadError::Server(_, _) => false,
+            UploadError::Unexpected(_, _) => false,
+            UploadError::Encoding(_) => true,
+        }
+    }
+}
+
+impl Display for UploadError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            UploadError::Network(error) => write!(f, "could not reach the server ({})", error),
+            UploadError::Client(status, text) => {
+                write!(f, "the server rejected the results ({} {})", status, text)
+            }
+            UploadError::Server(status, text) => {
+                write!(
+                    f,
+                    "the server failed to store the results ({} {})",
+                    status, text
+                )
+            }
+            UploadError::Unexpected(status, text) => {
+                write!(f, "the server answered unexpectedly ({} {})", status, text)
+            }
+            UploadError::Encoding(error) => write!(f, "the results are malformed ({})", error),
+        }
+    }
+}
+
+/// Maps the HTTP status of the upload response to its outcome
+pub fn classify_response(status: u16, status_text: &str) -> Result<(), UploadError> {
+    match status {
+        100..=199 => Err(UploadError::Unexpected(status, status_text.to_string())),
+        200..=299 => Ok(()),
+        300..=399 => Err(UploadError::Unexpected(status, status_text.to_string())),
+        400..=499 => Err(UploadError::Client(status, status_text.to_string())),
+        500..=599 => Err(UploadError::Server(status, status_text.to_string())),
+        _ => Err(UploadError::Unexpected(status, status_text.to_string())),
+    }
+}
+
+/// Delay before the given retry (counted from 1), doubled after every failed attempt
+pub fn backoff_delay(retry: u32) -> u32 {
+    INITIAL_BACKOFF_MS
+        .saturating_mul(2u32.saturating_pow(retry.saturating_sub(1)))
+        .min(MAX_BACKOFF_MS)
+}
+
+pub async fn upload_result(result: &ResultDTO) -> Result<(), UploadError> {
+    let request = Request::post(UPLOAD_URL)
+        .json(result)
+        .map_err(|error| UploadError::Encoding(error.to_string()))?;
+    let response = request
+        .send()
+        .await
+        .map_err(|error| UploadError::Network(error.to_string()))?;
+
+    if response.ok() {
+        return Ok(());
+    }
+    // the backend explains why it rejected the result in the body
+    let reason = match response.json::<ErrorDTO>().await {
+        Ok(error) => describe_error(&error),
+        Err(_) => response.status_text(),
+    };
+    classify_response(response.status(), &reason)
+}
+
+/// Message of the error response, including the invalid fields
+pub fn describe_error(error: &ErrorDTO) -> String {
+    if error.fields.is_empty() {
+        return error.message.clone();
+    }
+
+    let fields = error
+        .fields
+        .iter()
+        .map(|field| format!("{} {}", field.field, field.message))
+        .collect::<Vec<_>>();
+    format!("{}: {}", error.message, fields.join(", "))
+}
+
+/// Uploads the result, retrying transient failures with exponential backoff.
+/// `on_retry` is called with the retry number, the error and the delay before every retry.
+pub async fn upload_with_retry(
+    result: &ResultDTO,
+    on_retry: impl Fn(u32, &UploadError, u32),
+) -> Result<(), UploadError> {
+    let mut attempt = 1;
+    loop {
+        match upload_result(result).await {
+            Err(error) if error.is_transient() && attempt < MAX_ATTEMPTS => {
+                let delay = backoff_delay(attempt);
+                warn!("Upload attempt {} failed: {}", attempt, error);
+                on_retry(attempt, &error, delay);
+                TimeoutFuture::new(delay).await;
+                attempt += 1;
+            }
+            outcome => return outcome,
+        }
+    }
+}
+
+/// Results of the same run have the same seed and the same durations of the benchmarks
+fn is_same_run(result: &ResultDTO, seed: Option<u64>, times: &[f32]) -> bool {
+    result.seed == seed && result.times == times
+}
+
+fn load_queue() -> Vec<ResultDTO> {
+    LocalStorage::get::<Vec<ResultDTO>>(PENDING_UPLOADS_KEY).unwrap_or_default()
+}
+
+fn store_queue(pending: &[ResultDTO]) {
+    if let Err(error) = LocalStorage::set(PENDING_UPLOADS_KEY, pending) {
+        warn!("Failed to queue the result: {}", error);
+    }
+}
+
+/// Stores the result, so that its upload can be retried on the next visit.
+/// Replaces the queued result of the same run.
+pub fn queue_result(result: ResultDTO) {
+    let mut pending = load_queue();
+    pending.retain(|queued| !is_same_run(queued, result.seed, &result.times));
+    pending.push(result);
+    store_queue(&pending);
+}
+
+/// Removes the queued result of the same run, after it was uploaded
+pub fn dequeue_result(result: &ResultDTO) {
+    let mut pending = load_queue();
+    let count = pending.len();
+    pending.retain(|queued| !is_same_run(queued, result.seed, &result.times));
+    if pending.len() != count {
+        store_queue(&pending);
+    }
+}
+
+/// Whether the result of the run with the given seed and benchmark durations is queued
+pub fn is_queued(seed: Option<u64>, times: &[f32]) -> bool {
+    load_queue()
+        .iter()
+        .any(|queued| is_same_run(queued, seed, times))
+}
+
+/// Tries to upload the results queued during the previous visits.
+/// Results are only dropped from the queue once they are uploaded or rejected.
+pub async fn upload_pending_results() {
+    let pending = load_queue();
+    if pending.is_empty() {
+        return;
+    }
+    // results queued while uploading are appended to the emptied queue
+    LocalStorage::delete(PENDING_UPLOADS_KEY);
+
+    info!("Uploading {} pending results", pending.len());
+    for result in pending {
+        match upload_result(&result).await {
+            Ok(()) => info!("Pending result uploaded"),
+            Err(error) if error.is_rejected() => warn!("Pending result dropped: {}", error),
+            Err(error) => {
+                warn!("Pending result not uploaded: {}", error);
+                queue_result(result);
+            }
+        }
+    }
+}
+
+/*
+ * TESTS
+ */
+
+#[cfg(test)]
+mod tests {
+    use common::dto::error::{ErrorDTO, FieldErrorDTO};
+
+    use crate::upload::{backoff_delay, classify_response, describe_error, UploadError};
+
+    #[test]
+    fn responses_are_classified_by_status() {
+        // execute & assert
+        assert_eq!(classify_response(200, "OK"), Ok(()));
+        assert_eq!(
+            classify_response(413, "Payload Too Large"),
+            Err(UploadError::Client(413, "Payload Too Large".to_string()))
+        );
+        assert_eq!(
+            classify_response(503, "Service Unavailable"),
+            Err(UploadError::Server(503, "Service Unavailable".to_string()))
+        );
+        assert_eq!(
+            classify_response(101, "Switching Protocols"),
+            Err(UploadError::Unexpected(
+                101,
+                "Switching Protocols".to_string()
+            ))
+        );
+        assert_eq!(
+            classify_response(302, "Found"),
+            Err(UploadError::Unexpected(302, "Found".to_string()))
+        );
+        assert_eq!(
+            classify_response(0, ""),
+            Err(UploadError::Unexpected(0, "".to_string()))
+        );
+    }
+
+    #[test]
+    fn only_transient_errors_are_retried() {
+        // execute & assert
+        assert!(UploadError::Network("offline".to_string()).is_transient());
+        assert!(UploadError::Server(500, "".to_string()).is_transient());
+        assert!(UploadError::Client(429, "".to_string()).is_transient());
+        assert!(!UploadError::Client(400, "".to_string()).is_transient());
+        assert!(!UploadError::Encoding("".to_string()).is_transient());
+        assert!(!UploadError::Unexpected(302, "".to_string()).is_transient());
+    }
+
+    #[test]
+    fn only_invalid_results_are_rejected() {
+        // execute & assert
+        assert!(UploadError::Client(422, "".to_string()).is_rejected());
+        assert!(UploadError::Client(413, "".to_string()).is_rejected());
+        assert!(UploadError::Encoding("".to_string()).is_rejected());
+        assert!(!UploadError::Client(404, "".to_string()).is_rejected());
+        assert!(!UploadError::Server(500, "".to_string()).is_rejected());
+        assert!(!UploadError::Network("offline".to_string()).is_rejected());
+        assert!(!UploadError::Unexpected(302, "".to_string()).is_rejected());
+    }
+
+    #[test]
+    fn backoff_doubles_up_to_limit() {
+        // execute
+        let delays = (1..=7).map(backoff_delay).collect::<Vec<_>>();
+
+        // assert
+        assert_eq!(delays, vec![1000, 2000, 4000, 8000, 16000, 30000, 30000]);
+        assert_eq!(backoff_delay(u32::MAX), 30000);
+    }
+
+    #[test]
+    fn error_description_lists_fields() {
+        // prepare
+        let mut error = ErrorDTO {
+            error: "validation_failed".to_string(),
+            message: "the request has 2 invalid fields".to_string(),
+            fields: vec![],
+        };
+
+        // execute & assert
+        assert_eq!(describe_error(&error), "the request has 2 invalid fields");
+        error.fields = vec![
+            FieldErrorDTO {
+                field: "model".to_string(),
+                message: "must not be empty".to_string(),
+            },
+            FieldErrorDTO {
+                field: "times".to_string(),
+                message: "must have as many items as benchmark_results (5)".to_string(),
+            },
+        ];
+        assert_eq!(
+            describe_error(&error),
+            "the request has 2 invalid fields: model must not be empty, \
+             times must have as many items as benchmark_results (5)"
+        );
+    }
+}
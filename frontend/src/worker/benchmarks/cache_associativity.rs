@@ -1,12 +1,16 @@
 use std::hint::black_box;
 
-use gloo_console::info;
-use rand::seq::SliceRandom;
+use log::info;
+use rand::{Rng, SeedableRng};
+use rand_chacha::ChaCha8Rng;
 use serde::{Deserialize, Serialize};
 use serde_json::json;
 
+use common::benchmark::BenchmarkType;
+
 use crate::clock::Clock;
-use crate::worker::{BenchmarkResult, BenchmarkType};
+use crate::worker::benchmarks::chain::build_pointer_chain;
+use crate::worker::BenchmarkResult;
 
 const MAX_SIZE: usize = 32;
 const STEP: usize = 32 << 10;
@@ -18,23 +22,35 @@ struct DataPoint {
     y: i64,
 }
 
-pub fn run_cache_associativity_benchmark(clock: Clock) -> BenchmarkResult {
+pub fn run_cache_associativity_benchmark(clock: &impl Clock, seed: u64) -> BenchmarkResult {
     info!("Running cache associativity benchmark");
     let starting_time = clock.read();
-    let mut rand = rand::thread_rng();
-    let result = (1..MAX_SIZE)
-        .into_iter()
+    let mut rand = ChaCha8Rng::seed_from_u64(seed);
+    let result = measure_associativity(clock, &mut rand, MAX_SIZE, ITERATIONS);
+
+    BenchmarkResult {
+        benchmark: BenchmarkType::CacheAssociativity,
+        result_json: json!(result).to_string(),
+        time: (clock.read() - starting_time) as f32,
+    }
+}
+
+/// Measures chains of `1..max_size` elements placed `STEP` apart,
+/// following each chain `s * iterations` times
+fn measure_associativity(
+    clock: &impl Clock,
+    rand: &mut impl Rng,
+    max_size: usize,
+    iterations: usize,
+) -> Vec<DataPoint> {
+    (1..max_size)
         .map(|s| {
             let size = STEP * s;
-            let mut list = vec![0; size];
-            let mut indices = (0..size).step_by(STEP).collect::<Vec<_>>();
-            indices.shuffle(&mut rand);
-            indices.windows(2).for_each(|w| list[w[0]] = w[1]);
-            list[indices[s - 1]] = indices[0];
+            let list = build_pointer_chain(size, STEP, rand);
 
             let mut p = 0;
             let start = clock.read();
-            for _ in 0..(s * ITERATIONS) {
+            for _ in 0..(s * iterations) {
                 p = black_box(list[p]);
             }
             let end = clock.read();
@@ -43,11 +59,34 @@ pub fn run_cache_associativity_benchmark(clock: Clock) -> BenchmarkResult {
                 y: (end - start) / s as i64,
             }
         })
-        .collect::<Vec<_>>();
+        .collect::<Vec<_>>()
+}
 
-    BenchmarkResult {
-        benchmark: BenchmarkType::CacheAssociativity,
-        result_json: json!(result).to_string(),
-        time: (clock.read() - starting_time) as f32,
+/*
+ * TESTS
+ */
+
+#[cfg(test)]
+mod tests {
+    use rand::SeedableRng;
+    use rand_chacha::ChaCha8Rng;
+    use serde_json::json;
+
+    use crate::clock::mock::MockClock;
+    use crate::worker::benchmarks::cache_associativity::measure_associativity;
+    use crate::worker::benchmarks::testing::parse_data_points;
+
+    #[test]
+    fn data_points_cover_all_chain_lengths() {
+        // prepare
+        let clock = MockClock::new(12);
+        let mut rand = ChaCha8Rng::seed_from_u64(42);
+
+        // execute
+        let result = measure_associativity(&clock, &mut rand, 5, 10);
+
+        // assert
+        let points = parse_data_points(&json!(result).to_string());
+        assert_eq!(points, vec![(1, 12), (2, 6), (3, 4), (4, 3)]);
     }
 }
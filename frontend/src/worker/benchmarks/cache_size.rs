@@ -1,15 +1,34 @@
 use std::hint::black_box;
 use std::mem::size_of;
 
-use gloo_console::info;
-use rand::seq::SliceRandom;
+use log::info;
+use rand::{Rng, SeedableRng};
+use rand_chacha::ChaCha8Rng;
 use serde::{Deserialize, Serialize};
 use serde_json::json;
 
+use common::analysis::CACHE_LEVELS;
+use common::benchmark::{BenchmarkType, CacheSizeMode};
+use common::steps::rising_steps;
+
 use crate::clock::Clock;
-use crate::worker::{BenchmarkResult, BenchmarkType};
+use crate::worker::benchmarks::chain::{build_pointer_chain, build_random_cycle};
+use crate::worker::benchmarks::BenchmarkParameters;
+use crate::worker::BenchmarkResult;
 
 const KB: usize = 1024;
+const MB: usize = 1024 * KB;
+
+/// Largest list measured by the adaptive search, above the largest last level caches.
+/// The sweep ends earlier on devices that can't allocate a list, e.g. phones and 32-bit browsers.
+const ADAPTIVE_MAX_SIZE: usize = 256 * MB;
+/// Upper limit of the measured accesses per list in the adaptive search.
+/// Larger lists are walked only partially, as they don't fit in any cache anyway.
+const ADAPTIVE_MAX_ACCESSES: usize = 1 << 21;
+/// Number of sizes measured between the coarse points around a step
+const ADAPTIVE_REFINEMENT_POINTS: usize = 7;
+/// Granularity of the refined sizes in bytes
+const ADAPTIVE_GRANULARITY: usize = 64;
 
 #[derive(Debug, Serialize, Deserialize)]
 struct DataPoint {
@@ -17,30 +36,66 @@ struct DataPoint {
     y: i64,
 }
 
-pub fn run_cache_size_benchmark(clock: Clock) -> BenchmarkResult {
+#[derive(Debug, Serialize, Deserialize)]
+struct AdaptiveResult {
+    points: Vec<DataPoint>,
+    boundaries: Vec<Boundary>,
+}
+
+/// Size in bytes of the largest list measured before a latency step
+#[derive(Debug, Serialize, Deserialize, PartialEq)]
+struct Boundary {
+    x: u64,
+    y_before: i64,
+    y_after: i64,
+}
+
+pub fn run_cache_size_benchmark(
+    clock: &impl Clock,
+    parameters: &BenchmarkParameters,
+) -> BenchmarkResult {
     info!("Running cache size benchmark");
     let starting_time = clock.read();
+
+    let mut rand = ChaCha8Rng::seed_from_u64(parameters.seed);
+    let result_json = match parameters.cache_size_mode {
+        CacheSizeMode::Fixed => json!(measure_cache_sizes(clock, &mut rand, &cache_sizes())),
+        CacheSizeMode::Adaptive => {
+            let budget = fixed_sweep_cost(&cache_sizes());
+            json!(search_cache_boundaries(
+                clock,
+                &mut rand,
+                ADAPTIVE_MAX_SIZE,
+                budget
+            ))
+        }
+    };
+
+    BenchmarkResult {
+        benchmark: BenchmarkType::CacheSize,
+        result_json: result_json.to_string(),
+        time: (clock.read() - starting_time) as f32,
+    }
+}
+
+/// Sizes of the measured lists in KiB
+fn cache_sizes() -> Vec<u64> {
     let l0 = 1..=1;
     let l1 = (4..=512).step_by(4);
     let l2 = (1..=1).map(|x| x * 1024);
     let l3 = (2..=32).step_by(2).map(|x| x * 1024);
 
-    let sizes: Vec<u64> = l0.chain(l1).chain(l2).chain(l3).collect();
+    l0.chain(l1).chain(l2).chain(l3).collect()
+}
 
-    let mut rand = rand::thread_rng();
-    let result: Vec<DataPoint> = sizes
-        .into_iter()
-        .map(|s| {
+fn measure_cache_sizes(clock: &impl Clock, rand: &mut impl Rng, sizes: &[u64]) -> Vec<DataPoint> {
+    sizes
+        .iter()
+        .map(|&s| {
             // this info may be removed at a later stage
-            info!("Running cache-size profiler with size:", s);
+            info!("Running cache-size profiler with size: {}", s);
             let size = KB * s as usize / size_of::<usize>();
-            let mut list = vec![0usize; size];
-            let mut indices = (0..size).collect::<Vec<_>>();
-            indices.shuffle(&mut rand);
-
-            // the algorithm may need to be verified for correctness
-            indices[1..].windows(2).for_each(|w| list[w[0]] = w[1]);
-            list[indices[size - 1]] = indices[0];
+            let list = build_pointer_chain(size, 1, rand);
 
             // warmup step; to make sure that the data is in the cache
             let mut p = 0;
@@ -61,11 +116,318 @@ pub fn run_cache_size_benchmark(clock: Clock) -> BenchmarkResult {
                 y: (end - start) / s as i64,
             }
         })
+        .collect::<Vec<_>>()
+}
+
+/// Estimated number of element operations (writes and reads) needed by the fixed sweep.
+/// Used as the time budget of the adaptive search.
+fn fixed_sweep_cost(sizes: &[u64]) -> usize {
+    sizes
+        .iter()
+        // building the chain takes two passes, then it's walked twice
+        .map(|&s| 4 * KB * s as usize / size_of::<usize>())
+        .sum()
+}
+
+/// Estimated number of element operations needed by a single measurement of the adaptive search
+fn adaptive_measurement_cost(size: usize) -> usize {
+    let elements = size / size_of::<usize>();
+    elements + 2 * elements.min(ADAPTIVE_MAX_ACCESSES)
+}
+
+/// Sizes of the coarse sweep in bytes, doubling from 1 KiB up to `max_size`
+fn coarse_sizes(max_size: usize) -> Vec<usize> {
+    std::iter::successors(Some(KB), |size| Some(size * 2))
+        .take_while(|size| *size <= max_size)
+        .collect()
+}
+
+/// Searches for the cache boundaries. First measures a coarse sweep, then measures
+/// more sizes between the coarse points with a latency step, as long as the budget allows.
+fn search_cache_boundaries(
+    clock: &impl Clock,
+    rand: &mut impl Rng,
+    max_size: usize,
+    budget: usize,
+) -> AdaptiveResult {
+    let coarse_sizes = coarse_sizes(max_size);
+    let mut remaining_budget = budget.saturating_sub(
+        coarse_sizes
+            .iter()
+            .map(|size| adaptive_measurement_cost(*size))
+            .sum(),
+    );
+
+    // larger lists wouldn't fit either
+    let mut points = coarse_sizes
+        .iter()
+        .map_while(|size| measure_adaptive_size(clock, rand, *size))
         .collect::<Vec<_>>();
 
-    BenchmarkResult {
-        benchmark: BenchmarkType::CacheSize,
-        result_json: json!(result).to_string(),
-        time: (clock.read() - starting_time) as f32,
+    let steps = find_steps(&points);
+
+    // smaller sizes are cheaper, so they are refined first
+    for (low, high) in steps.iter() {
+        let sizes = refinement_sizes(*low as usize, *high as usize);
+        let cost = sizes
+            .iter()
+            .map(|size| adaptive_measurement_cost(*size))
+            .sum::<usize>();
+
+        if cost > remaining_budget {
+            info!("Skipping refinement between {} and {} bytes", low, high);
+            continue;
+        }
+        remaining_budget -= cost;
+
+        points.extend(
+            sizes
+                .iter()
+                .filter_map(|size| measure_adaptive_size(clock, rand, *size)),
+        );
+    }
+
+    points.sort_by_key(|point| point.x);
+    let boundaries = steps
+        .iter()
+        .filter_map(|(low, high)| locate_boundary(&points, *low, *high))
+        .collect();
+
+    AdaptiveResult { points, boundaries }
+}
+
+/// Measures a list of `size` bytes. The latency is scaled the same way as in
+/// the fixed sweep, i.e. to ticks per KiB of the walked list.
+/// Returns `None` if the list can't be allocated.
+fn measure_adaptive_size(
+    clock: &impl Clock,
+    rand: &mut impl Rng,
+    size: usize,
+) -> Option<DataPoint> {
+    info!("Running adaptive cache-size profiler with size: {}", size);
+    let elements = size / size_of::<usize>();
+    let accesses = elements.min(ADAPTIVE_MAX_ACCESSES);
+    let Some(list) = build_random_cycle(elements, rand) else {
+        info!("Could not allocate a list of {} bytes", size);
+        return None;
+    };
+
+    let mut p = 0;
+    for _ in 0..accesses {
+        p = black_box(list[p]);
+    }
+
+    let start = clock.read();
+    for _ in 0..accesses {
+        p = black_box(list[p]);
+    }
+    let end = clock.read();
+
+    let walked_kb = (accesses * size_of::<usize>()) as f64 / KB as f64;
+    Some(DataPoint {
+        x: size as u64,
+        y: ((end - start) as f64 / walked_kb) as i64,
+    })
+}
+
+/// Pairs of neighbouring sizes, between which the latency grows by a step of a cache level.
+/// Uses the same detector as the analysis of the results.
+fn find_steps(points: &[DataPoint]) -> Vec<(u64, u64)> {
+    rising_steps(&latencies(points.iter()), CACHE_LEVELS)
+        .iter()
+        .map(|step| (step.x as u64, step.x_after as u64))
+        .collect()
+}
+
+fn latencies<'a>(points: impl Iterator<Item = &'a DataPoint>) -> Vec<(f64, f64)> {
+    points
+        .map(|point| (point.x as f64, point.y as f64))
+        .collect()
+}
+
+/// Evenly spaced sizes between `low` and `high` (both exclusive), aligned to the granularity
+fn refinement_sizes(low: usize, high: usize) -> Vec<usize> {
+    let step = (high - low) / (ADAPTIVE_REFINEMENT_POINTS + 1);
+    let mut sizes = (1..=ADAPTIVE_REFINEMENT_POINTS)
+        .map(|i| (low + i * step) / ADAPTIVE_GRANULARITY * ADAPTIVE_GRANULARITY)
+        .filter(|size| *size > low && *size < high)
+        .collect::<Vec<_>>();
+    sizes.dedup();
+    sizes
+}
+
+/// Finds the step between the points in the refined range. Falls back to the coarse
+/// points if the range wasn't refined or the step isn't found among the refined points.
+fn locate_boundary(points: &[DataPoint], low: u64, high: u64) -> Option<Boundary> {
+    let range = points
+        .iter()
+        .filter(|point| point.x >= low && point.x <= high)
+        .collect::<Vec<_>>();
+    let (x, x_after) = rising_steps(&latencies(range.iter().copied()), 1)
+        .first()
+        .map_or((low, high), |step| (step.x as u64, step.x_after as u64));
+
+    let y_at = |x| range.iter().find(|point| point.x == x).map(|point| point.y);
+    Some(Boundary {
+        x,
+        y_before: y_at(x)?,
+        y_after: y_at(x_after)?,
+    })
+}
+
+/*
+ * TESTS
+ */
+
+#[cfg(test)]
+mod tests {
+    use rand::SeedableRng;
+    use rand_chacha::ChaCha8Rng;
+    use serde_json::json;
+
+    use crate::clock::mock::MockClock;
+    use crate::worker::benchmarks::cache_size::{
+        adaptive_measurement_cost, cache_sizes, coarse_sizes, find_steps, fixed_sweep_cost,
+        locate_boundary, measure_cache_sizes, refinement_sizes, search_cache_boundaries, Boundary,
+        DataPoint, ADAPTIVE_MAX_SIZE, KB, MB,
+    };
+    use crate::worker::benchmarks::testing::parse_data_points;
+
+    fn points(values: &[(u64, i64)]) -> Vec<DataPoint> {
+        values.iter().map(|&(x, y)| DataPoint { x, y }).collect()
+    }
+
+    #[test]
+    fn sizes_match_configuration() {
+        // execute
+        let sizes = cache_sizes();
+
+        // assert
+        assert_eq!(sizes.len(), 1 + 128 + 1 + 16);
+        assert_eq!(sizes[..3], [1, 4, 8]);
+        assert_eq!(sizes[128..131], [512, 1024, 2048]);
+        assert_eq!(sizes.last(), Some(&(32 * 1024)));
+        assert!(sizes.windows(2).all(|w| w[0] < w[1]));
+    }
+
+    #[test]
+    fn data_points_match_sizes() {
+        // prepare
+        let clock = MockClock::new(1024);
+        let mut rand = ChaCha8Rng::seed_from_u64(42);
+
+        // execute
+        let result = measure_cache_sizes(&clock, &mut rand, &[1, 4, 8]);
+
+        // assert
+        let points = parse_data_points(&json!(result).to_string());
+        assert_eq!(points, vec![(1024, 1024), (4096, 256), (8192, 128)]);
+    }
+
+    #[test]
+    fn coarse_sweep_reaches_256_mib_within_budget() {
+        // execute
+        let sizes = coarse_sizes(ADAPTIVE_MAX_SIZE);
+
+        // assert
+        assert_eq!(sizes.first(), Some(&KB));
+        assert_eq!(sizes.last(), Some(&(256 * MB)));
+        let cost = sizes
+            .iter()
+            .map(|size| adaptive_measurement_cost(*size))
+            .sum::<usize>();
+        assert!(cost < fixed_sweep_cost(&cache_sizes()));
+    }
+
+    #[test]
+    fn steps_are_found_between_latency_jumps() {
+        // prepare
+        let points = points(&[
+            (1024, 10),
+            (2048, 11),
+            (4096, 30),
+            (8192, 31),
+            (16384, 80),
+            (32768, 82),
+        ]);
+
+        // execute
+        let steps = find_steps(&points);
+
+        // assert
+        assert_eq!(steps, vec![(2048, 4096), (8192, 16384)]);
+    }
+
+    #[test]
+    fn refinement_sizes_lie_between_coarse_points() {
+        // execute
+        let sizes = refinement_sizes(32 * KB, 64 * KB);
+
+        // assert
+        assert_eq!(sizes.len(), 7);
+        assert_eq!(sizes.first(), Some(&(36 * KB)));
+        assert_eq!(sizes.last(), Some(&(60 * KB)));
+        assert!(sizes.iter().all(|size| size % 64 == 0));
+    }
+
+    #[test]
+    fn boundary_is_located_at_refined_step() {
+        // prepare
+        let points = points(&[
+            (32768, 10),
+            (36864, 10),
+            (40960, 11),
+            (45056, 25),
+            (49152, 26),
+            (65536, 27),
+        ]);
+
+        // execute
+        let boundary = locate_boundary(&points, 32768, 65536);
+
+        // assert
+        assert_eq!(
+            boundary,
+            Some(Boundary {
+                x: 40960,
+                y_before: 11,
+                y_after: 25,
+            })
+        );
+    }
+
+    #[test]
+    fn boundary_falls_back_to_coarse_points() {
+        // prepare
+        let points = points(&[(16384, 10), (32768, 10), (65536, 27), (131072, 28)]);
+
+        // execute
+        let boundary = locate_boundary(&points, 32768, 65536);
+
+        // assert
+        assert_eq!(
+            boundary,
+            Some(Boundary {
+                x: 32768,
+                y_before: 10,
+                y_after: 27,
+            })
+        );
+    }
+
+    #[test]
+    fn flat_latencies_have_no_boundaries() {
+        // prepare
+        let clock = MockClock::new(1024);
+        let mut rand = ChaCha8Rng::seed_from_u64(42);
+
+        // execute
+        let result = search_cache_boundaries(&clock, &mut rand, 64 * KB, usize::MAX);
+
+        // assert
+        let points = parse_data_points(&json!(result.points).to_string());
+        let sizes = points.iter().map(|(x, _)| *x as usize).collect::<Vec<_>>();
+        assert_eq!(sizes, coarse_sizes(64 * KB));
+        assert!(result.boundaries.is_empty());
     }
 }
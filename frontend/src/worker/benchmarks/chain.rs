@@ -0,0 +1,152 @@
+use rand::seq::SliceRandom;
+use rand::Rng;
+
+/// Builds a list for pointer chasing. Every `stride`-th element points to the next one
+/// in random order, so that following the pointers from index 0 visits all of them
+/// in a single cycle. The remaining elements are not used.
+pub fn build_pointer_chain(length: usize, stride: usize, rng: &mut impl Rng) -> Vec<usize> {
+    let mut list = vec![0; length];
+    let mut indices = (0..length).step_by(stride).collect::<Vec<_>>();
+    indices.shuffle(rng);
+
+    indices.windows(2).for_each(|w| list[w[0]] = w[1]);
+    list[indices[indices.len() - 1]] = indices[0];
+
+    list
+}
+
+/// Builds a list for pointer chasing, in which all elements form a single random cycle.
+/// Uses Sattolo's algorithm, which needs a single pass and no additional memory,
+/// so it's cheaper than [build_pointer_chain] for large lists.
+/// Returns `None` if the list can't be allocated, instead of aborting.
+pub fn build_random_cycle(length: usize, rng: &mut impl Rng) -> Option<Vec<usize>> {
+    let mut list = Vec::new();
+    list.try_reserve_exact(length).ok()?;
+    list.extend(0..length);
+
+    for i in (1..length).rev() {
+        let j = rng.gen_range(0..i);
+        list.swap(i, j);
+    }
+
+    Some(list)
+}
+
+/*
+ * TESTS
+ */
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashSet;
+
+    use rand::SeedableRng;
+    use rand_chacha::ChaCha8Rng;
+
+    use crate::worker::benchmarks::chain::{build_pointer_chain, build_random_cycle};
+
+    /// Follows the chain from index 0 until it returns there and collects the visited indices
+    fn follow_chain(list: &[usize]) -> Vec<usize> {
+        let mut visited = vec![0];
+        let mut p = list[0];
+        while p != 0 {
+            assert!(
+                visited.len() <= list.len(),
+                "chain does not return to the start"
+            );
+            visited.push(p);
+            p = list[p];
+        }
+        visited
+    }
+
+    #[test]
+    fn dense_chain_is_single_cycle() {
+        // prepare
+        let mut rng = ChaCha8Rng::seed_from_u64(42);
+
+        // execute
+        let list = build_pointer_chain(1000, 1, &mut rng);
+
+        // assert
+        let visited = follow_chain(&list);
+        assert_eq!(visited.len(), 1000);
+        assert_eq!(visited.iter().collect::<HashSet<_>>().len(), 1000);
+    }
+
+    #[test]
+    fn strided_chain_is_single_cycle_over_strided_indices() {
+        // prepare
+        let mut rng = ChaCha8Rng::seed_from_u64(42);
+
+        // execute
+        let list = build_pointer_chain(64 * 10, 64, &mut rng);
+
+        // assert
+        let visited = follow_chain(&list).into_iter().collect::<HashSet<_>>();
+        let expected = (0..640).step_by(64).collect::<HashSet<_>>();
+        assert_eq!(visited, expected);
+    }
+
+    #[test]
+    fn single_element_chain_points_to_itself() {
+        // prepare
+        let mut rng = ChaCha8Rng::seed_from_u64(42);
+
+        // execute
+        let list = build_pointer_chain(1, 1, &mut rng);
+
+        // assert
+        assert_eq!(list, vec![0]);
+    }
+
+    #[test]
+    fn chain_is_reproducible_from_seed() {
+        // execute
+        let first = build_pointer_chain(1000, 1, &mut ChaCha8Rng::seed_from_u64(42));
+        let second = build_pointer_chain(1000, 1, &mut ChaCha8Rng::seed_from_u64(42));
+        let other = build_pointer_chain(1000, 1, &mut ChaCha8Rng::seed_from_u64(43));
+
+        // assert
+        assert_eq!(first, second);
+        assert_ne!(first, other);
+    }
+
+    #[test]
+    fn random_cycle_is_single_cycle() {
+        // prepare
+        let mut rng = ChaCha8Rng::seed_from_u64(42);
+
+        // execute
+        let list = build_random_cycle(1000, &mut rng).unwrap();
+
+        // assert
+        let visited = follow_chain(&list);
+        assert_eq!(visited.len(), 1000);
+        assert_eq!(visited.iter().collect::<HashSet<_>>().len(), 1000);
+    }
+
+    #[test]
+    fn single_element_random_cycle_points_to_itself() {
+        // prepare
+        let mut rng = ChaCha8Rng::seed_from_u64(42);
+
+        // execute
+        let list = build_random_cycle(1, &mut rng).unwrap();
+
+        // assert
+        assert_eq!(list, vec![0]);
+    }
+
+    #[test]
+    fn unallocatable_random_cycle_is_none() {
+        // prepare
+        let mut rng = ChaCha8Rng::seed_from_u64(42);
+
+        // execute
+        let list = build_random_cycle(usize::MAX / 2, &mut rng);
+
+        // assert
+        assert!(list.is_none());
+    }
+}
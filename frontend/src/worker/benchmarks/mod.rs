@@ -1,5 +1,46 @@
+use serde::{Deserialize, Serialize};
+
+use common::benchmark::{BenchmarkType, CacheSizeMode};
+
+use crate::clock::Clock;
+use crate::worker::benchmarks::cache_associativity::run_cache_associativity_benchmark;
+use crate::worker::benchmarks::cache_size::run_cache_size_benchmark;
+use crate::worker::benchmarks::page_size::run_page_size_benchmark;
+use crate::worker::benchmarks::single_performance::run_single_performance_benchmark;
+use crate::worker::benchmarks::tlb_size::run_tlb_size_benchmark;
+use crate::worker::BenchmarkResult;
+
 pub mod cache_associativity;
 pub mod cache_size;
+mod chain;
 pub mod page_size;
 pub mod single_performance;
+#[cfg(test)]
+mod testing;
 pub mod tlb_size;
+
+/// Configuration of the benchmarks, shared by all benchmarks of a single run
+#[derive(Serialize, Deserialize, Clone, Copy, Debug)]
+pub struct BenchmarkParameters {
+    /// Seed of the random number generator used to build the benchmark data
+    pub seed: u64,
+    pub cache_size_mode: CacheSizeMode,
+}
+
+/// Runs the benchmark with the given clock. Doesn't depend on the browser APIs,
+/// so it can be used both in the worker and natively.
+pub fn run_benchmark(
+    benchmark: BenchmarkType,
+    parameters: &BenchmarkParameters,
+    clock: &impl Clock,
+) -> BenchmarkResult {
+    match benchmark {
+        BenchmarkType::PageSize => run_page_size_benchmark(clock),
+        BenchmarkType::CacheSize => run_cache_size_benchmark(clock, parameters),
+        BenchmarkType::TlbSize => run_tlb_size_benchmark(clock, parameters.seed),
+        BenchmarkType::SinglePerformance => run_single_performance_benchmark(clock),
+        BenchmarkType::CacheAssociativity => {
+            run_cache_associativity_benchmark(clock, parameters.seed)
+        }
+    }
+}
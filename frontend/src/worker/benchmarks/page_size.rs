@@ -1,11 +1,13 @@
-use gloo_console::info;
+use log::info;
 use serde_json::json;
 use std::hint::black_box;
 
 use serde::Serialize;
 
+use common::benchmark::BenchmarkType;
+
 use crate::clock::Clock;
-use crate::worker::{BenchmarkResult, BenchmarkType};
+use crate::worker::BenchmarkResult;
 
 // Constants
 const MB: usize = 1024 * 1024;
@@ -18,16 +20,16 @@ struct DataPoint {
     y: i64,
 }
 
-pub fn run_page_size_benchmark(clock: Clock) -> BenchmarkResult {
+pub fn run_page_size_benchmark(clock: &impl Clock) -> BenchmarkResult {
     info!("Running page size benchmark");
 
-    let mut buffer = [0; MAXSIZE];
+    let mut buffer = vec![0; MAXSIZE];
     let mut size = 0;
 
     let mut results: Vec<DataPoint> = Vec::new();
 
     while START + size * 4 < MAXSIZE {
-        let diff = black_box(iteration(&clock, &mut buffer, black_box(START + size * 4)));
+        let diff = black_box(iteration(clock, &mut buffer, black_box(START + size * 4)));
 
         results.push(DataPoint {
             x: START + size * 4,
@@ -44,7 +46,7 @@ pub fn run_page_size_benchmark(clock: Clock) -> BenchmarkResult {
 }
 
 #[allow(unused_variables, unused_assignments)]
-fn iteration(clock: &Clock, buffer: &mut [i32; MAXSIZE], i: usize) -> i64 {
+fn iteration(clock: &impl Clock, buffer: &mut [i32], i: usize) -> i64 {
     let start = clock.read();
 
     let mut tmp = 0;
@@ -53,3 +55,31 @@ fn iteration(clock: &Clock, buffer: &mut [i32; MAXSIZE], i: usize) -> i64 {
     let end = clock.read();
     end - start
 }
+
+/*
+ * TESTS
+ */
+
+#[cfg(test)]
+mod tests {
+    use crate::clock::mock::MockClock;
+    use crate::worker::benchmarks::page_size::{run_page_size_benchmark, MAXSIZE, START};
+    use crate::worker::benchmarks::testing::parse_data_points;
+
+    #[test]
+    fn data_points_cover_buffer_in_steps() {
+        // prepare
+        let clock = MockClock::new(3);
+
+        // execute
+        let result = run_page_size_benchmark(&clock);
+
+        // assert
+        let points = parse_data_points(&result.result_json);
+        assert_eq!(points.len(), (START..MAXSIZE).step_by(4).count());
+        assert!(points
+            .iter()
+            .enumerate()
+            .all(|(i, &(x, y))| x as usize == START + i * 4 && y == 3));
+    }
+}
@@ -1,9 +1,11 @@
-use gloo_console::info;
+use log::info;
 use serde::{Deserialize, Serialize};
 use serde_json::json;
 
+use common::benchmark::BenchmarkType;
+
 use crate::clock::Clock;
-use crate::worker::{BenchmarkResult, BenchmarkType};
+use crate::worker::BenchmarkResult;
 
 #[derive(Debug, Serialize, Deserialize)]
 struct DataPoint {
@@ -11,7 +13,7 @@ struct DataPoint {
     y: i16,
 }
 
-pub fn run_single_performance_benchmark(clock: Clock) -> BenchmarkResult {
+pub fn run_single_performance_benchmark(clock: &impl Clock) -> BenchmarkResult {
     info!("Running single core performance benchmark");
 
     let total_starting_time = clock.read();
@@ -66,3 +68,32 @@ pub fn run_single_performance_benchmark(clock: Clock) -> BenchmarkResult {
         time: (clock.read() - total_starting_time) as f32,
     }
 }
+
+/*
+ * TESTS
+ */
+
+#[cfg(test)]
+mod tests {
+    use crate::clock::mock::MockClock;
+    use crate::worker::benchmarks::single_performance::run_single_performance_benchmark;
+    use crate::worker::benchmarks::testing::parse_data_points;
+
+    #[test]
+    fn data_points_count_clock_reads_in_every_interval() {
+        // prepare
+        let clock = MockClock::new(1);
+
+        // execute
+        let result = run_single_performance_benchmark(&clock);
+
+        // assert
+        let points = parse_data_points(&result.result_json);
+        assert_eq!(points.len(), 1500);
+        // the interval of 1000 ticks ends on the 1000th read, so the counter is incremented 999 times
+        assert!(points
+            .iter()
+            .enumerate()
+            .all(|(i, &(x, y))| x == i as i64 && y == 999));
+    }
+}
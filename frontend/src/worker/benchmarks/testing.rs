@@ -0,0 +1,20 @@
+use serde_json::Value;
+
+/// Parses the result of a benchmark, checking that it is a list of `{x, y}` integer points
+pub fn parse_data_points(result_json: &str) -> Vec<(i64, i64)> {
+    let value = serde_json::from_str::<Value>(result_json).expect("result is valid JSON");
+
+    value
+        .as_array()
+        .expect("result is an array")
+        .iter()
+        .map(|point| {
+            let point = point.as_object().expect("data point is an object");
+            assert_eq!(point.len(), 2, "data point has only x and y: {:?}", point);
+            (
+                point["x"].as_i64().expect("x is an integer"),
+                point["y"].as_i64().expect("y is an integer"),
+            )
+        })
+        .collect()
+}
@@ -1,12 +1,16 @@
-use gloo_console::info;
-use rand::seq::SliceRandom;
+use log::info;
+use rand::{Rng, SeedableRng};
+use rand_chacha::ChaCha8Rng;
 use serde::{Deserialize, Serialize};
 use serde_json::json;
 use std::hint::black_box;
 use std::mem::size_of;
 
+use common::benchmark::BenchmarkType;
+
 use crate::clock::Clock;
-use crate::worker::{BenchmarkResult, BenchmarkType};
+use crate::worker::benchmarks::chain::build_pointer_chain;
+use crate::worker::BenchmarkResult;
 
 const PAGE_SIZE: usize = 4 * 1024;
 
@@ -16,21 +20,36 @@ struct DataPoint {
     y: i64,
 }
 
-pub fn run_tlb_size_benchmark(clock: Clock) -> BenchmarkResult {
+pub fn run_tlb_size_benchmark(clock: &impl Clock, seed: u64) -> BenchmarkResult {
     info!("Running TLB size benchmark");
     let starting_time = clock.read();
-    let entries = (2..126).step_by(4);
-    let mut rand = rand::thread_rng();
-    let result: Vec<DataPoint> = entries
-        .into_iter()
-        .map(|s| {
-            let size = PAGE_SIZE * s / size_of::<usize>();
-            let mut list = vec![0; size];
-            let mut indices = (0..s).collect::<Vec<_>>();
-            indices.shuffle(&mut rand);
-
-            indices.windows(2).for_each(|w| list[w[0]] = w[1]);
-            list[indices[s - 1]] = indices[0];
+    let mut rand = ChaCha8Rng::seed_from_u64(seed);
+    let result = measure_tlb_entries(clock, &mut rand, &tlb_entries());
+
+    BenchmarkResult {
+        benchmark: BenchmarkType::TlbSize,
+        result_json: json!(result).to_string(),
+        time: (clock.read() - starting_time) as f32,
+    }
+}
+
+/// Numbers of pages accessed in the measurements
+fn tlb_entries() -> Vec<usize> {
+    (2..126).step_by(4).collect()
+}
+
+fn measure_tlb_entries(
+    clock: &impl Clock,
+    rand: &mut impl Rng,
+    entries: &[usize],
+) -> Vec<DataPoint> {
+    entries
+        .iter()
+        .map(|&s| {
+            // one element per page is part of the chain
+            let stride = PAGE_SIZE / size_of::<usize>();
+            let size = stride * s;
+            let list = build_pointer_chain(size, stride, rand);
 
             let mut p = 0;
 
@@ -44,17 +63,52 @@ pub fn run_tlb_size_benchmark(clock: Clock) -> BenchmarkResult {
             }
             let end = clock.read();
 
-            info!(s, end - start);
+            info!("TLB entries: {}, time: {}", s, end - start);
             DataPoint {
                 x: s,
                 y: end - start,
             }
         })
-        .collect::<Vec<_>>();
+        .collect::<Vec<_>>()
+}
 
-    BenchmarkResult {
-        benchmark: BenchmarkType::TlbSize,
-        result_json: json!(result).to_string(),
-        time: (clock.read() - starting_time) as f32,
+/*
+ * TESTS
+ */
+
+#[cfg(test)]
+mod tests {
+    use rand::SeedableRng;
+    use rand_chacha::ChaCha8Rng;
+    use serde_json::json;
+
+    use crate::clock::mock::MockClock;
+    use crate::worker::benchmarks::testing::parse_data_points;
+    use crate::worker::benchmarks::tlb_size::{measure_tlb_entries, tlb_entries};
+
+    #[test]
+    fn entries_match_configuration() {
+        // execute
+        let entries = tlb_entries();
+
+        // assert
+        assert_eq!(entries.len(), 31);
+        assert_eq!(entries.first(), Some(&2));
+        assert_eq!(entries.last(), Some(&122));
+        assert!(entries.windows(2).all(|w| w[1] - w[0] == 4));
+    }
+
+    #[test]
+    fn data_points_match_entries() {
+        // prepare
+        let clock = MockClock::new(10);
+        let mut rand = ChaCha8Rng::seed_from_u64(42);
+
+        // execute
+        let result = measure_tlb_entries(&clock, &mut rand, &[2, 6]);
+
+        // assert
+        let points = parse_data_points(&json!(result).to_string());
+        assert_eq!(points, vec![(2, 10), (6, 10)]);
     }
 }
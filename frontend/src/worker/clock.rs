@@ -4,9 +4,9 @@ use wasm_bindgen::prelude::*;
 use wasm_bindgen::JsValue;
 use web_sys::{Blob, BlobPropertyBag, MessageEvent, Url, Worker};
 
-use crate::clock::{Clock, CLOCK_MESSAGE_READY, CLOCK_MESSAGE_STARTED};
+use crate::clock::{CounterClock, CLOCK_MESSAGE_READY, CLOCK_MESSAGE_STARTED};
 
-pub fn start_clock_worker<F: Fn(Clock, Worker) + 'static>(
+pub fn start_clock_worker<F: Fn(CounterClock, Worker) + 'static>(
     page_origin: String,
     on_clock_started: F,
 ) -> Result<(), JsValue> {
@@ -16,16 +16,15 @@ pub fn start_clock_worker<F: Fn(Clock, Worker) + 'static>(
             .into()
     );
 
-    let blob = Blob::new_with_str_sequence_and_options(
-        &script,
-        BlobPropertyBag::new().type_("text/javascript"),
-    )?;
+    let blob_properties = BlobPropertyBag::new();
+    blob_properties.set_type("text/javascript");
+    let blob = Blob::new_with_str_sequence_and_options(&script, &blob_properties)?;
     let url = Url::create_object_url_with_blob(&blob)?;
 
     // Instantiate the worker
     info!("Instantiating worker");
     let worker = Worker::new(&url)?;
-    let clock = Clock::new();
+    let clock = CounterClock::new();
 
     // Handle the message from the worker, which means that it was successfully loaded
     // and send the shared array buffer to it
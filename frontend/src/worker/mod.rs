@@ -1,60 +1,27 @@
-use std::fmt::{Display, Formatter};
-
 use serde::{Deserialize, Serialize};
 use yew_agent::{HandlerId, Private, Worker, WorkerLink};
 
-use crate::clock::Clock;
-use crate::worker::benchmarks::cache_associativity::run_cache_associativity_benchmark;
-use crate::worker::benchmarks::cache_size::run_cache_size_benchmark;
-use crate::worker::benchmarks::page_size::run_page_size_benchmark;
-use crate::worker::benchmarks::single_performance::run_single_performance_benchmark;
-use crate::worker::benchmarks::tlb_size::run_tlb_size_benchmark;
+use common::benchmark::BenchmarkType;
+use common::dto::result::ClockMode;
+
+use crate::clock::PerformanceClock;
+use crate::worker::benchmarks::{run_benchmark, BenchmarkParameters};
 use crate::worker::clock::start_clock_worker;
 
-mod benchmarks;
+pub mod benchmarks;
 mod clock;
 
-#[derive(Serialize, Deserialize, Copy, Clone)]
-pub enum BenchmarkType {
-    PageSize,
-    CacheSize,
-    TlbSize,
-    SinglePerformance,
-    CacheAssociativity,
-}
-
-impl Display for BenchmarkType {
-    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
-        match self {
-            BenchmarkType::PageSize => write!(f, "Page size"),
-            BenchmarkType::CacheSize => write!(f, "Cache size"),
-            BenchmarkType::TlbSize => write!(f, "TLB size"),
-            BenchmarkType::SinglePerformance => write!(f, "Single core performance"),
-            BenchmarkType::CacheAssociativity => write!(f, "Cache associativity"),
-        }
-    }
-}
-
-impl BenchmarkType {
-    fn needs_clock(&self) -> bool {
-        match self {
-            BenchmarkType::PageSize => true,
-            BenchmarkType::CacheSize => true,
-            BenchmarkType::TlbSize => true,
-            BenchmarkType::SinglePerformance => true,
-            BenchmarkType::CacheAssociativity => true,
-        }
-    }
-}
-
 #[derive(Serialize, Deserialize)]
 pub struct BenchmarkInput {
     pub benchmark: BenchmarkType,
     /// Origin of the webpage, required by the spawned workers to load the scripts
     pub page_origin: String,
+    /// Clock selected by probing the browser capabilities
+    pub clock_mode: ClockMode,
+    pub parameters: BenchmarkParameters,
 }
 
-#[derive(Serialize, Deserialize)]
+#[derive(Serialize, Deserialize, Clone, Debug)]
 pub struct BenchmarkResult {
     pub benchmark: BenchmarkType,
     /// Workaround for broken (de)serialization of raw JSON value.
@@ -80,18 +47,18 @@ impl Worker for BenchmarkWorker {
     fn update(&mut self, _msg: Self::Message) {}
 
     fn handle_input(&mut self, msg: Self::Input, id: HandlerId) {
-        if msg.benchmark.needs_clock() {
+        if msg.benchmark.needs_clock() && msg.clock_mode == ClockMode::SharedCounter {
             let link = self.link.clone();
             // start the clock and run benchmark in the callback
             start_clock_worker(msg.page_origin, move |clock, clock_worker| {
-                let result = run_benchmark(msg.benchmark, Some(clock));
+                let result = run_benchmark(msg.benchmark, &msg.parameters, &clock);
                 clock_worker.terminate();
                 link.respond(id, result);
             })
             .expect("clock worker should start");
         } else {
-            // run benchmark directly
-            let result = run_benchmark(msg.benchmark, None);
+            // run benchmark directly, using the degraded clock
+            let result = run_benchmark(msg.benchmark, &msg.parameters, &PerformanceClock::new());
             self.link.respond(id, result);
         }
     }
@@ -100,13 +67,3 @@ impl Worker for BenchmarkWorker {
         "benchmark_worker.js"
     }
 }
-
-fn run_benchmark(benchmark: BenchmarkType, clock: Option<Clock>) -> BenchmarkResult {
-    match benchmark {
-        BenchmarkType::PageSize => run_page_size_benchmark(clock.unwrap()),
-        BenchmarkType::CacheSize => run_cache_size_benchmark(clock.unwrap()),
-        BenchmarkType::TlbSize => run_tlb_size_benchmark(clock.unwrap()),
-        BenchmarkType::SinglePerformance => run_single_performance_benchmark(clock.unwrap()),
-        BenchmarkType::CacheAssociativity => run_cache_associativity_benchmark(clock.unwrap()),
-    }
-}